@@ -0,0 +1,597 @@
+//! Derive macros for the FHIR traits of `octofhir-fhir-model`
+//!
+//! The macros implement the traits of `octofhir_fhir_model::fhir_traits` from
+//! the struct and enum definitions, so generated models cannot drift from
+//! their trait implementations. They are re-exported by `octofhir-fhir-model`
+//! under the `derive` feature, and the generated code refers to that crate.
+//!
+//! Options are given with `#[fhir(...)]` attributes:
+//!
+//! - `#[fhir(choice = "value")]` on a `ChoiceElement` enum sets the base name;
+//!   each variant becomes the field `value<Variant>` unless it is renamed with
+//!   `#[fhir(rename = "valueDateTime")]`
+//! - `#[fhir(resource_type = "Observation")]` on a `FhirResourceMetadata`
+//!   struct overrides the resource type (the struct name by default)
+//! - `#[fhir(choice)]` on a field marks a choice element whose type implements
+//!   `ChoiceElement`, and `ToFhirJson` to serialize it as `<base><Variant>`
+//! - `#[fhir(choice = "component.value")]` on the struct declares choice
+//!   elements of nested backbone elements
+//! - `#[fhir(nested)]` on a field serializes it through the `ToFhirJson` of
+//!   its type (inside `Option`, `Box` or `Vec`), for backbone elements and
+//!   data types that hold choice elements themselves
+//! - `#[fhir(rename = "...")]` on a field sets its FHIR name (the camel-cased
+//!   field name by default)
+//! - `#[fhir(value)]`, `#[fhir(extension)]`, `#[fhir(reference)]`,
+//!   `#[fhir(display)]`, `#[fhir(id)]` and `#[fhir(modifier_extension)]` mark
+//!   the fields backing `FhirPrimitive`, `FhirReference` and `BackboneElement`
+//!   when they are not named that way
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! #[derive(ChoiceElement, ToFhirJson, Serialize)]
+//! #[fhir(choice = "value")]
+//! enum ObservationValue {
+//!     Quantity(Quantity),
+//!     String(String),
+//!     #[fhir(rename = "valueDateTime")]
+//!     DateTime(String),
+//! }
+//!
+//! #[derive(FhirResourceMetadata, ToFhirJson, Serialize)]
+//! #[fhir(choice = "component.value")]
+//! struct Observation {
+//!     status: String,
+//!     #[fhir(choice)]
+//!     value: Option<ObservationValue>,
+//!     #[fhir(nested)]
+//!     component: Vec<ObservationComponent>,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{
+    Attribute, Data, DataEnum, DeriveInput, Error, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Result, Type, parse_macro_input,
+};
+
+/// Keys accepted in `#[fhir(...)]` attributes
+const KNOWN_KEYS: &[&str] = &[
+    "choice",
+    "nested",
+    "resource_type",
+    "rename",
+    "value",
+    "extension",
+    "reference",
+    "display",
+    "id",
+    "modifier_extension",
+];
+
+/// Derive `ChoiceElement` for an enum of choice variants
+#[proc_macro_derive(ChoiceElement, attributes(fhir))]
+pub fn derive_choice_element(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), choice_element)
+}
+
+/// Derive `FhirResourceMetadata` for a resource struct
+#[proc_macro_derive(FhirResourceMetadata, attributes(fhir))]
+pub fn derive_fhir_resource_metadata(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), resource_metadata)
+}
+
+/// Derive `ToFhirJson` for a struct or a choice enum
+///
+/// Structs become objects keyed by the FHIR field names, without empty fields;
+/// choice fields appear as `<base><Variant>`. Nested fields go through their
+/// own `ToFhirJson`; other field values, and types that are neither named
+/// structs nor choice enums, go through `Serialize`. Serialization errors are
+/// returned by `try_to_fhir_json`, and `to_fhir_json` panics on them.
+#[proc_macro_derive(ToFhirJson, attributes(fhir))]
+pub fn derive_to_fhir_json(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), to_fhir_json)
+}
+
+/// Derive `FhirPrimitive` from the `value` and `extension` fields
+#[proc_macro_derive(FhirPrimitive, attributes(fhir))]
+pub fn derive_fhir_primitive(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), fhir_primitive)
+}
+
+/// Derive `FhirReference` from the `reference` and `display` fields
+#[proc_macro_derive(FhirReference, attributes(fhir))]
+pub fn derive_fhir_reference(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), fhir_reference)
+}
+
+/// Derive `BackboneElement` from the `id` and `modifier_extension` fields
+#[proc_macro_derive(BackboneElement, attributes(fhir))]
+pub fn derive_backbone_element(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), backbone_element)
+}
+
+fn expand(input: DeriveInput, derive: fn(&DeriveInput) -> Result<TokenStream2>) -> TokenStream {
+    derive(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn choice_element(input: &DeriveInput) -> Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "ChoiceElement can only be derived for enums",
+        ));
+    };
+    let base = FhirAttrs::parse(&input.attrs)?
+        .value("choice")
+        .ok_or_else(|| {
+            Error::new_spanned(
+                &input.ident,
+                "missing #[fhir(choice = \"...\")] with the base name of the choice element",
+            )
+        })?;
+    let field_names = choice_field_names(data, &base.value())?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::octofhir_fhir_model::fhir_traits::ChoiceElement
+            for #name #ty_generics #where_clause
+        {
+            fn base_name() -> &'static str {
+                #base
+            }
+
+            fn possible_field_names() -> ::std::vec::Vec<&'static str> {
+                ::std::vec![#(#field_names),*]
+            }
+        }
+    })
+}
+
+fn resource_metadata(input: &DeriveInput) -> Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "FhirResourceMetadata can only be derived for structs",
+        ));
+    };
+    let attrs = FhirAttrs::parse(&input.attrs)?;
+    let resource_type = attrs
+        .value("resource_type")
+        .unwrap_or_else(|| LitStr::new(&input.ident.unraw().to_string(), input.ident.span()));
+
+    let mut choice_fields = Vec::new();
+    let mut choice_types = Vec::new();
+    for field in &data.fields {
+        let field_attrs = FhirAttrs::parse(&field.attrs)?;
+        if !field_attrs.has("choice") {
+            continue;
+        }
+        if field_attrs.value("choice").is_some() {
+            return Err(Error::new_spanned(
+                &field.ident,
+                "choice fields take the plain #[fhir(choice)] marker",
+            ));
+        }
+        choice_fields.push(fhir_field_name(field.ident.as_ref(), &field_attrs)?);
+        choice_types.push(inner_type(&field.ty));
+    }
+    let nested_choices = attrs.values("choice");
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::octofhir_fhir_model::fhir_traits::FhirResourceMetadata
+            for #name #ty_generics #where_clause
+        {
+            fn choice_elements() -> &'static [&'static str] {
+                &[#(#choice_fields,)* #(#nested_choices),*]
+            }
+
+            fn resource_type() -> &'static str {
+                #resource_type
+            }
+
+            fn get_choice_field_names(base_name: &str) -> ::std::vec::Vec<::std::string::String> {
+                match base_name {
+                    #(
+                        #choice_fields => {
+                            <#choice_types as ::octofhir_fhir_model::fhir_traits::ChoiceElement>
+                                ::possible_field_names()
+                                .into_iter()
+                                .map(::std::string::String::from)
+                                .collect()
+                        }
+                    )*
+                    _ => ::std::vec::Vec::new(),
+                }
+            }
+        }
+    })
+}
+
+fn to_fhir_json(input: &DeriveInput) -> Result<TokenStream2> {
+    let body = match &input.data {
+        Data::Struct(data) if matches!(data.fields, Fields::Named(_)) => struct_json(&data.fields)?,
+        Data::Enum(data) => match FhirAttrs::parse(&input.attrs)?.value("choice") {
+            Some(base) => choice_json(data, &base.value())?,
+            None => serde_value(),
+        },
+        _ => serde_value(),
+    };
+
+    let name = &input.ident;
+    let message = format!("{} cannot be serialized to JSON", name.unraw());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::octofhir_fhir_model::fhir_traits::ToFhirJson
+            for #name #ty_generics #where_clause
+        {
+            fn to_fhir_json(&self) -> ::octofhir_fhir_model::__private::serde_json::Value {
+                match ::octofhir_fhir_model::fhir_traits::ToFhirJson::try_to_fhir_json(self) {
+                    ::std::result::Result::Ok(value) => value,
+                    ::std::result::Result::Err(error) => ::std::panic!("{}: {}", #message, error),
+                }
+            }
+
+            fn try_to_fhir_json(
+                &self,
+            ) -> ::octofhir_fhir_model::__private::serde_json::Result<
+                ::octofhir_fhir_model::__private::serde_json::Value,
+            > {
+                #body
+            }
+        }
+    })
+}
+
+/// Object with the FHIR names of the fields, leaving out empty ones
+///
+/// Choice fields are merged in through their own `ToFhirJson`, which yields
+/// the `<base><Variant>` member of the value they hold.
+fn struct_json(fields: &Fields) -> Result<TokenStream2> {
+    let mut members = Vec::new();
+    for field in fields {
+        let attrs = FhirAttrs::parse(&field.attrs)?;
+        let ident = &field.ident;
+        if attrs.has("choice") && attrs.has("nested") {
+            return Err(Error::new_spanned(
+                ident,
+                "a field is either #[fhir(choice)] or #[fhir(nested)]",
+            ));
+        }
+        if attrs.has("choice") {
+            members.push(quote! {
+                if let ::octofhir_fhir_model::__private::serde_json::Value::Object(choice) =
+                    ::octofhir_fhir_model::fhir_traits::ToFhirJson::try_to_fhir_json(&self.#ident)?
+                {
+                    object.extend(choice);
+                }
+            });
+        } else {
+            let name = fhir_field_name(ident.as_ref(), &attrs)?;
+            let value = if attrs.has("nested") {
+                quote! {
+                    ::octofhir_fhir_model::fhir_traits::ToFhirJson::try_to_fhir_json(&self.#ident)?
+                }
+            } else {
+                serde_json(quote!(&self.#ident))
+            };
+            members.push(quote! {
+                if let ::std::option::Option::Some(value) =
+                    ::octofhir_fhir_model::__private::prune(#value)
+                {
+                    object.insert(::std::string::String::from(#name), value);
+                }
+            });
+        }
+    }
+    Ok(quote! {
+        let mut object = ::octofhir_fhir_model::__private::serde_json::Map::new();
+        #(#members)*
+        ::std::result::Result::Ok(::octofhir_fhir_model::__private::serde_json::Value::Object(object))
+    })
+}
+
+/// Object with the single `<base><Variant>` member of a choice enum
+fn choice_json(data: &DataEnum, base_name: &str) -> Result<TokenStream2> {
+    let field_names = choice_field_names(data, base_name)?;
+    let mut arms = Vec::new();
+    for (variant, field_name) in data.variants.iter().zip(&field_names) {
+        if !matches!(&variant.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1) {
+            return Err(Error::new_spanned(
+                &variant.ident,
+                "ToFhirJson choice variants must hold exactly one value",
+            ));
+        }
+        let ident = &variant.ident;
+        let value = serde_json(quote!(value));
+        arms.push(quote! {
+            Self::#ident(value) => (#field_name, #value),
+        });
+    }
+    Ok(quote! {
+        let (name, value) = match self {
+            #(#arms)*
+        };
+        let mut object = ::octofhir_fhir_model::__private::serde_json::Map::new();
+        if let ::std::option::Option::Some(value) = ::octofhir_fhir_model::__private::prune(value) {
+            object.insert(::std::string::String::from(name), value);
+        }
+        ::std::result::Result::Ok(::octofhir_fhir_model::__private::serde_json::Value::Object(object))
+    })
+}
+
+/// Serialize the whole value through its `Serialize` implementation
+fn serde_value() -> TokenStream2 {
+    quote! {
+        ::octofhir_fhir_model::__private::serde_json::to_value(self)
+    }
+}
+
+/// Serialize a value to JSON through its `Serialize` implementation, returning
+/// early on errors
+fn serde_json(value: TokenStream2) -> TokenStream2 {
+    quote! {
+        ::octofhir_fhir_model::__private::serde_json::to_value(#value)?
+    }
+}
+
+fn fhir_primitive(input: &DeriveInput) -> Result<TokenStream2> {
+    let value = role_field(input, "FhirPrimitive", "value")?;
+    let extension = role_field(input, "FhirPrimitive", "extension")?;
+    let extensions = json_values(quote!(self.#extension));
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::octofhir_fhir_model::fhir_traits::FhirPrimitive
+            for #name #ty_generics #where_clause
+        {
+            fn primitive_value(
+                &self,
+            ) -> ::std::option::Option<::octofhir_fhir_model::__private::serde_json::Value> {
+                self.#value
+                    .as_ref()
+                    .and_then(|value| {
+                        ::octofhir_fhir_model::__private::serde_json::to_value(value).ok()
+                    })
+            }
+
+            fn has_extensions(&self) -> bool {
+                self.#extension
+                    .as_ref()
+                    .is_some_and(|extensions| !extensions.is_empty())
+            }
+
+            fn get_extensions(
+                &self,
+            ) -> ::std::option::Option<
+                ::std::vec::Vec<::octofhir_fhir_model::__private::serde_json::Value>,
+            > {
+                #extensions
+            }
+        }
+    })
+}
+
+fn fhir_reference(input: &DeriveInput) -> Result<TokenStream2> {
+    let reference = role_field(input, "FhirReference", "reference")?;
+    let display = role_field(input, "FhirReference", "display")?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::octofhir_fhir_model::fhir_traits::FhirReference
+            for #name #ty_generics #where_clause
+        {
+            fn reference(&self) -> ::std::option::Option<&str> {
+                self.#reference.as_deref()
+            }
+
+            fn display(&self) -> ::std::option::Option<&str> {
+                self.#display.as_deref()
+            }
+        }
+    })
+}
+
+fn backbone_element(input: &DeriveInput) -> Result<TokenStream2> {
+    let id = role_field(input, "BackboneElement", "id")?;
+    let modifier_extension = role_field(input, "BackboneElement", "modifier_extension")?;
+    let extensions = json_values(quote!(self.#modifier_extension));
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::octofhir_fhir_model::fhir_traits::BackboneElement
+            for #name #ty_generics #where_clause
+        {
+            fn element_id(&self) -> ::std::option::Option<&str> {
+                self.#id.as_deref()
+            }
+
+            fn modifier_extensions(
+                &self,
+            ) -> ::std::option::Option<
+                ::std::vec::Vec<::octofhir_fhir_model::__private::serde_json::Value>,
+            > {
+                #extensions
+            }
+        }
+    })
+}
+
+/// Convert an optional, possibly empty list of extensions to JSON values
+fn json_values(field: TokenStream2) -> TokenStream2 {
+    quote! {
+        #field
+            .as_ref()
+            .filter(|extensions| !extensions.is_empty())
+            .map(|extensions| {
+                extensions
+                    .iter()
+                    .filter_map(|extension| {
+                        ::octofhir_fhir_model::__private::serde_json::to_value(extension).ok()
+                    })
+                    .collect()
+            })
+    }
+}
+
+/// Field names of the variants of a choice enum
+fn choice_field_names(data: &DataEnum, base_name: &str) -> Result<Vec<String>> {
+    let mut field_names: Vec<String> = Vec::new();
+    for variant in &data.variants {
+        let field_name = match FhirAttrs::parse(&variant.attrs)?.value("rename") {
+            Some(rename) if !rename.value().starts_with(base_name) => {
+                return Err(Error::new_spanned(
+                    rename,
+                    format!("choice field names must start with the base name '{base_name}'"),
+                ));
+            }
+            Some(rename) => rename.value(),
+            None => format!("{base_name}{}", variant.ident.unraw()),
+        };
+        if field_names.contains(&field_name) {
+            return Err(Error::new_spanned(
+                &variant.ident,
+                format!("duplicate choice field name '{field_name}'"),
+            ));
+        }
+        field_names.push(field_name);
+    }
+    Ok(field_names)
+}
+
+/// Field marked with `#[fhir(<role>)]`, or else the field named after the role
+fn role_field<'a>(input: &'a DeriveInput, derive: &str, role: &str) -> Result<&'a Ident> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!("{derive} can only be derived for structs"),
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!("{derive} can only be derived for structs with named fields"),
+        ));
+    };
+
+    let mut named = None;
+    for field in &fields.named {
+        let Some(ident) = field.ident.as_ref() else {
+            continue;
+        };
+        if FhirAttrs::parse(&field.attrs)?.has(role) {
+            return Ok(ident);
+        }
+        if ident.unraw() == role {
+            named = Some(ident);
+        }
+    }
+    named.ok_or_else(|| {
+        Error::new_spanned(
+            &input.ident,
+            format!("{derive} needs a `{role}` field or a field marked #[fhir({role})]"),
+        )
+    })
+}
+
+/// FHIR name of a field: its `rename` or the camel-cased field name
+fn fhir_field_name(ident: Option<&Ident>, attrs: &FhirAttrs) -> Result<LitStr> {
+    if let Some(rename) = attrs.value("rename") {
+        return Ok(rename);
+    }
+    let Some(ident) = ident else {
+        return Err(Error::new(
+            Span::call_site(),
+            "tuple fields need #[fhir(rename = \"...\")]",
+        ));
+    };
+
+    let mut name = String::new();
+    let mut upper = false;
+    for c in ident.unraw().to_string().chars() {
+        match c {
+            '_' => upper = !name.is_empty(),
+            c if upper => {
+                name.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => name.push(c),
+        }
+    }
+    Ok(LitStr::new(&name, ident.span()))
+}
+
+/// Type inside `Option`, `Box` and `Vec` wrappers
+fn inner_type(ty: &Type) -> &Type {
+    if let Type::Path(path) = ty
+        && path.qself.is_none()
+        && let Some(segment) = path.path.segments.last()
+        && matches!(segment.ident.to_string().as_str(), "Option" | "Box" | "Vec")
+        && let PathArguments::AngleBracketed(arguments) = &segment.arguments
+        && let Some(GenericArgument::Type(inner)) = arguments.args.first()
+    {
+        return inner_type(inner);
+    }
+    ty
+}
+
+/// Options collected from `#[fhir(...)]` attributes
+struct FhirAttrs {
+    entries: Vec<(String, Option<LitStr>)>,
+}
+
+impl FhirAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut entries = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("fhir")) {
+            attr.parse_nested_meta(|meta| {
+                let key = meta
+                    .path
+                    .get_ident()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                if !KNOWN_KEYS.contains(&key.as_str()) {
+                    return Err(meta.error(format!(
+                        "unknown fhir attribute, expected one of: {}",
+                        KNOWN_KEYS.join(", ")
+                    )));
+                }
+                let value = if meta.input.peek(syn::Token![=]) {
+                    Some(meta.value()?.parse::<LitStr>()?)
+                } else {
+                    None
+                };
+                entries.push((key, value));
+                Ok(())
+            })?;
+        }
+        Ok(Self { entries })
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _)| k == key)
+    }
+
+    fn value(&self, key: &str) -> Option<LitStr> {
+        self.values(key).into_iter().next()
+    }
+
+    fn values(&self, key: &str) -> Vec<LitStr> {
+        self.entries
+            .iter()
+            .filter(|(k, _)| k == key)
+            .filter_map(|(_, value)| value.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,652 @@
+//! Caching decorator for ModelProvider
+//!
+//! This module provides `CachedModelProvider`, which wraps any `ModelProvider`
+//! and caches its async lookups with moka. It is meant for remote or
+//! database-backed providers that would otherwise be queried for the same
+//! element types over and over during evaluation.
+
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Cache configuration for model provider operations
+///
+/// Controls TTL and maximum size for each type of cached operation.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelCacheConfig {
+    /// TTL for `get_type` and `get_profile_type` entries (default: 1 hour)
+    pub type_ttl: Duration,
+    /// Maximum entries in the type cache (default: 10,000)
+    pub type_max_size: u64,
+    /// TTL for `get_element_type` entries (default: 1 hour)
+    pub element_type_ttl: Duration,
+    /// Maximum entries in the element type cache (default: 100,000)
+    pub element_type_max_size: u64,
+    /// TTL for `get_elements` and `get_choice_types` entries (default: 1 hour)
+    pub elements_ttl: Duration,
+    /// Maximum entries in the elements cache (default: 5,000)
+    pub elements_max_size: u64,
+    /// TTL for resource, complex, primitive and logical type listings (default: 24 hours)
+    pub listing_ttl: Duration,
+}
+
+impl Default for ModelCacheConfig {
+    fn default() -> Self {
+        Self {
+            type_ttl: Duration::from_secs(3600), // 1 hour
+            type_max_size: 10_000,
+            element_type_ttl: Duration::from_secs(3600), // 1 hour
+            element_type_max_size: 100_000,
+            elements_ttl: Duration::from_secs(3600), // 1 hour
+            elements_max_size: 5_000,
+            listing_ttl: Duration::from_secs(86400), // 24 hours
+        }
+    }
+}
+
+impl ModelCacheConfig {
+    /// Set type cache TTL
+    pub fn with_type_ttl(mut self, ttl: Duration) -> Self {
+        self.type_ttl = ttl;
+        self
+    }
+
+    /// Set type cache max size
+    pub fn with_type_max_size(mut self, size: u64) -> Self {
+        self.type_max_size = size;
+        self
+    }
+
+    /// Set element type cache TTL
+    pub fn with_element_type_ttl(mut self, ttl: Duration) -> Self {
+        self.element_type_ttl = ttl;
+        self
+    }
+
+    /// Set element type cache max size
+    pub fn with_element_type_max_size(mut self, size: u64) -> Self {
+        self.element_type_max_size = size;
+        self
+    }
+
+    /// Set elements cache TTL
+    pub fn with_elements_ttl(mut self, ttl: Duration) -> Self {
+        self.elements_ttl = ttl;
+        self
+    }
+
+    /// Set elements cache max size
+    pub fn with_elements_max_size(mut self, size: u64) -> Self {
+        self.elements_max_size = size;
+        self
+    }
+
+    /// Set type listing cache TTL
+    pub fn with_listing_ttl(mut self, ttl: Duration) -> Self {
+        self.listing_ttl = ttl;
+        self
+    }
+}
+
+/// Cache statistics for model provider
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModelCacheStats {
+    /// Number of entries in the type cache
+    pub type_entries: u64,
+    /// Number of entries in the element type cache
+    pub element_type_entries: u64,
+    /// Number of entries in the elements cache (including choice types)
+    pub elements_entries: u64,
+    /// Number of cached type listings
+    pub listing_entries: u64,
+}
+
+// ============================================================================
+// CachedModelProvider (requires "caching" feature)
+// ============================================================================
+
+#[cfg(feature = "caching")]
+use async_trait::async_trait;
+#[cfg(feature = "caching")]
+use moka::future::Cache;
+
+#[cfg(feature = "caching")]
+use crate::error::Result;
+#[cfg(feature = "caching")]
+use crate::interner::Symbol;
+#[cfg(feature = "caching")]
+use crate::provider::{
+    ChoiceTypeInfo, ElementInfo, ExtensionDefinition, FhirVersion, ModelProvider,
+    ProfileElementInfo, SliceDefinition, TypeInfo,
+};
+
+/// Key for type cache
+#[cfg(feature = "caching")]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum TypeCacheKey {
+    Type(String),
+    Profile(String),
+}
+
+/// Whether a looked up name is a type or one of its backbone elements, with or
+/// without a namespace (`Patient`, `FHIR.Patient`, `Patient.contact`)
+#[cfg(feature = "caching")]
+fn names_type(key: &str, type_name: &str) -> bool {
+    let names = |key: &str| {
+        key.strip_prefix(type_name)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    };
+    names(key) || key.split_once('.').is_some_and(|(_, rest)| names(rest))
+}
+
+/// Key for element type cache: the parent's namespace and name, and the property
+///
+/// Parents are keyed by name rather than by their whole `TypeInfo`, so lookups
+/// only clone symbols and the property name.
+#[cfg(feature = "caching")]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct ElementTypeKey {
+    namespace: Option<Symbol>,
+    parent: Symbol,
+    property: String,
+}
+
+/// Key for elements cache
+#[cfg(feature = "caching")]
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+enum ElementsCacheKey {
+    Elements(String),
+    ChoiceTypes(String, String),
+}
+
+#[cfg(feature = "caching")]
+impl ElementsCacheKey {
+    fn type_name(&self) -> &str {
+        match self {
+            ElementsCacheKey::Elements(type_name) => type_name,
+            ElementsCacheKey::ChoiceTypes(type_name, _) => type_name,
+        }
+    }
+}
+
+/// Cached elements or choice types
+#[cfg(feature = "caching")]
+#[derive(Debug, Clone)]
+enum ElementsCacheValue {
+    Elements(Vec<ElementInfo>),
+    ChoiceTypes(Option<Vec<ChoiceTypeInfo>>),
+}
+
+/// Type listings
+#[cfg(feature = "caching")]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum TypeListing {
+    Resource,
+    Complex,
+    Primitive,
+    Logical,
+}
+
+#[cfg(feature = "caching")]
+impl TypeListing {
+    /// Number of listings, so all of them fit in the listing cache
+    const COUNT: u64 = 4;
+}
+
+/// Cached wrapper around any ModelProvider
+///
+/// Caches `get_type`, `get_element_type`, `get_elements`, `get_choice_types`,
+/// `get_profile_type` and the type listings, including lookups that found
+/// nothing. Errors are never cached. Synchronous methods and profile element
+/// queries are passed straight through.
+///
+/// # Example
+///
+/// ```ignore
+/// use octofhir_fhir_model::{CachedModelProvider, ModelCacheConfig};
+///
+/// let cached = CachedModelProvider::with_default_config(remote_provider);
+/// let patient = cached.get_type("Patient").await?;
+///
+/// // After reloading packages behind the inner provider
+/// cached.clear_cache();
+/// ```
+#[cfg(feature = "caching")]
+pub struct CachedModelProvider<P: ModelProvider> {
+    inner: P,
+    type_cache: Cache<TypeCacheKey, Option<TypeInfo>>,
+    element_type_cache: Cache<ElementTypeKey, Option<TypeInfo>>,
+    elements_cache: Cache<ElementsCacheKey, ElementsCacheValue>,
+    listing_cache: Cache<TypeListing, Vec<String>>,
+}
+
+#[cfg(feature = "caching")]
+impl<P: ModelProvider> std::fmt::Debug for CachedModelProvider<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedModelProvider")
+            .field("inner", &self.inner)
+            .field("type_entries", &self.type_cache.entry_count())
+            .field(
+                "element_type_entries",
+                &self.element_type_cache.entry_count(),
+            )
+            .field("elements_entries", &self.elements_cache.entry_count())
+            .field("listing_entries", &self.listing_cache.entry_count())
+            .finish()
+    }
+}
+
+#[cfg(feature = "caching")]
+impl<P: ModelProvider> CachedModelProvider<P> {
+    /// Create a new cached provider with custom configuration
+    pub fn new(inner: P, config: ModelCacheConfig) -> Self {
+        let type_cache = Cache::builder()
+            .max_capacity(config.type_max_size)
+            .time_to_live(config.type_ttl)
+            .support_invalidation_closures()
+            .build();
+
+        let element_type_cache = Cache::builder()
+            .max_capacity(config.element_type_max_size)
+            .time_to_live(config.element_type_ttl)
+            .support_invalidation_closures()
+            .build();
+
+        let elements_cache = Cache::builder()
+            .max_capacity(config.elements_max_size)
+            .time_to_live(config.elements_ttl)
+            .support_invalidation_closures()
+            .build();
+
+        let listing_cache = Cache::builder()
+            .max_capacity(TypeListing::COUNT)
+            .time_to_live(config.listing_ttl)
+            .build();
+
+        Self {
+            inner,
+            type_cache,
+            element_type_cache,
+            elements_cache,
+            listing_cache,
+        }
+    }
+
+    /// Create a new cached provider with default configuration
+    pub fn with_default_config(inner: P) -> Self {
+        Self::new(inner, ModelCacheConfig::default())
+    }
+
+    /// Get cache statistics
+    pub fn cache_stats(&self) -> ModelCacheStats {
+        ModelCacheStats {
+            type_entries: self.type_cache.entry_count(),
+            element_type_entries: self.element_type_cache.entry_count(),
+            elements_entries: self.elements_cache.entry_count(),
+            listing_entries: self.listing_cache.entry_count(),
+        }
+    }
+
+    /// Clear all caches (e.g. after the inner provider reloaded its packages)
+    pub fn clear_cache(&self) {
+        self.type_cache.invalidate_all();
+        self.element_type_cache.invalidate_all();
+        self.elements_cache.invalidate_all();
+        self.listing_cache.invalidate_all();
+    }
+
+    /// Invalidate everything cached about a type, its elements and the type listings
+    ///
+    /// This covers the type under any namespace, its backbone elements, the
+    /// profiles constrained on it and profile lookups that found nothing, which
+    /// a reload may have added.
+    pub async fn invalidate_type(&self, type_name: &str) {
+        let name = type_name.to_string();
+        let backbone_prefix = format!("{type_name}.");
+        let is_cached_type = move |type_info: &TypeInfo| {
+            let found = type_info.name.as_deref().unwrap_or(&type_info.type_name);
+            found == name || found.starts_with(&backbone_prefix)
+        };
+
+        let name = type_name.to_string();
+        // Invalidation closures are enabled for these caches, so this cannot fail
+        let _ = self
+            .type_cache
+            .invalidate_entries_if(move |key, value| match key {
+                TypeCacheKey::Type(key) => {
+                    names_type(key, &name) || value.as_ref().is_some_and(&is_cached_type)
+                }
+                TypeCacheKey::Profile(_) => value.as_ref().is_none_or(&is_cached_type),
+            });
+
+        let name = type_name.to_string();
+        let backbone_prefix = format!("{type_name}.");
+        let _ = self
+            .element_type_cache
+            .invalidate_entries_if(move |key, _| {
+                key.parent == name.as_str() || key.parent.starts_with(&backbone_prefix)
+            });
+        let name = type_name.to_string();
+        let _ = self
+            .elements_cache
+            .invalidate_entries_if(move |key, _| names_type(key.type_name(), &name));
+
+        self.listing_cache.invalidate_all();
+    }
+
+    /// Sync pending cache operations (moka is eventually consistent)
+    pub async fn sync(&self) {
+        self.type_cache.run_pending_tasks().await;
+        self.element_type_cache.run_pending_tasks().await;
+        self.elements_cache.run_pending_tasks().await;
+        self.listing_cache.run_pending_tasks().await;
+    }
+
+    /// Get reference to the inner provider
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    async fn listing(&self, listing: TypeListing) -> Result<Vec<String>> {
+        if let Some(cached) = self.listing_cache.get(&listing).await {
+            return Ok(cached);
+        }
+
+        let result = match listing {
+            TypeListing::Resource => self.inner.get_resource_types().await?,
+            TypeListing::Complex => self.inner.get_complex_types().await?,
+            TypeListing::Primitive => self.inner.get_primitive_types().await?,
+            TypeListing::Logical => self.inner.get_logical_types().await?,
+        };
+        self.listing_cache.insert(listing, result.clone()).await;
+
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "caching")]
+#[async_trait]
+impl<P: ModelProvider + 'static> ModelProvider for CachedModelProvider<P> {
+    async fn get_type(&self, type_name: &str) -> Result<Option<TypeInfo>> {
+        let key = TypeCacheKey::Type(type_name.to_string());
+        if let Some(cached) = self.type_cache.get(&key).await {
+            return Ok(cached);
+        }
+
+        let result = self.inner.get_type(type_name).await?;
+        self.type_cache.insert(key, result.clone()).await;
+
+        Ok(result)
+    }
+
+    async fn get_element_type(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Result<Option<TypeInfo>> {
+        let key = ElementTypeKey {
+            namespace: parent_type.namespace.clone(),
+            parent: parent_type
+                .name
+                .clone()
+                .unwrap_or_else(|| parent_type.type_name.clone()),
+            property: property_name.to_string(),
+        };
+        if let Some(cached) = self.element_type_cache.get(&key).await {
+            return Ok(cached);
+        }
+
+        let result = self
+            .inner
+            .get_element_type(parent_type, property_name)
+            .await?;
+        self.element_type_cache.insert(key, result.clone()).await;
+
+        Ok(result)
+    }
+
+    fn of_type(&self, type_info: &TypeInfo, target_type: &str) -> Option<TypeInfo> {
+        self.inner.of_type(type_info, target_type)
+    }
+
+    fn get_element_names(&self, parent_type: &TypeInfo) -> Vec<String> {
+        self.inner.get_element_names(parent_type)
+    }
+
+    async fn get_children_type(&self, parent_type: &TypeInfo) -> Result<Option<TypeInfo>> {
+        self.inner.get_children_type(parent_type).await
+    }
+
+    async fn get_elements(&self, type_name: &str) -> Result<Vec<ElementInfo>> {
+        let key = ElementsCacheKey::Elements(type_name.to_string());
+        if let Some(ElementsCacheValue::Elements(cached)) = self.elements_cache.get(&key).await {
+            return Ok(cached);
+        }
+
+        let result = self.inner.get_elements(type_name).await?;
+        self.elements_cache
+            .insert(key, ElementsCacheValue::Elements(result.clone()))
+            .await;
+
+        Ok(result)
+    }
+
+    async fn get_resource_types(&self) -> Result<Vec<String>> {
+        self.listing(TypeListing::Resource).await
+    }
+
+    async fn get_complex_types(&self) -> Result<Vec<String>> {
+        self.listing(TypeListing::Complex).await
+    }
+
+    async fn get_primitive_types(&self) -> Result<Vec<String>> {
+        self.listing(TypeListing::Primitive).await
+    }
+
+    async fn get_logical_types(&self) -> Result<Vec<String>> {
+        self.listing(TypeListing::Logical).await
+    }
+
+    async fn resource_type_exists(&self, resource_type: &str) -> Result<bool> {
+        self.inner.resource_type_exists(resource_type).await
+    }
+
+    async fn get_fhir_version(&self) -> Result<FhirVersion> {
+        self.inner.get_fhir_version().await
+    }
+
+    fn is_type_derived_from(&self, derived_type: &str, base_type: &str) -> bool {
+        self.inner.is_type_derived_from(derived_type, base_type)
+    }
+
+    fn get_type_ancestors(&self, type_name: &str) -> Vec<String> {
+        self.inner.get_type_ancestors(type_name)
+    }
+
+    fn get_direct_subtypes(&self, type_name: &str) -> Vec<String> {
+        self.inner.get_direct_subtypes(type_name)
+    }
+
+    fn get_concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        self.inner.get_concrete_subtypes(type_name)
+    }
+
+    fn get_common_supertype(&self, type_names: &[&str]) -> Option<String> {
+        self.inner.get_common_supertype(type_names)
+    }
+
+    async fn get_choice_types(
+        &self,
+        parent_type: &str,
+        property_name: &str,
+    ) -> Result<Option<Vec<ChoiceTypeInfo>>> {
+        let key = ElementsCacheKey::ChoiceTypes(parent_type.to_string(), property_name.to_string());
+        if let Some(ElementsCacheValue::ChoiceTypes(cached)) = self.elements_cache.get(&key).await {
+            return Ok(cached);
+        }
+
+        let result = self
+            .inner
+            .get_choice_types(parent_type, property_name)
+            .await?;
+        self.elements_cache
+            .insert(key, ElementsCacheValue::ChoiceTypes(result.clone()))
+            .await;
+
+        Ok(result)
+    }
+
+    async fn get_union_types(&self, type_info: &TypeInfo) -> Result<Option<Vec<TypeInfo>>> {
+        self.inner.get_union_types(type_info).await
+    }
+
+    fn is_union_type(&self, type_info: &TypeInfo) -> bool {
+        self.inner.is_union_type(type_info)
+    }
+
+    async fn get_profile_type(&self, profile_url: &str) -> Result<Option<TypeInfo>> {
+        let key = TypeCacheKey::Profile(profile_url.to_string());
+        if let Some(cached) = self.type_cache.get(&key).await {
+            return Ok(cached);
+        }
+
+        let result = self.inner.get_profile_type(profile_url).await?;
+        self.type_cache.insert(key, result.clone()).await;
+
+        Ok(result)
+    }
+
+    async fn get_element_type_in_profile(
+        &self,
+        profile_url: &str,
+        parent_path: &str,
+        property_name: &str,
+    ) -> Result<Option<ProfileElementInfo>> {
+        self.inner
+            .get_element_type_in_profile(profile_url, parent_path, property_name)
+            .await
+    }
+
+    async fn get_slices(
+        &self,
+        profile_url: &str,
+        element_path: &str,
+    ) -> Result<Vec<SliceDefinition>> {
+        self.inner.get_slices(profile_url, element_path).await
+    }
+
+    async fn get_extension_definition(
+        &self,
+        extension_url: &str,
+    ) -> Result<Option<ExtensionDefinition>> {
+        self.inner.get_extension_definition(extension_url).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_config_builder() {
+        let config = ModelCacheConfig::default()
+            .with_element_type_ttl(Duration::from_secs(60))
+            .with_element_type_max_size(500);
+
+        assert_eq!(config.type_ttl, Duration::from_secs(3600));
+        assert_eq!(config.element_type_ttl, Duration::from_secs(60));
+        assert_eq!(config.element_type_max_size, 500);
+        assert_eq!(config.listing_ttl, Duration::from_secs(86400));
+    }
+
+    #[cfg(feature = "caching")]
+    #[tokio::test]
+    async fn test_cached_provider_caches_and_invalidates() {
+        use crate::package_provider::PackageModelProvider;
+        use crate::test_support::fixture_definitions;
+
+        let inner =
+            PackageModelProvider::from_structure_definitions(&fixture_definitions()).unwrap();
+        let cached = CachedModelProvider::with_default_config(inner);
+
+        let patient = cached.get_type("Patient").await.unwrap().unwrap();
+        let name = cached.get_element_type(&patient, "name").await.unwrap();
+        assert_eq!(name.unwrap().name.as_deref(), Some("HumanName"));
+        assert!(cached.get_type("Unknown").await.unwrap().is_none());
+        cached.get_elements("Patient").await.unwrap();
+        cached
+            .get_choice_types("Patient", "deceased")
+            .await
+            .unwrap();
+        assert_eq!(cached.get_primitive_types().await.unwrap(), vec!["string"]);
+        cached.sync().await;
+
+        let stats = cached.cache_stats();
+        assert_eq!(stats.type_entries, 2);
+        assert_eq!(stats.element_type_entries, 1);
+        assert_eq!(stats.elements_entries, 2);
+        assert_eq!(stats.listing_entries, 1);
+
+        cached.invalidate_type("Patient").await;
+        cached.sync().await;
+        let stats = cached.cache_stats();
+        assert_eq!(stats.type_entries, 1);
+        assert_eq!(stats.element_type_entries, 0);
+        assert_eq!(stats.elements_entries, 0);
+        assert_eq!(stats.listing_entries, 0);
+
+        cached.clear_cache();
+        cached.sync().await;
+        assert_eq!(cached.cache_stats().type_entries, 0);
+    }
+
+    #[cfg(feature = "caching")]
+    #[tokio::test]
+    async fn test_invalidate_type_covers_qualified_and_profile_entries() {
+        use crate::package_provider::{CORE_STRUCTURE_DEFINITION_PREFIX, PackageModelProvider};
+        use crate::test_support::fixture_definitions;
+        use serde_json::json;
+
+        let mut definitions = fixture_definitions();
+        definitions.push(json!({
+            "resourceType": "StructureDefinition",
+            "url": "http://example.org/StructureDefinition/named-patient",
+            "name": "NamedPatient",
+            "kind": "resource",
+            "abstract": false,
+            "type": "Patient",
+            "derivation": "constraint",
+            "baseDefinition": format!("{CORE_STRUCTURE_DEFINITION_PREFIX}Patient"),
+            "differential": {"element": [{"id": "Patient.name", "path": "Patient.name", "min": 1}]}
+        }));
+        let inner = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+        let cached = CachedModelProvider::with_default_config(inner);
+
+        let patient = cached.get_type("FHIR.Patient").await.unwrap().unwrap();
+        cached.get_element_type(&patient, "name").await.unwrap();
+        cached.get_type("Patient.contact").await.unwrap();
+        cached.get_type("HumanName").await.unwrap().unwrap();
+        let profile = "http://example.org/StructureDefinition/named-patient";
+        assert!(cached.get_profile_type(profile).await.unwrap().is_some());
+        assert!(
+            cached
+                .get_profile_type("http://example.org/StructureDefinition/missing")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        cached.sync().await;
+        assert_eq!(cached.cache_stats().type_entries, 5);
+
+        cached.invalidate_type("Patient").await;
+        cached.sync().await;
+        let stats = cached.cache_stats();
+        // Only the unrelated HumanName lookup is left
+        assert_eq!(stats.type_entries, 1);
+        assert_eq!(stats.element_type_entries, 0);
+        assert!(cached.get_type("HumanName").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,395 @@
+//! Canonical resource resolution
+//!
+//! This module provides the shared notion of a FHIR canonical reference
+//! (`url`, `url|version`) and the `CanonicalResolver` trait used to look up
+//! conformance resources such as StructureDefinitions and ValueSets.
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::{ModelError, Result};
+use crate::package::{FhirPackage, PackageSet, compare_versions};
+
+/// A parsed canonical reference (`url` or `url|version`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Canonical {
+    /// Canonical URL without version
+    pub url: String,
+    /// Requested version, possibly partial (e.g. `4.0`)
+    pub version: Option<String>,
+}
+
+impl Canonical {
+    /// Parse a canonical reference, splitting off a `|version` suffix
+    pub fn parse(canonical: &str) -> Self {
+        match canonical.split_once('|') {
+            Some((url, version)) if !version.is_empty() => Self {
+                url: url.to_string(),
+                version: Some(version.to_string()),
+            },
+            Some((url, _)) => Self {
+                url: url.to_string(),
+                version: None,
+            },
+            None => Self {
+                url: canonical.to_string(),
+                version: None,
+            },
+        }
+    }
+
+    /// Whether a resource version satisfies the requested version
+    ///
+    /// A requested version matches exactly or as a dot-separated prefix, so
+    /// `4.0` matches `4.0.1` but not `4.01`. No requested version matches all.
+    pub fn matches_version(&self, version: Option<&str>) -> bool {
+        match (&self.version, version) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(requested), Some(version)) => {
+                version == requested
+                    || version
+                        .strip_prefix(requested.as_str())
+                        .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('-'))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Canonical {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}|{version}", self.url),
+            None => write!(f, "{}", self.url),
+        }
+    }
+}
+
+/// Conformance resource types addressable by canonical URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CanonicalResourceType {
+    /// StructureDefinition
+    StructureDefinition,
+    /// ValueSet
+    ValueSet,
+    /// CodeSystem
+    CodeSystem,
+    /// ConceptMap
+    ConceptMap,
+    /// StructureMap
+    StructureMap,
+}
+
+impl CanonicalResourceType {
+    /// Parse a FHIR resource type name
+    pub fn from_resource_type(resource_type: &str) -> Option<Self> {
+        match resource_type {
+            "StructureDefinition" => Some(Self::StructureDefinition),
+            "ValueSet" => Some(Self::ValueSet),
+            "CodeSystem" => Some(Self::CodeSystem),
+            "ConceptMap" => Some(Self::ConceptMap),
+            "StructureMap" => Some(Self::StructureMap),
+            _ => None,
+        }
+    }
+
+    /// FHIR resource type name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::StructureDefinition => "StructureDefinition",
+            Self::ValueSet => "ValueSet",
+            Self::CodeSystem => "CodeSystem",
+            Self::ConceptMap => "ConceptMap",
+            Self::StructureMap => "StructureMap",
+        }
+    }
+}
+
+impl std::fmt::Display for CanonicalResourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Policy for choosing between several versions of the same canonical URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VersionPolicy {
+    /// The highest matching version wins
+    #[default]
+    Latest,
+    /// The first registered matching version wins
+    FirstRegistered,
+}
+
+/// A resolved conformance resource
+#[derive(Debug, Clone)]
+pub struct ResolvedCanonical {
+    /// Canonical URL
+    pub url: String,
+    /// Business version of the resource
+    pub version: Option<String>,
+    /// Resource type
+    pub resource_type: CanonicalResourceType,
+    /// The resource itself
+    pub resource: Arc<JsonValue>,
+}
+
+/// Resolver for conformance resources referenced by canonical URL
+#[async_trait]
+pub trait CanonicalResolver: Send + Sync + std::fmt::Debug {
+    /// Resolve a canonical reference (`url`, `url|version` or `url|partial-version`)
+    ///
+    /// When `resource_type` is given, only resources of that type are considered.
+    async fn resolve(
+        &self,
+        canonical: &str,
+        resource_type: Option<CanonicalResourceType>,
+    ) -> Result<Option<ResolvedCanonical>>;
+
+    /// Resolve a StructureDefinition
+    async fn resolve_structure_definition(
+        &self,
+        canonical: &str,
+    ) -> Result<Option<Arc<JsonValue>>> {
+        Ok(self
+            .resolve(canonical, Some(CanonicalResourceType::StructureDefinition))
+            .await?
+            .map(|r| r.resource))
+    }
+
+    /// Resolve a ValueSet
+    async fn resolve_value_set(&self, canonical: &str) -> Result<Option<Arc<JsonValue>>> {
+        Ok(self
+            .resolve(canonical, Some(CanonicalResourceType::ValueSet))
+            .await?
+            .map(|r| r.resource))
+    }
+
+    /// Resolve a CodeSystem
+    async fn resolve_code_system(&self, canonical: &str) -> Result<Option<Arc<JsonValue>>> {
+        Ok(self
+            .resolve(canonical, Some(CanonicalResourceType::CodeSystem))
+            .await?
+            .map(|r| r.resource))
+    }
+
+    /// Resolve a ConceptMap
+    async fn resolve_concept_map(&self, canonical: &str) -> Result<Option<Arc<JsonValue>>> {
+        Ok(self
+            .resolve(canonical, Some(CanonicalResourceType::ConceptMap))
+            .await?
+            .map(|r| r.resource))
+    }
+
+    /// Resolve a StructureMap
+    async fn resolve_structure_map(&self, canonical: &str) -> Result<Option<Arc<JsonValue>>> {
+        Ok(self
+            .resolve(canonical, Some(CanonicalResourceType::StructureMap))
+            .await?
+            .map(|r| r.resource))
+    }
+}
+
+/// In-memory canonical resolver
+///
+/// Resources are registered up front (individually or from packages) and
+/// resolved according to the configured `VersionPolicy`.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryCanonicalResolver {
+    entries: HashMap<String, Vec<ResolvedCanonical>>,
+    policy: VersionPolicy,
+}
+
+impl InMemoryCanonicalResolver {
+    /// Create an empty resolver using the "latest version wins" policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a resolver with all supported resources of a package set
+    pub fn from_package_set(packages: &PackageSet) -> Self {
+        let mut resolver = Self::new();
+        for package in packages.packages() {
+            resolver.add_package(package);
+        }
+        resolver
+    }
+
+    /// Set the version policy
+    pub fn with_version_policy(mut self, policy: VersionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Register a conformance resource
+    ///
+    /// Fails if the resource has no `url` or is not a supported resource type.
+    pub fn add_resource(&mut self, resource: impl Into<Arc<JsonValue>>) -> Result<()> {
+        let resource = resource.into();
+        let field = |name: &str| resource.get(name).and_then(|v| v.as_str());
+        let type_name = field("resourceType").unwrap_or_default();
+        let resource_type =
+            CanonicalResourceType::from_resource_type(type_name).ok_or_else(|| {
+                ModelError::validation_error(format!(
+                    "Resource type '{type_name}' is not a supported canonical resource"
+                ))
+            })?;
+        let url = field("url")
+            .ok_or_else(|| ModelError::validation_error("Canonical resource is missing 'url'"))?
+            .to_string();
+        let version = field("version").map(String::from);
+
+        self.entries
+            .entry(url.clone())
+            .or_default()
+            .push(ResolvedCanonical {
+                url,
+                version,
+                resource_type,
+                resource,
+            });
+        Ok(())
+    }
+
+    /// Register all supported conformance resources of a package
+    ///
+    /// Resources of other types or without a `url` are skipped.
+    pub fn add_package(&mut self, package: &FhirPackage) {
+        for resource in package.resources() {
+            let _ = self.add_resource(Arc::clone(resource));
+        }
+    }
+
+    /// Number of registered resources
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    /// Whether no resources are registered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Synchronous lookup used by the `CanonicalResolver` implementation
+    pub fn lookup(
+        &self,
+        canonical: &str,
+        resource_type: Option<CanonicalResourceType>,
+    ) -> Option<&ResolvedCanonical> {
+        let canonical = Canonical::parse(canonical);
+        let mut candidates = self.entries.get(&canonical.url)?.iter().filter(|entry| {
+            resource_type.is_none_or(|t| entry.resource_type == t)
+                && canonical.matches_version(entry.version.as_deref())
+        });
+
+        match self.policy {
+            VersionPolicy::FirstRegistered => candidates.next(),
+            VersionPolicy::Latest => candidates.max_by(|a, b| {
+                compare_versions(
+                    a.version.as_deref().unwrap_or(""),
+                    b.version.as_deref().unwrap_or(""),
+                )
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl CanonicalResolver for InMemoryCanonicalResolver {
+    async fn resolve(
+        &self,
+        canonical: &str,
+        resource_type: Option<CanonicalResourceType>,
+    ) -> Result<Option<ResolvedCanonical>> {
+        Ok(self.lookup(canonical, resource_type).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resolver(policy: VersionPolicy) -> InMemoryCanonicalResolver {
+        let mut resolver = InMemoryCanonicalResolver::new().with_version_policy(policy);
+        for version in ["4.0.1", "4.3.0", "4.0.2"] {
+            resolver
+                .add_resource(json!({
+                    "resourceType": "ValueSet",
+                    "url": "http://hl7.org/fhir/ValueSet/example",
+                    "version": version
+                }))
+                .unwrap();
+        }
+        resolver
+            .add_resource(json!({
+                "resourceType": "StructureMap",
+                "url": "http://example.org/StructureMap/map"
+            }))
+            .unwrap();
+        resolver
+    }
+
+    #[test]
+    fn test_parse_canonical() {
+        let canonical = Canonical::parse("http://example.org/vs|1.2");
+        assert_eq!(canonical.url, "http://example.org/vs");
+        assert_eq!(canonical.version.as_deref(), Some("1.2"));
+        assert_eq!(canonical.to_string(), "http://example.org/vs|1.2");
+
+        assert_eq!(Canonical::parse("http://example.org/vs|").version, None);
+        assert!(canonical.matches_version(Some("1.2.3")));
+        assert!(!canonical.matches_version(Some("1.23")));
+    }
+
+    #[tokio::test]
+    async fn test_latest_version_wins() {
+        let resolver = resolver(VersionPolicy::Latest);
+        let url = "http://hl7.org/fhir/ValueSet/example";
+
+        let latest = resolver.resolve(url, None).await.unwrap().unwrap();
+        assert_eq!(latest.version.as_deref(), Some("4.3.0"));
+
+        let partial = resolver
+            .resolve_value_set(&format!("{url}|4.0"))
+            .await
+            .unwrap();
+        assert_eq!(partial.unwrap()["version"], "4.0.2");
+
+        let exact = resolver.lookup(&format!("{url}|4.0.1"), None).unwrap();
+        assert_eq!(exact.version.as_deref(), Some("4.0.1"));
+
+        assert!(resolver.lookup(&format!("{url}|5.0"), None).is_none());
+        assert!(resolver.resolve_code_system(url).await.unwrap().is_none());
+        assert!(
+            resolver
+                .resolve_structure_map("http://example.org/StructureMap/map")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_first_registered_policy() {
+        let resolver = resolver(VersionPolicy::FirstRegistered);
+        let entry = resolver
+            .lookup("http://hl7.org/fhir/ValueSet/example|4.0", None)
+            .unwrap();
+        assert_eq!(entry.version.as_deref(), Some("4.0.1"));
+        assert_eq!(resolver.len(), 4);
+    }
+
+    #[test]
+    fn test_rejects_unsupported_resources() {
+        let mut resolver = InMemoryCanonicalResolver::new();
+        assert!(
+            resolver
+                .add_resource(json!({"resourceType": "Patient", "id": "p"}))
+                .is_err()
+        );
+        assert!(resolver.is_empty());
+    }
+}
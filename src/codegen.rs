@@ -0,0 +1,644 @@
+//! Code generator for static ModelProviders
+//!
+//! This module turns the types loaded into a `PackageModelProvider` into Rust
+//! source defining a `StaticModel`, for use with `StaticModelProvider`. It is
+//! intended to run from a build script so the FHIR model is compiled into the
+//! binary and nothing has to be loaded at startup.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! // build.rs
+//! use octofhir_fhir_model::{PackageModelProvider, codegen::StaticModelGenerator};
+//!
+//! fn main() {
+//!     let provider = PackageModelProvider::from_directory("fhir/hl7.fhir.r4.core#4.0.1").unwrap();
+//!     let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("fhir_r4.rs");
+//!     StaticModelGenerator::new(&provider)
+//!         .with_static_name("FHIR_R4")
+//!         .write_to(out)
+//!         .unwrap();
+//! }
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/fhir_r4.rs"));
+//! static PROVIDER: StaticModelProvider = StaticModelProvider::new(&FHIR_R4);
+//! ```
+
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::package_provider::PackageModelProvider;
+use crate::provider::{BindingStrength, ModelProvider};
+use crate::static_provider::{perfect_hash, slot};
+use crate::structure_definition::{StructureDefinitionKind, choice_suffix};
+
+/// Average number of keys per bucket of the perfect hash
+///
+/// Small buckets leave enough single-key buckets to fill the last free slots
+/// directly, so multi-key buckets always find a displacement within range.
+const KEYS_PER_BUCKET: usize = 2;
+
+/// Upper bound (exclusive) of each displacement tried for a multi-key bucket
+const MAX_DISPLACEMENT: u32 = 64;
+
+/// Generates Rust source for a `StaticModel` from a loaded package provider
+#[derive(Debug, Clone)]
+pub struct StaticModelGenerator<'a> {
+    provider: &'a PackageModelProvider,
+    static_name: String,
+    crate_path: String,
+}
+
+impl<'a> StaticModelGenerator<'a> {
+    /// Create a generator for the types loaded into a provider
+    pub fn new(provider: &'a PackageModelProvider) -> Self {
+        Self {
+            provider,
+            static_name: "FHIR_MODEL".to_string(),
+            crate_path: "::octofhir_fhir_model".to_string(),
+        }
+    }
+
+    /// Set the name of the generated `static` (defaults to `FHIR_MODEL`)
+    pub fn with_static_name(mut self, name: impl Into<String>) -> Self {
+        self.static_name = name.into();
+        self
+    }
+
+    /// Set the path this crate is reachable at (defaults to `::octofhir_fhir_model`)
+    pub fn with_crate_path(mut self, path: impl Into<String>) -> Self {
+        self.crate_path = path.into();
+        self
+    }
+
+    /// Generate the Rust source
+    pub fn generate(&self) -> String {
+        let tables = self.tables();
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "// @generated by octofhir-fhir-model StaticModelGenerator. Do not edit."
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "/// Static FHIR model generated from StructureDefinitions"
+        );
+        let _ = writeln!(
+            out,
+            "pub static {}: {}::static_provider::StaticModel = {{",
+            self.static_name, self.crate_path
+        );
+        let _ = writeln!(out, "    use {}::static_provider::*;", self.crate_path);
+        let _ = writeln!(out, "    StaticModel {{");
+        let _ = writeln!(out, "        fhir_version: {:?},", tables.fhir_version);
+
+        write_map(&mut out, "types", tables.types, TypeRow::source);
+        write_map(&mut out, "elements", tables.elements, ElementRow::source);
+        write_map(&mut out, "children", tables.children, |names| {
+            str_slice(names)
+        });
+        write_map(
+            &mut out,
+            "choice_elements",
+            tables.choice_elements,
+            ChoiceRow::source,
+        );
+
+        for (field, names) in [
+            ("resource_types", &tables.resource_types),
+            ("complex_types", &tables.complex_types),
+            ("primitive_types", &tables.primitive_types),
+            ("logical_types", &tables.logical_types),
+            ("namespaces", &tables.namespaces),
+        ] {
+            let _ = writeln!(out, "        {field}: {},", str_slice(names));
+        }
+
+        let _ = writeln!(out, "    }}");
+        let _ = writeln!(out, "}};");
+        out
+    }
+
+    /// Write the generated source to a file
+    ///
+    /// The file is left untouched when its content would not change, so build
+    /// scripts do not trigger needless recompilation.
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let source = self.generate();
+        if std::fs::read_to_string(path).is_ok_and(|existing| existing == source) {
+            return Ok(());
+        }
+        std::fs::write(path, source)?;
+        Ok(())
+    }
+
+    /// Collect the model tables from the provider
+    fn tables(&self) -> ModelTables {
+        let types = self
+            .provider
+            .types()
+            .iter()
+            .map(|(name, model)| {
+                let row = TypeRow {
+                    kind: model.kind,
+                    is_abstract: model.is_abstract,
+                    ancestors: self.provider.get_type_ancestors(name),
+                    system_type: model.system_type.as_ref().map(|s| s.to_string()),
+                    namespace: (model.namespace != "FHIR").then(|| model.namespace.to_string()),
+                };
+                (name.clone(), row)
+            })
+            .collect();
+
+        let mut elements = Vec::new();
+        let mut children = Vec::new();
+        let mut choices = Vec::new();
+        for (parent, child_elements) in self.provider.children() {
+            children.push((parent.clone(), child_elements.keys().cloned().collect()));
+
+            for (name, element) in child_elements {
+                let key = format!("{parent}.{name}");
+                let info = element.to_element_info();
+                if element.is_choice() {
+                    for code in element.type_codes() {
+                        choices.push((
+                            format!("{parent}.{name}{}", choice_suffix(code)),
+                            ChoiceRow {
+                                element: key.clone(),
+                                type_code: code.to_string(),
+                            },
+                        ));
+                    }
+                }
+                let row = ElementRow {
+                    name: name.clone(),
+                    path: element.path.clone(),
+                    types: element
+                        .type_codes()
+                        .map(|code| self.provider.resolve_code(code).to_string())
+                        .collect(),
+                    min: info.cardinality.min,
+                    max: info.cardinality.max,
+                    is_modifier: info.is_modifier,
+                    is_summary: info.is_summary,
+                    must_support: info.must_support,
+                    binding_strength: info.binding.as_ref().map(|b| b.strength),
+                    value_set: info.binding.and_then(|b| b.value_set),
+                    constraints: info.constraints,
+                    documentation: info.documentation,
+                    content_reference: element.content_reference_path().map(String::from),
+                    fixed_value: info.fixed_value.as_ref().map(ToString::to_string),
+                    pattern_value: info.pattern_value.as_ref().map(ToString::to_string),
+                };
+                elements.push((key, row));
+            }
+        }
+
+        // A typed choice name never shadows a real element
+        choices.retain(|(key, _)| !elements.iter().any(|(element, _)| element == key));
+
+        ModelTables {
+            fhir_version: self
+                .provider
+                .fhir_version()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "R4".to_string()),
+            types,
+            elements,
+            children,
+            choice_elements: choices,
+            resource_types: self
+                .provider
+                .names_of_kind(StructureDefinitionKind::Resource, true),
+            complex_types: self
+                .provider
+                .names_of_kind(StructureDefinitionKind::ComplexType, false),
+            primitive_types: self
+                .provider
+                .names_of_kind(StructureDefinitionKind::PrimitiveType, false),
+            logical_types: self
+                .provider
+                .names_of_kind(StructureDefinitionKind::Logical, false),
+            namespaces: self.provider.custom_namespaces(),
+        }
+    }
+}
+
+/// Tables of a static model with owned data, before rendering
+#[derive(Debug)]
+struct ModelTables {
+    fhir_version: String,
+    types: Vec<(String, TypeRow)>,
+    elements: Vec<(String, ElementRow)>,
+    children: Vec<(String, Vec<String>)>,
+    choice_elements: Vec<(String, ChoiceRow)>,
+    resource_types: Vec<String>,
+    complex_types: Vec<String>,
+    primitive_types: Vec<String>,
+    logical_types: Vec<String>,
+    namespaces: Vec<String>,
+}
+
+/// Owned counterpart of `StaticTypeDef`
+#[derive(Debug)]
+struct TypeRow {
+    kind: StructureDefinitionKind,
+    is_abstract: bool,
+    ancestors: Vec<String>,
+    system_type: Option<String>,
+    namespace: Option<String>,
+}
+
+impl TypeRow {
+    fn source(&self) -> String {
+        format!(
+            "StaticTypeDef {{ kind: StructureDefinitionKind::{:?}, is_abstract: {}, ancestors: {}, system_type: {:?}, namespace: {:?} }}",
+            self.kind,
+            self.is_abstract,
+            str_slice(&self.ancestors),
+            self.system_type,
+            self.namespace,
+        )
+    }
+}
+
+/// Owned counterpart of `StaticElementDef`
+#[derive(Debug)]
+struct ElementRow {
+    name: String,
+    path: String,
+    types: Vec<String>,
+    min: u32,
+    max: Option<u32>,
+    is_modifier: bool,
+    is_summary: bool,
+    must_support: bool,
+    binding_strength: Option<BindingStrength>,
+    value_set: Option<String>,
+    constraints: Vec<String>,
+    documentation: Option<String>,
+    content_reference: Option<String>,
+    fixed_value: Option<String>,
+    pattern_value: Option<String>,
+}
+
+impl ElementRow {
+    fn source(&self) -> String {
+        format!(
+            "StaticElementDef {{ name: {:?}, path: {:?}, types: {}, min: {}, max: {:?}, \
+             is_modifier: {}, is_summary: {}, must_support: {}, binding_strength: {}, \
+             value_set: {:?}, constraints: {}, documentation: {:?}, content_reference: {:?}, \
+             fixed_value: {:?}, pattern_value: {:?} }}",
+            self.name,
+            self.path,
+            str_slice(&self.types),
+            self.min,
+            self.max,
+            self.is_modifier,
+            self.is_summary,
+            self.must_support,
+            match self.binding_strength {
+                Some(strength) => format!("Some(BindingStrength::{strength:?})"),
+                None => "None".to_string(),
+            },
+            self.value_set,
+            str_slice(&self.constraints),
+            self.documentation,
+            self.content_reference,
+            self.fixed_value,
+            self.pattern_value,
+        )
+    }
+}
+
+/// Owned counterpart of `StaticChoiceDef`
+#[derive(Debug)]
+struct ChoiceRow {
+    element: String,
+    type_code: String,
+}
+
+impl ChoiceRow {
+    fn source(&self) -> String {
+        format!(
+            "StaticChoiceDef {{ element: {:?}, type_code: {:?} }}",
+            self.element, self.type_code
+        )
+    }
+}
+
+/// Perfect hash over a set of keys
+#[derive(Debug)]
+pub(crate) struct PerfectHash {
+    pub(crate) seed: u64,
+    pub(crate) displacements: Vec<(u32, u32)>,
+    /// Key index for every slot
+    pub(crate) slots: Vec<usize>,
+}
+
+/// Build a perfect hash with the CHD (compress, hash and displace) algorithm
+pub(crate) fn build_perfect_hash(keys: &[&str]) -> PerfectHash {
+    if keys.is_empty() {
+        return PerfectHash {
+            seed: 0,
+            displacements: Vec::new(),
+            slots: Vec::new(),
+        };
+    }
+
+    (0u64..)
+        .find_map(|seed| try_perfect_hash(keys, seed))
+        .expect("a perfect hash exists for distinct keys")
+}
+
+fn try_perfect_hash(keys: &[&str], seed: u64) -> Option<PerfectHash> {
+    let len = keys.len();
+    let bucket_count = len.div_ceil(KEYS_PER_BUCKET);
+    let hashes: Vec<_> = keys.iter().map(|key| perfect_hash(key, seed)).collect();
+
+    let mut buckets = vec![Vec::new(); bucket_count];
+    for (index, hash) in hashes.iter().enumerate() {
+        buckets[(hash.0 % bucket_count as u32) as usize].push(index);
+    }
+    let mut order: Vec<usize> = (0..bucket_count).collect();
+    order.sort_by_key(|&bucket| Reverse(buckets[bucket].len()));
+
+    let mut slots: Vec<Option<usize>> = vec![None; len];
+    let mut displacements = vec![(0, 0); bucket_count];
+    let mut candidate = Vec::new();
+    let range = MAX_DISPLACEMENT.min(len as u32);
+    let mut free_slots = 0..len;
+
+    'buckets: for bucket in order {
+        let members = &buckets[bucket];
+        match members.as_slice() {
+            [] => continue,
+            // Single keys go straight to the next free slot: `d2` alone can reach any slot
+            &[index] => {
+                let free = free_slots.find(|&slot| slots[slot].is_none())?;
+                let offset = hashes[index].2 as usize % len;
+                let d2 = ((free + len - offset) % len) as u32;
+                if slot(hashes[index], 0, d2, len) != free {
+                    return None;
+                }
+                slots[free] = Some(index);
+                displacements[bucket] = (0, d2);
+                continue 'buckets;
+            }
+            _ => {}
+        }
+        for d1 in 0..range {
+            for d2 in 0..range {
+                candidate.clear();
+                let fits = members.iter().all(|&index| {
+                    let slot = slot(hashes[index], d1, d2, len);
+                    let free = slots[slot].is_none() && !candidate.contains(&slot);
+                    candidate.push(slot);
+                    free
+                });
+                if fits {
+                    for (&index, &slot) in members.iter().zip(&candidate) {
+                        slots[slot] = Some(index);
+                    }
+                    displacements[bucket] = (d1, d2);
+                    continue 'buckets;
+                }
+            }
+        }
+        // No displacement within range; the caller retries with another seed
+        return None;
+    }
+
+    Some(PerfectHash {
+        seed,
+        displacements,
+        slots: slots.into_iter().collect::<Option<_>>()?,
+    })
+}
+
+/// Entries of a map in perfect-hash slot order
+#[derive(Debug)]
+struct HashedEntries<V> {
+    seed: u64,
+    displacements: Vec<(u32, u32)>,
+    entries: Vec<(String, V)>,
+}
+
+/// Order map entries by their perfect-hash slot
+fn hash_entries<V>(entries: Vec<(String, V)>) -> HashedEntries<V> {
+    let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    let hash = build_perfect_hash(&keys);
+    let mut entries: Vec<Option<(String, V)>> = entries.into_iter().map(Some).collect();
+    HashedEntries {
+        seed: hash.seed,
+        entries: hash
+            .slots
+            .iter()
+            .filter_map(|&index| entries[index].take())
+            .collect(),
+        displacements: hash.displacements,
+    }
+}
+
+/// Write a `StaticMap` field from key/value pairs
+fn write_map<V>(
+    out: &mut String,
+    field: &str,
+    entries: Vec<(String, V)>,
+    source: impl Fn(&V) -> String,
+) {
+    let HashedEntries {
+        seed,
+        displacements,
+        entries,
+    } = hash_entries(entries);
+
+    let _ = writeln!(out, "        {field}: StaticMap {{");
+    let _ = writeln!(out, "            seed: {seed},");
+    let _ = writeln!(out, "            displacements: &{displacements:?},");
+    let _ = writeln!(out, "            entries: &[");
+    for (key, value) in &entries {
+        let _ = writeln!(out, "                ({key:?}, {}),", source(value));
+    }
+    let _ = writeln!(out, "            ],");
+    let _ = writeln!(out, "        }},");
+}
+
+/// Rust expression for a `&'static [&'static str]`
+fn str_slice(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| format!("{item:?}")).collect();
+    format!("&[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::SyncModelProvider;
+    use crate::static_provider::{
+        StaticChoiceDef, StaticElementDef, StaticMap, StaticModel, StaticModelProvider,
+        StaticTypeDef,
+    };
+    use crate::test_support::fixture_definitions;
+    use serde_json::json;
+
+    fn leak_str(value: &str) -> &'static str {
+        Box::leak(value.to_string().into_boxed_str())
+    }
+
+    fn leak_strs(values: &[String]) -> &'static [&'static str] {
+        Box::leak(values.iter().map(|v| leak_str(v)).collect())
+    }
+
+    fn leak_map<V, S>(entries: Vec<(String, V)>, convert: impl Fn(V) -> S) -> StaticMap<S> {
+        let HashedEntries {
+            seed,
+            displacements,
+            entries,
+        } = hash_entries(entries);
+        StaticMap {
+            seed,
+            displacements: Box::leak(displacements.into_boxed_slice()),
+            entries: Box::leak(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (leak_str(&key), convert(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Build the `StaticModel` the generated source describes, from the same tables
+    fn leak_model(tables: ModelTables) -> &'static StaticModel {
+        Box::leak(Box::new(StaticModel {
+            fhir_version: leak_str(&tables.fhir_version),
+            types: leak_map(tables.types, |row| StaticTypeDef {
+                kind: row.kind,
+                is_abstract: row.is_abstract,
+                ancestors: leak_strs(&row.ancestors),
+                system_type: row.system_type.as_deref().map(leak_str),
+                namespace: row.namespace.as_deref().map(leak_str),
+            }),
+            elements: leak_map(tables.elements, |row| StaticElementDef {
+                name: leak_str(&row.name),
+                path: leak_str(&row.path),
+                types: leak_strs(&row.types),
+                min: row.min,
+                max: row.max,
+                is_modifier: row.is_modifier,
+                is_summary: row.is_summary,
+                must_support: row.must_support,
+                binding_strength: row.binding_strength,
+                value_set: row.value_set.as_deref().map(leak_str),
+                constraints: leak_strs(&row.constraints),
+                documentation: row.documentation.as_deref().map(leak_str),
+                content_reference: row.content_reference.as_deref().map(leak_str),
+                fixed_value: row.fixed_value.as_deref().map(leak_str),
+                pattern_value: row.pattern_value.as_deref().map(leak_str),
+            }),
+            children: leak_map(tables.children, |names| leak_strs(&names)),
+            choice_elements: leak_map(tables.choice_elements, |row| StaticChoiceDef {
+                element: leak_str(&row.element),
+                type_code: leak_str(&row.type_code),
+            }),
+            resource_types: leak_strs(&tables.resource_types),
+            complex_types: leak_strs(&tables.complex_types),
+            primitive_types: leak_strs(&tables.primitive_types),
+            logical_types: leak_strs(&tables.logical_types),
+            namespaces: leak_strs(&tables.namespaces),
+        }))
+    }
+
+    #[test]
+    fn test_perfect_hash_places_every_key() {
+        let keys: Vec<String> = (0..20_000).map(|i| format!("Type{i}.element")).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let hash = build_perfect_hash(&keys);
+
+        assert_eq!(hash.slots.len(), keys.len());
+        for (slot_index, &key_index) in hash.slots.iter().enumerate() {
+            let hashes = perfect_hash(keys[key_index], hash.seed);
+            let (d1, d2) =
+                hash.displacements[(hashes.0 % hash.displacements.len() as u32) as usize];
+            assert_eq!(slot(hashes, d1, d2, keys.len()), slot_index);
+        }
+    }
+
+    #[test]
+    fn test_generate_static_model() {
+        let provider =
+            PackageModelProvider::from_structure_definitions(&fixture_definitions()).unwrap();
+        let source = StaticModelGenerator::new(&provider)
+            .with_static_name("TEST_MODEL")
+            .generate();
+
+        assert!(source.contains(
+            "pub static TEST_MODEL: ::octofhir_fhir_model::static_provider::StaticModel"
+        ));
+        assert!(source.contains("fhir_version: \"R4\""));
+        assert!(source.contains(
+            "(\"Patient\", StaticTypeDef { kind: StructureDefinitionKind::Resource, \
+             is_abstract: false, ancestors: &[\"DomainResource\", \"Resource\"], system_type: None, \
+             namespace: None })"
+        ));
+        assert!(source.contains("(\"Patient.deceasedDateTime\", StaticChoiceDef { element: \"Patient.deceased\", type_code: \"dateTime\" })"));
+        assert!(
+            source.contains("resource_types: &[\"DomainResource\", \"Patient\", \"Resource\"]")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generated_tables_round_trip() {
+        let mut definitions = fixture_definitions();
+        let patient = definitions
+            .iter_mut()
+            .find(|sd| sd["name"] == "Patient")
+            .unwrap();
+        let elements = &mut patient["snapshot"]["element"];
+        elements[1]["mustSupport"] = json!(true);
+        elements[1]["patternHumanName"] = json!({"family": "Smith", "given": ["Jo"]});
+        elements[2]["fixedBoolean"] = json!(false);
+        let package = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+        let model = leak_model(StaticModelGenerator::new(&package).tables());
+        let provider = StaticModelProvider::new(model);
+
+        for name in package.types().keys() {
+            assert!(model.types.contains_key(name), "missing type {name}");
+        }
+        for (parent, children) in package.children() {
+            for name in children.keys() {
+                assert!(model.elements.contains_key(&format!("{parent}.{name}")));
+            }
+        }
+
+        let patient = provider.get_type_sync("Patient").unwrap();
+        let name = provider.get_element_type_sync(&patient, "name").unwrap();
+        assert_eq!(name.name.as_deref(), Some("HumanName"));
+        assert_eq!(name.singleton, Some(false));
+        let family = provider.get_element_type_sync(&name, "family").unwrap();
+        assert_eq!(family.type_name, "String");
+        let deceased = provider
+            .get_element_type_sync(&patient, "deceasedDateTime")
+            .unwrap();
+        assert_eq!(deceased.name.as_deref(), Some("dateTime"));
+        assert!(provider.is_type_derived_from("Patient", "Resource"));
+        assert_eq!(
+            provider.get_resource_types().await.unwrap(),
+            vec!["DomainResource", "Patient", "Resource"]
+        );
+        let elements = provider.get_elements("Patient").await.unwrap();
+        assert_eq!(elements, package.get_elements("Patient").await.unwrap());
+        let name = elements.iter().find(|e| e.name == "name").unwrap();
+        assert!(name.must_support);
+        assert_eq!(
+            name.pattern_value,
+            Some(json!({"family": "Smith", "given": ["Jo"]}))
+        );
+        let deceased = elements.iter().find(|e| e.name == "deceased").unwrap();
+        assert_eq!(deceased.fixed_value, Some(json!(false)));
+    }
+}
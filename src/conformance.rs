@@ -0,0 +1,824 @@
+//! Conformance test kit for ModelProvider implementations
+//!
+//! This module runs a `ModelProvider` against a battery of expectations derived
+//! from the FHIR R4 core specification: inheritance chains, choice type
+//! expansion, singleton handling, case-sensitive names, primitive listings and
+//! backbone element naming. Providers loaded with `hl7.fhir.r4.core` (or a
+//! later release) are expected to pass every check.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use octofhir_fhir_model::conformance::ConformanceSuite;
+//!
+//! #[tokio::test]
+//! async fn provider_is_conformant() {
+//!     let provider: Arc<dyn ModelProvider> = Arc::new(my_provider());
+//!     ConformanceSuite::r4().run(&*provider).await.assert_conformant();
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::interner::Symbol;
+use crate::provider::{ModelProvider, TypeInfo, type_constants};
+
+/// Area of the `ModelProvider` contract a check covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConformanceCategory {
+    /// `is_type_derived_from` and `of_type` along inheritance chains
+    Inheritance,
+    /// Choice element expansion and typed choice names (`valueQuantity`)
+    ChoiceTypes,
+    /// Singleton vs collection handling, including `get_children_type`
+    Cardinality,
+    /// Type and element names are case-sensitive
+    CaseSensitivity,
+    /// Primitive, complex and resource type listings
+    TypeListings,
+    /// Navigation into and naming of anonymous backbone elements
+    BackboneElements,
+}
+
+impl fmt::Display for ConformanceCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConformanceCategory::Inheritance => "inheritance",
+            ConformanceCategory::ChoiceTypes => "choice types",
+            ConformanceCategory::Cardinality => "cardinality",
+            ConformanceCategory::CaseSensitivity => "case sensitivity",
+            ConformanceCategory::TypeListings => "type listings",
+            ConformanceCategory::BackboneElements => "backbone elements",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Outcome of a single conformance check
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceCheck {
+    /// Area of the contract the check covers
+    pub category: ConformanceCategory,
+    /// Description of the expectation
+    pub name: String,
+    /// Failure reason, or `None` if the check passed
+    pub failure: Option<String>,
+}
+
+impl ConformanceCheck {
+    /// Whether the check passed
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Results of running a conformance suite against a provider
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConformanceReport {
+    /// All checks in the order they were run
+    pub checks: Vec<ConformanceCheck>,
+}
+
+impl ConformanceReport {
+    /// Whether every check passed
+    pub fn is_conformant(&self) -> bool {
+        self.checks.iter().all(ConformanceCheck::passed)
+    }
+
+    /// Checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &ConformanceCheck> {
+        self.checks.iter().filter(|check| !check.passed())
+    }
+
+    /// Checks of a category
+    pub fn checks_in(
+        &self,
+        category: ConformanceCategory,
+    ) -> impl Iterator<Item = &ConformanceCheck> {
+        self.checks
+            .iter()
+            .filter(move |check| check.category == category)
+    }
+
+    /// Find a check by name
+    pub fn check(&self, name: &str) -> Option<&ConformanceCheck> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+
+    /// Panic with the list of failed checks unless every check passed
+    pub fn assert_conformant(&self) {
+        assert!(self.is_conformant(), "{self}");
+    }
+
+    fn record(&mut self, category: ConformanceCategory, name: String, outcome: Outcome) {
+        self.checks.push(ConformanceCheck {
+            category,
+            name,
+            failure: outcome.err(),
+        });
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = self.failures().count();
+        writeln!(
+            f,
+            "{} of {} conformance checks passed",
+            self.checks.len() - failed,
+            self.checks.len()
+        )?;
+        for check in self.failures() {
+            writeln!(
+                f,
+                "  [{}] {}: {}",
+                check.category,
+                check.name,
+                check.failure.as_deref().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+type Outcome = std::result::Result<(), String>;
+
+/// `(derived, base, expected)` relationships from the R4 type hierarchy
+const R4_INHERITANCE: &[(&str, &str, bool)] = &[
+    ("Patient", "DomainResource", true),
+    ("Patient", "Resource", true),
+    ("Observation", "DomainResource", true),
+    ("Bundle", "Resource", true),
+    ("Bundle", "DomainResource", false),
+    ("Patient", "Observation", false),
+    ("Resource", "Patient", false),
+    ("HumanName", "Element", true),
+    ("Age", "Quantity", true),
+    ("Duration", "Quantity", true),
+    ("Quantity", "Age", false),
+    ("code", "string", true),
+    ("id", "string", true),
+    ("markdown", "string", true),
+    ("url", "uri", true),
+    ("canonical", "uri", true),
+    ("positiveInt", "integer", true),
+    ("unsignedInt", "integer", true),
+    ("string", "Element", true),
+    ("integer", "string", false),
+];
+
+/// R4 primitive types every R4 (or later) provider must list
+const R4_PRIMITIVES: &[&str] = &[
+    "base64Binary",
+    "boolean",
+    "canonical",
+    "code",
+    "date",
+    "dateTime",
+    "decimal",
+    "id",
+    "instant",
+    "integer",
+    "markdown",
+    "oid",
+    "positiveInt",
+    "string",
+    "time",
+    "unsignedInt",
+    "uri",
+    "url",
+    "uuid",
+    "xhtml",
+];
+
+/// `(type, choice element, allowed types)` from R4 resources
+const R4_CHOICES: &[(&str, &str, &[&str])] = &[
+    ("Patient", "deceased", &["boolean", "dateTime"]),
+    ("Patient", "multipleBirth", &["boolean", "integer"]),
+    (
+        "Observation",
+        "value",
+        &[
+            "Quantity",
+            "CodeableConcept",
+            "string",
+            "boolean",
+            "integer",
+            "Range",
+            "Ratio",
+            "SampledData",
+            "time",
+            "dateTime",
+            "Period",
+        ],
+    ),
+    (
+        "Observation",
+        "effective",
+        &["dateTime", "Period", "Timing", "instant"],
+    ),
+];
+
+/// `(parent, element, expected singleton)` from R4 resources and data types
+const R4_CARDINALITIES: &[(&str, &str, bool)] = &[
+    ("Patient", "name", false),
+    ("Patient", "gender", true),
+    ("Patient", "birthDate", true),
+    ("Patient", "identifier", false),
+    ("Observation", "status", true),
+    ("Observation", "category", false),
+    ("HumanName", "family", true),
+    ("HumanName", "given", false),
+];
+
+/// `(parent, backbone element, child, expected child type)` from R4 resources
+const R4_BACKBONES: &[(&str, &str, &str, &str)] = &[
+    ("Patient", "contact", "name", "HumanName"),
+    ("Patient", "contact", "relationship", "CodeableConcept"),
+    ("Patient", "communication", "language", "CodeableConcept"),
+    ("Observation", "component", "code", "CodeableConcept"),
+    ("Observation", "referenceRange", "low", "Quantity"),
+    ("Bundle", "entry", "fullUrl", "uri"),
+];
+
+/// Battery of `ModelProvider` expectations
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceSuite {
+    skipped: Vec<ConformanceCategory>,
+}
+
+impl ConformanceSuite {
+    /// Expectations derived from the FHIR R4 core specification
+    ///
+    /// These hold for R4, R4B and R5 providers alike.
+    pub fn r4() -> Self {
+        Self::default()
+    }
+
+    /// Skip the checks of a category (e.g. for providers without backbone support)
+    pub fn without(mut self, category: ConformanceCategory) -> Self {
+        self.skipped.push(category);
+        self
+    }
+
+    /// Run the suite against a provider
+    pub async fn run(&self, provider: &dyn ModelProvider) -> ConformanceReport {
+        let mut report = ConformanceReport::default();
+        for category in [
+            ConformanceCategory::Inheritance,
+            ConformanceCategory::ChoiceTypes,
+            ConformanceCategory::Cardinality,
+            ConformanceCategory::CaseSensitivity,
+            ConformanceCategory::TypeListings,
+            ConformanceCategory::BackboneElements,
+        ] {
+            if self.skipped.contains(&category) {
+                continue;
+            }
+            match category {
+                ConformanceCategory::Inheritance => check_inheritance(provider, &mut report).await,
+                ConformanceCategory::ChoiceTypes => check_choices(provider, &mut report).await,
+                ConformanceCategory::Cardinality => check_cardinality(provider, &mut report).await,
+                ConformanceCategory::CaseSensitivity => {
+                    check_case_sensitivity(provider, &mut report).await
+                }
+                ConformanceCategory::TypeListings => check_listings(provider, &mut report).await,
+                ConformanceCategory::BackboneElements => {
+                    check_backbones(provider, &mut report).await
+                }
+            }
+        }
+        report
+    }
+}
+
+async fn check_inheritance(provider: &dyn ModelProvider, report: &mut ConformanceReport) {
+    for &(derived, base, expected) in R4_INHERITANCE {
+        let relation = if expected {
+            "derives from"
+        } else {
+            "does not derive from"
+        };
+        let outcome = async {
+            if provider.is_type_derived_from(derived, base) != expected {
+                return Err(format!("is_type_derived_from returned {}", !expected));
+            }
+            let type_info = require_type(provider, derived).await?;
+            let cast = provider.of_type(&type_info, base);
+            if cast.is_some() != expected {
+                return Err(format!("of_type returned {cast:?}"));
+            }
+            Ok(())
+        }
+        .await;
+        report.record(
+            ConformanceCategory::Inheritance,
+            format!("{derived} {relation} {base}"),
+            outcome,
+        );
+    }
+}
+
+async fn check_choices(provider: &dyn ModelProvider, report: &mut ConformanceReport) {
+    for &(parent, element, allowed) in R4_CHOICES {
+        let outcome = async {
+            let choices = provider
+                .get_choice_types(parent, element)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("get_choice_types returned None")?;
+            let listed: Vec<&str> = choices.iter().map(|c| c.type_name.as_str()).collect();
+            if let Some(missing) = allowed.iter().find(|t| !listed.contains(t)) {
+                return Err(format!("{missing} missing from choice types {listed:?}"));
+            }
+
+            let parent_type = require_type(provider, parent).await?;
+            let choice = require_element(provider, &parent_type, element).await?;
+            if !provider.is_union_type(&choice) {
+                return Err(format!("{choice:?} is not a union type"));
+            }
+            let cast = provider
+                .of_type(&choice, allowed[0])
+                .ok_or_else(|| format!("of_type({}) returned None", allowed[0]))?;
+            expect_name(&cast, allowed[0])?;
+            // No release allows HumanName in these choices (R5 adds Attachment
+            // and Reference to Observation.value[x])
+            if let Some(cast) = provider.of_type(&choice, "HumanName") {
+                return Err(format!("of_type(HumanName) returned {cast:?}"));
+            }
+
+            for choice in &choices {
+                let typed = format!("{element}{}", choice.suffix);
+                let typed_type = require_element(provider, &parent_type, &typed).await?;
+                expect_name(&typed_type, &choice.type_name)?;
+            }
+            Ok(())
+        }
+        .await;
+        report.record(
+            ConformanceCategory::ChoiceTypes,
+            format!("{parent}.{element}[x] expands to {}", allowed.join(" | ")),
+            outcome,
+        );
+    }
+}
+
+async fn check_cardinality(provider: &dyn ModelProvider, report: &mut ConformanceReport) {
+    for &(parent, element, singleton) in R4_CARDINALITIES {
+        let outcome = async {
+            let parent_type = require_type(provider, parent).await?;
+            let element_type = require_element(provider, &parent_type, element).await?;
+            if element_type.singleton != Some(singleton) {
+                return Err(format!(
+                    "element type has singleton {:?}",
+                    element_type.singleton
+                ));
+            }
+
+            let children = provider
+                .get_children_type(&element_type)
+                .await
+                .map_err(|e| e.to_string())?;
+            match (singleton, children) {
+                (true, None) => Ok(()),
+                (false, Some(item)) if item.singleton == Some(true) => {
+                    expect_name(&item, element_type.name.as_deref().unwrap_or_default())
+                }
+                (_, children) => Err(format!("get_children_type returned {children:?}")),
+            }
+        }
+        .await;
+        let expected = if singleton { "singleton" } else { "collection" };
+        report.record(
+            ConformanceCategory::Cardinality,
+            format!("{parent}.{element} is a {expected}"),
+            outcome,
+        );
+    }
+}
+
+async fn check_case_sensitivity(provider: &dyn ModelProvider, report: &mut ConformanceReport) {
+    for (name, exists) in [
+        ("Patient", true),
+        ("patient", false),
+        ("PATIENT", false),
+        ("humanName", false),
+        ("Code", false),
+    ] {
+        let outcome = match provider.get_type(name).await {
+            Ok(found) if found.is_some() == exists => Ok(()),
+            Ok(found) => Err(format!("get_type returned {found:?}")),
+            Err(e) => Err(e.to_string()),
+        };
+        let expected = if exists {
+            "resolves"
+        } else {
+            "does not resolve"
+        };
+        report.record(
+            ConformanceCategory::CaseSensitivity,
+            format!("type {name} {expected}"),
+            outcome,
+        );
+    }
+
+    let outcome = match provider.resource_type_exists("patient").await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err("resource_type_exists returned true".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    report.record(
+        ConformanceCategory::CaseSensitivity,
+        "resource type patient does not exist".to_string(),
+        outcome,
+    );
+
+    let outcome = async {
+        let patient = require_type(provider, "Patient").await?;
+        match provider.get_element_type(&patient, "BirthDate").await {
+            Ok(None) => Ok(()),
+            Ok(found) => Err(format!("get_element_type returned {found:?}")),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+    .await;
+    report.record(
+        ConformanceCategory::CaseSensitivity,
+        "element Patient.BirthDate does not resolve".to_string(),
+        outcome,
+    );
+}
+
+async fn check_listings(provider: &dyn ModelProvider, report: &mut ConformanceReport) {
+    let outcome = match provider.get_primitive_types().await {
+        Ok(listed) => match R4_PRIMITIVES
+            .iter()
+            .find(|p| !listed.iter().any(|l| l == *p))
+        {
+            Some(missing) => Err(format!("{missing} missing from primitive types")),
+            None => Ok(()),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    report.record(
+        ConformanceCategory::TypeListings,
+        "all R4 primitive types are listed".to_string(),
+        outcome,
+    );
+
+    for &primitive in R4_PRIMITIVES {
+        let outcome = async {
+            let type_info = require_type(provider, primitive).await?;
+            let system_type = type_constants::to_system_type(primitive).unwrap_or("String");
+            if type_info.type_name != system_type {
+                return Err(format!(
+                    "type name is {} instead of {system_type}",
+                    type_info.type_name
+                ));
+            }
+            expect_name(&type_info, primitive)
+        }
+        .await;
+        report.record(
+            ConformanceCategory::TypeListings,
+            format!("primitive {primitive} maps to its System type"),
+            outcome,
+        );
+    }
+
+    let outcome = async {
+        let resources = provider
+            .get_resource_types()
+            .await
+            .map_err(|e| e.to_string())?;
+        for resource in ["Patient", "Observation", "Bundle"] {
+            if !resources.iter().any(|r| r == resource) {
+                return Err(format!("{resource} missing from resource types"));
+            }
+        }
+        if let Some(listed) = resources
+            .iter()
+            .find(|r| *r == "HumanName" || *r == "string")
+        {
+            return Err(format!("{listed} listed as a resource type"));
+        }
+        Ok(())
+    }
+    .await;
+    report.record(
+        ConformanceCategory::TypeListings,
+        "resource types list resources only".to_string(),
+        outcome,
+    );
+
+    let outcome = async {
+        let complex = provider
+            .get_complex_types()
+            .await
+            .map_err(|e| e.to_string())?;
+        for data_type in ["HumanName", "Quantity", "CodeableConcept"] {
+            if !complex.iter().any(|c| c == data_type) {
+                return Err(format!("{data_type} missing from complex types"));
+            }
+        }
+        if complex.iter().any(|c| c == "Patient") {
+            return Err("Patient listed as a complex type".to_string());
+        }
+        Ok(())
+    }
+    .await;
+    report.record(
+        ConformanceCategory::TypeListings,
+        "complex types list data types only".to_string(),
+        outcome,
+    );
+}
+
+async fn check_backbones(provider: &dyn ModelProvider, report: &mut ConformanceReport) {
+    let mut names: Vec<(String, Symbol)> = Vec::new();
+
+    for &(parent, backbone, child, child_type) in R4_BACKBONES {
+        let outcome = async {
+            let parent_type = require_type(provider, parent).await?;
+            let backbone_type = require_element(provider, &parent_type, backbone).await?;
+            let name = backbone_type
+                .name
+                .clone()
+                .ok_or("backbone element type has no name")?;
+            if !names
+                .iter()
+                .any(|(path, _)| *path == format!("{parent}.{backbone}"))
+            {
+                names.push((format!("{parent}.{backbone}"), name));
+            }
+
+            if !provider
+                .get_element_names(&backbone_type)
+                .iter()
+                .any(|n| n == child)
+            {
+                return Err(format!("{child} missing from get_element_names"));
+            }
+            let child_info = require_element(provider, &backbone_type, child).await?;
+            expect_name(&child_info, child_type)
+        }
+        .await;
+        report.record(
+            ConformanceCategory::BackboneElements,
+            format!("{parent}.{backbone}.{child} is a {child_type}"),
+            outcome,
+        );
+    }
+
+    let outcome = match names.iter().enumerate().find_map(|(i, (path, name))| {
+        names[i + 1..]
+            .iter()
+            .find(|(_, other)| other == name)
+            .map(|(other, _)| (path, other, name))
+    }) {
+        Some((path, other, name)) => Err(format!("{path} and {other} are both named {name}")),
+        None => Ok(()),
+    };
+    report.record(
+        ConformanceCategory::BackboneElements,
+        "backbone elements have distinct type names".to_string(),
+        outcome,
+    );
+}
+
+async fn require_type(provider: &dyn ModelProvider, name: &str) -> Result<TypeInfo, String> {
+    provider
+        .get_type(name)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("get_type({name}) returned None"))
+}
+
+async fn require_element(
+    provider: &dyn ModelProvider,
+    parent: &TypeInfo,
+    property: &str,
+) -> Result<TypeInfo, String> {
+    provider
+        .get_element_type(parent, property)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("get_element_type({property}) returned None"))
+}
+
+fn expect_name(type_info: &TypeInfo, expected: &str) -> Outcome {
+    if type_info.name.as_deref() == Some(expected) {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected type {expected}, got {:?}",
+            type_info.name.as_deref().unwrap_or(&type_info.type_name)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_provider::PackageModelProvider;
+    use crate::test_support::{el, fixture_definitions, sd};
+    use serde_json::Value as JsonValue;
+
+    /// The slice of R4 core the suite's expectations touch
+    fn r4_definitions() -> Vec<JsonValue> {
+        core_definitions(R4_CHOICES[2].2)
+    }
+
+    /// Core definitions with the given `Observation.value[x]` types
+    fn core_definitions(observation_values: &[&str]) -> Vec<JsonValue> {
+        let mut definitions = Vec::new();
+        let mut add = |name: &str, kind: &str, base: Option<&str>, elements: Vec<JsonValue>| {
+            let mut all = vec![el(name, "*", &[])];
+            all.extend(elements);
+            definitions.push(sd(name, kind, base, JsonValue::Array(all)));
+        };
+
+        add("Element", "complex-type", None, vec![]);
+        add("BackboneElement", "complex-type", Some("Element"), vec![]);
+        add("Resource", "resource", None, vec![]);
+        add("DomainResource", "resource", Some("Resource"), vec![]);
+
+        for &primitive in R4_PRIMITIVES {
+            let base = match primitive {
+                "code" | "id" | "markdown" => "string",
+                "url" | "canonical" | "uuid" | "oid" => "uri",
+                "positiveInt" | "unsignedInt" => "integer",
+                _ => "Element",
+            };
+            let system_type = format!(
+                "http://hl7.org/fhirpath/System.{}",
+                type_constants::to_system_type(primitive).unwrap_or("String")
+            );
+            add(
+                primitive,
+                "primitive-type",
+                Some(base),
+                vec![el(&format!("{primitive}.value"), "1", &[&system_type])],
+            );
+        }
+
+        for data_type in [
+            "Quantity",
+            "CodeableConcept",
+            "Identifier",
+            "Range",
+            "Ratio",
+            "SampledData",
+            "Period",
+            "Timing",
+            "Attachment",
+            "Reference",
+        ] {
+            add(data_type, "complex-type", Some("Element"), vec![]);
+        }
+        add("Age", "complex-type", Some("Quantity"), vec![]);
+        add("Duration", "complex-type", Some("Quantity"), vec![]);
+        add(
+            "HumanName",
+            "complex-type",
+            Some("Element"),
+            vec![
+                el("HumanName.family", "1", &["string"]),
+                el("HumanName.given", "*", &["string"]),
+            ],
+        );
+
+        add(
+            "Patient",
+            "resource",
+            Some("DomainResource"),
+            vec![
+                el("Patient.identifier", "*", &["Identifier"]),
+                el("Patient.name", "*", &["HumanName"]),
+                el("Patient.gender", "1", &["code"]),
+                el("Patient.birthDate", "1", &["date"]),
+                el("Patient.deceased[x]", "1", &["boolean", "dateTime"]),
+                el("Patient.multipleBirth[x]", "1", &["boolean", "integer"]),
+                el("Patient.contact", "*", &["BackboneElement"]),
+                el("Patient.contact.relationship", "*", &["CodeableConcept"]),
+                el("Patient.contact.name", "1", &["HumanName"]),
+                el("Patient.communication", "*", &["BackboneElement"]),
+                el("Patient.communication.language", "1", &["CodeableConcept"]),
+            ],
+        );
+        add(
+            "Observation",
+            "resource",
+            Some("DomainResource"),
+            vec![
+                el("Observation.status", "1", &["code"]),
+                el("Observation.category", "*", &["CodeableConcept"]),
+                el("Observation.effective[x]", "1", R4_CHOICES[3].2),
+                el("Observation.value[x]", "1", observation_values),
+                el("Observation.referenceRange", "*", &["BackboneElement"]),
+                el("Observation.referenceRange.low", "1", &["Quantity"]),
+                el("Observation.component", "*", &["BackboneElement"]),
+                el("Observation.component.code", "1", &["CodeableConcept"]),
+            ],
+        );
+        add(
+            "Bundle",
+            "resource",
+            Some("Resource"),
+            vec![
+                el("Bundle.entry", "*", &["BackboneElement"]),
+                el("Bundle.entry.fullUrl", "1", &["uri"]),
+            ],
+        );
+        definitions
+    }
+
+    #[tokio::test]
+    async fn test_package_provider_is_conformant() {
+        let provider = PackageModelProvider::from_structure_definitions(&r4_definitions()).unwrap();
+        let report = ConformanceSuite::r4().run(&provider).await;
+
+        report.assert_conformant();
+        assert!(
+            report
+                .check("Patient.contact.name is a HumanName")
+                .unwrap()
+                .passed()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_r5_observation_value_is_conformant() {
+        let mut observation_values = R4_CHOICES[2].2.to_vec();
+        observation_values.extend(["Attachment", "Reference"]);
+        let provider = PackageModelProvider::from_structure_definitions(&core_definitions(
+            &observation_values,
+        ))
+        .unwrap();
+        let report = ConformanceSuite::r4().run(&provider).await;
+
+        report.assert_conformant();
+        assert!(
+            report
+                .check(&format!(
+                    "Observation.value[x] expands to {}",
+                    R4_CHOICES[2].2.join(" | ")
+                ))
+                .unwrap()
+                .passed()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_report_records_checks() {
+        let provider =
+            PackageModelProvider::from_structure_definitions(&fixture_definitions()).unwrap();
+        let report = ConformanceSuite::r4()
+            .without(ConformanceCategory::BackboneElements)
+            .run(&provider)
+            .await;
+
+        assert!(!report.is_conformant());
+        assert_eq!(
+            report
+                .checks_in(ConformanceCategory::BackboneElements)
+                .count(),
+            0
+        );
+        assert!(
+            report
+                .check("Patient derives from Resource")
+                .unwrap()
+                .passed()
+        );
+        assert!(
+            report
+                .check("Resource does not derive from Patient")
+                .unwrap()
+                .passed()
+        );
+        assert!(
+            report
+                .check("type patient does not resolve")
+                .unwrap()
+                .passed()
+        );
+        assert!(
+            report
+                .check("HumanName.given is a collection")
+                .unwrap()
+                .passed()
+        );
+
+        let age = report.check("Age derives from Quantity").unwrap();
+        assert_eq!(
+            age.failure.as_deref(),
+            Some("is_type_derived_from returned false")
+        );
+        assert!(
+            report
+                .to_string()
+                .contains("[inheritance] Age derives from Quantity")
+        );
+    }
+}
@@ -54,6 +54,13 @@ pub enum ModelError {
         message: String,
     },
 
+    /// FHIR package not found or not resolvable
+    #[error("Package not found: {package}")]
+    PackageNotFound {
+        /// Package id (`name#version` or bare name)
+        package: String,
+    },
+
     /// Type incompatibility error
     #[error("Type incompatibility: expected {expected}, got {actual}")]
     TypeIncompatibility {
@@ -140,6 +147,13 @@ impl ModelError {
         }
     }
 
+    /// Create a package not found error
+    pub fn package_not_found(package: impl Into<String>) -> Self {
+        Self::PackageNotFound {
+            package: package.into(),
+        }
+    }
+
     /// Create a type incompatibility error
     pub fn type_incompatibility(expected: impl Into<String>, actual: impl Into<String>) -> Self {
         Self::TypeIncompatibility {
@@ -6,27 +6,44 @@
 use serde::{Deserialize, Serialize};
 
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::future::Future;
 use std::hash::{Hash, Hasher};
+use std::pin::Pin;
 
+use indexmap::IndexMap;
+use rust_decimal::RoundingStrategy;
 use rust_decimal::prelude::FromPrimitive;
+use serde_json::Value as JsonValue;
+
+use crate::error::{ModelError, Result};
+use crate::fhir_json::JsonNode;
+use crate::interner::Symbol;
+use crate::provider::{ModelProvider, TypeInfo, type_constants};
+use crate::temporal::{PrecisionDate, PrecisionDateTime, PrecisionTime};
+use crate::ucum;
 
 /// Lightweight type information for FHIRPath type() function
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Both parts are symbols, so annotating a value never allocates. Names read
+/// back from the serialized form are only interned if they already were, so
+/// untrusted input cannot grow the symbol table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeInfoResult {
     /// Type namespace (e.g., "FHIR", "System")
-    pub namespace: String,
+    pub namespace: Symbol,
     /// Type name (e.g., "Patient", "String")
-    pub name: String,
+    pub name: Symbol,
 }
 
 impl TypeInfoResult {
     /// Create new type info result
+    ///
+    /// Names known to the symbol table are shared; others are owned by the result.
     pub fn new(namespace: &str, name: &str) -> Self {
         Self {
-            namespace: namespace.to_string(),
-            name: name.to_string(),
+            namespace: Symbol::lookup_or_owned(namespace),
+            name: Symbol::lookup_or_owned(name),
         }
     }
 
@@ -42,6 +59,22 @@ impl TypeInfoResult {
 }
 
 /// Universal result type for FHIRPath expression evaluation
+///
+/// With the `serde` feature, results serialize to an externally tagged form
+/// that round-trips every variant:
+///
+/// ```json
+/// {"Collection": {"items": [
+///     {"Decimal": {"value": "1.50", "typeInfo": {"namespace": "System", "name": "Decimal"}}},
+///     {"Quantity": {"value": "5", "unit": "mg"}},
+///     {"DateTime": {"value": "2020-01-01T10:00:00+02:00"}},
+///     "Empty"
+/// ], "hasUndefinedOrder": true}}
+/// ```
+///
+/// Decimals are strings so their precision is kept, temporal values use their
+/// literal form without `@`, objects are `{"Object": {"properties": {...}}}`
+/// in property order, and `typeInfo` is left out when absent.
 #[derive(Debug, Clone)]
 pub enum EvaluationResult {
     /// No value or empty collection
@@ -62,14 +95,14 @@ pub enum EvaluationResult {
     /// 64-bit integer value (explicit)
     Integer64(i64, Option<TypeInfoResult>),
 
-    /// Date value in ISO format
-    Date(String, Option<TypeInfoResult>),
+    /// Date value with its precision
+    Date(PrecisionDate, Option<TypeInfoResult>),
 
-    /// DateTime value in ISO format
-    DateTime(String, Option<TypeInfoResult>),
+    /// DateTime value with its precision and timezone offset
+    DateTime(PrecisionDateTime, Option<TypeInfoResult>),
 
-    /// Time value in ISO format
-    Time(String, Option<TypeInfoResult>),
+    /// Time value with its precision
+    Time(PrecisionTime, Option<TypeInfoResult>),
 
     /// Quantity with value and unit
     Quantity(rust_decimal::Decimal, String, Option<TypeInfoResult>),
@@ -86,8 +119,8 @@ pub enum EvaluationResult {
 
     /// Key-value object representing complex FHIR types
     Object {
-        /// The object's properties
-        map: HashMap<String, EvaluationResult>,
+        /// The object's properties, in their original order
+        map: IndexMap<String, EvaluationResult>,
         /// Optional type information
         type_info: Option<TypeInfoResult>,
     },
@@ -135,20 +168,47 @@ impl EvaluationResult {
     }
 
     /// Create a Date result with System type
-    pub fn date(value: String) -> Self {
+    pub fn date(value: PrecisionDate) -> Self {
         EvaluationResult::Date(value, Some(TypeInfoResult::system("Date")))
     }
 
     /// Create a DateTime result with System type
-    pub fn datetime(value: String) -> Self {
+    pub fn datetime(value: PrecisionDateTime) -> Self {
         EvaluationResult::DateTime(value, Some(TypeInfoResult::system("DateTime")))
     }
 
     /// Create a Time result with System type
-    pub fn time(value: String) -> Self {
+    pub fn time(value: PrecisionTime) -> Self {
         EvaluationResult::Time(value, Some(TypeInfoResult::system("Time")))
     }
 
+    /// Create a temporal result with FHIR type from a FHIR JSON string
+    ///
+    /// `fhir_type` is one of `date`, `dateTime`, `instant` or `time`, and the
+    /// value must follow that type's FHIR format.
+    pub fn fhir_temporal(value: &str, fhir_type: &str) -> Result<Self> {
+        let type_info = Some(TypeInfoResult::fhir(fhir_type));
+        match fhir_type {
+            "date" => Ok(EvaluationResult::Date(value.parse()?, type_info)),
+            "dateTime" => Ok(EvaluationResult::DateTime(
+                PrecisionDateTime::parse_fhir(value)?,
+                type_info,
+            )),
+            "instant" => Ok(EvaluationResult::DateTime(
+                PrecisionDateTime::parse_fhir_instant(value)?,
+                type_info,
+            )),
+            "time" => Ok(EvaluationResult::Time(
+                PrecisionTime::parse_fhir(value)?,
+                type_info,
+            )),
+            _ => Err(ModelError::type_incompatibility(
+                "date, dateTime, instant or time",
+                fhir_type,
+            )),
+        }
+    }
+
     /// Create a Quantity result with System type
     pub fn quantity(value: rust_decimal::Decimal, unit: String) -> Self {
         EvaluationResult::Quantity(value, unit, Some(TypeInfoResult::system("Quantity")))
@@ -164,7 +224,7 @@ impl EvaluationResult {
     }
 
     /// Create an Object variant with just the map, no type information
-    pub fn object(map: HashMap<String, EvaluationResult>) -> Self {
+    pub fn object(map: IndexMap<String, EvaluationResult>) -> Self {
         EvaluationResult::Object {
             map,
             type_info: None,
@@ -173,7 +233,7 @@ impl EvaluationResult {
 
     /// Create an Object variant with type information
     pub fn typed_object(
-        map: HashMap<String, EvaluationResult>,
+        map: IndexMap<String, EvaluationResult>,
         type_namespace: &str,
         type_name: &str,
     ) -> Self {
@@ -252,9 +312,9 @@ impl EvaluationResult {
             EvaluationResult::Decimal(d, _) => d.to_string(),
             EvaluationResult::Integer(i, _) => i.to_string(),
             EvaluationResult::Integer64(i, _) => i.to_string(),
-            EvaluationResult::Date(d, _) => d.clone(),
-            EvaluationResult::DateTime(dt, _) => dt.clone(),
-            EvaluationResult::Time(t, _) => t.clone(),
+            EvaluationResult::Date(d, _) => d.to_string(),
+            EvaluationResult::DateTime(dt, _) => dt.to_string(),
+            EvaluationResult::Time(t, _) => t.to_string(),
             EvaluationResult::Quantity(val, unit, _) => {
                 format!("{val} '{unit}'")
             }
@@ -293,6 +353,433 @@ impl EvaluationResult {
             EvaluationResult::Object { .. } => "Object",
         }
     }
+
+    /// Build a result from FHIR JSON text, typing values with a model
+    ///
+    /// `type_info` is the type of the value; resources with a `resourceType`
+    /// are typed by it instead. Object properties keep their order and decimals
+    /// keep their digits as written. Temporal primitives are checked against
+    /// their FHIR format and integers against the range of their FHIR type.
+    /// The `_name` siblings holding ids and extensions of primitives are kept
+    /// as untyped objects under their own key, and `null` entries of primitive
+    /// arrays as `Empty` items, so `to_fhir_string` restores the input.
+    /// Properties the model does not know are converted without type information.
+    pub async fn from_fhir_str(
+        text: &str,
+        type_info: &TypeInfo,
+        model: &dyn ModelProvider,
+    ) -> Result<Self> {
+        let node = JsonNode::parse(text)?;
+        from_json(&node, Some(type_info), model).await
+    }
+
+    /// Build a result from a FHIR JSON value, like `from_fhir_str`
+    ///
+    /// The value only has the precision and property order `serde_json` kept
+    /// when it was read; use `from_fhir_str` to convert JSON text losslessly.
+    pub async fn from_fhir_json(
+        value: &JsonValue,
+        type_info: &TypeInfo,
+        model: &dyn ModelProvider,
+    ) -> Result<Self> {
+        let node = JsonNode::from_value(value);
+        from_json(&node, Some(type_info), model).await
+    }
+
+    /// Convert to FHIR JSON text, keeping property order and decimal digits
+    ///
+    /// `Empty` becomes `null`, Integer64 values become strings as FHIR requires,
+    /// and Quantities become FHIR Quantity objects with their UCUM code.
+    pub fn to_fhir_string(&self) -> Result<String> {
+        Ok(self.to_json_node()?.to_string())
+    }
+
+    /// Convert to a FHIR JSON value, like `to_fhir_string`
+    ///
+    /// Decimals are limited to the precision of `serde_json` numbers.
+    pub fn to_fhir_json(&self) -> Result<JsonValue> {
+        self.to_json_node()?.to_value()
+    }
+
+    fn to_json_node(&self) -> Result<JsonNode> {
+        Ok(match self {
+            EvaluationResult::Empty => JsonNode::Null,
+            EvaluationResult::Boolean(b, _) => JsonNode::Bool(*b),
+            EvaluationResult::String(s, _) => JsonNode::String(s.clone()),
+            EvaluationResult::Integer(i, _) => JsonNode::Number(i.to_string()),
+            EvaluationResult::Integer64(i, _) => JsonNode::String(i.to_string()),
+            EvaluationResult::Decimal(d, _) => decimal_to_json(d)?,
+            EvaluationResult::Date(d, _) => JsonNode::String(d.to_string()),
+            EvaluationResult::DateTime(dt, _) => JsonNode::String(dt.to_string()),
+            EvaluationResult::Time(t, _) => JsonNode::String(t.to_string()),
+            EvaluationResult::Quantity(value, unit, _) => {
+                let mut quantity = IndexMap::new();
+                quantity.insert("value".to_string(), decimal_to_json(value)?);
+                quantity.insert("unit".to_string(), JsonNode::String(unit.clone()));
+                let code = ucum::calendar_duration_unit(unit).unwrap_or(unit);
+                if ucum::is_valid_unit(code) {
+                    quantity.insert(
+                        "system".to_string(),
+                        JsonNode::String("http://unitsofmeasure.org".to_string()),
+                    );
+                    quantity.insert("code".to_string(), JsonNode::String(code.to_string()));
+                }
+                JsonNode::Object(quantity)
+            }
+            EvaluationResult::Collection { items, .. } => JsonNode::Array(
+                items
+                    .iter()
+                    .map(EvaluationResult::to_json_node)
+                    .collect::<Result<_>>()?,
+            ),
+            EvaluationResult::Object { map, .. } => JsonNode::Object(
+                map.iter()
+                    .map(|(key, value)| Ok((key.clone(), value.to_json_node()?)))
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+
+    /// FHIRPath equality (`=`)
+    ///
+    /// Empty when either side is empty or the values cannot be compared (e.g.
+    /// DateTimes of different precision, Quantities in incommensurable units).
+    /// Integers and Decimals compare numerically, Dates are promoted to
+    /// DateTimes, and collections are equal when their items are equal in order.
+    pub fn fhirpath_equals(&self, other: &Self) -> Option<bool> {
+        let (left, right) = (self.items(), other.items());
+        if left.is_empty() || right.is_empty() {
+            return None;
+        }
+        if left.len() != right.len() {
+            return Some(false);
+        }
+        all_of(left.iter().zip(&right).map(|(a, b)| a.item_equals(b)))
+    }
+
+    /// FHIRPath equivalence (`~`), which is never empty
+    ///
+    /// Strings compare ignoring case and with whitespace normalized, Decimals
+    /// are rounded to the precision of the less precise operand, temporal
+    /// values of different precision are not equivalent, and collection items
+    /// may appear in any order.
+    pub fn fhirpath_equivalent(&self, other: &Self) -> bool {
+        let (left, mut unmatched) = (self.items(), other.items());
+        if left.len() != unmatched.len() {
+            return false;
+        }
+        left.iter().all(|item| {
+            match unmatched
+                .iter()
+                .position(|candidate| item.item_equivalent(candidate))
+            {
+                Some(index) => {
+                    unmatched.swap_remove(index);
+                    true
+                }
+                None => false,
+            }
+        })
+    }
+
+    /// FHIRPath ordering (`<`, `<=`, `>`, `>=`)
+    ///
+    /// Empty unless both sides are single values of comparable types: numbers
+    /// (Integers promoted to Decimals), Strings, Dates/DateTimes and Times of
+    /// comparable precision, and Quantities in commensurable units.
+    pub fn fhirpath_compare(&self, other: &Self) -> Option<Ordering> {
+        let (left, right) = (self.singleton()?, other.singleton()?);
+        match (left, right) {
+            (EvaluationResult::String(a, _), EvaluationResult::String(b, _)) => Some(a.cmp(b)),
+            (EvaluationResult::Date(a, _), EvaluationResult::Date(b, _)) => a.fhirpath_compare(b),
+            (EvaluationResult::DateTime(a, _), EvaluationResult::DateTime(b, _)) => {
+                a.fhirpath_compare(b)
+            }
+            (EvaluationResult::Date(a, _), EvaluationResult::DateTime(b, _)) => {
+                PrecisionDateTime::from(*a).fhirpath_compare(b)
+            }
+            (EvaluationResult::DateTime(a, _), EvaluationResult::Date(b, _)) => {
+                a.fhirpath_compare(&PrecisionDateTime::from(*b))
+            }
+            (EvaluationResult::Time(a, _), EvaluationResult::Time(b, _)) => a.fhirpath_compare(b),
+            _ => {
+                if let (Some(a), Some(b)) = (left.as_decimal(), right.as_decimal()) {
+                    return Some(a.cmp(&b));
+                }
+                // Numbers are Quantities with the unit '1'
+                let (value_a, unit_a) = left.as_quantity()?;
+                let (value_b, unit_b) = right.as_quantity()?;
+                ucum::compare_quantities(value_a, unit_a, value_b, unit_b)
+            }
+        }
+    }
+
+    fn item_equals(&self, other: &Self) -> Option<bool> {
+        match (self, other) {
+            (EvaluationResult::Boolean(a, _), EvaluationResult::Boolean(b, _)) => Some(a == b),
+            (EvaluationResult::Collection { .. }, _) | (_, EvaluationResult::Collection { .. }) => {
+                self.fhirpath_equals(other)
+            }
+            (EvaluationResult::Object { map: a, .. }, EvaluationResult::Object { map: b, .. }) => {
+                if a.len() != b.len() || a.keys().any(|key| !b.contains_key(key)) {
+                    return Some(false);
+                }
+                all_of(a.iter().map(|(key, value)| value.fhirpath_equals(&b[key])))
+            }
+            _ => match self.fhirpath_compare(other) {
+                Some(ordering) => Some(ordering == Ordering::Equal),
+                // Temporal values and Quantities may be undetermined
+                None if self.is_comparable_with(other) => None,
+                None => Some(false),
+            },
+        }
+    }
+
+    fn item_equivalent(&self, other: &Self) -> bool {
+        match (self, other) {
+            (EvaluationResult::Boolean(a, _), EvaluationResult::Boolean(b, _)) => a == b,
+            (EvaluationResult::String(a, _), EvaluationResult::String(b, _)) => {
+                normalize_string(a) == normalize_string(b)
+            }
+            (
+                EvaluationResult::Quantity(a, unit_a, _),
+                EvaluationResult::Quantity(b, unit_b, _),
+            ) => ucum::quantities_equivalent(*a, unit_a, *b, unit_b),
+            (EvaluationResult::Collection { .. }, _) | (_, EvaluationResult::Collection { .. }) => {
+                self.fhirpath_equivalent(other)
+            }
+            (EvaluationResult::Object { map: a, .. }, EvaluationResult::Object { map: b, .. }) => {
+                a.len() == b.len()
+                    && a.iter().all(|(key, value)| {
+                        b.get(key)
+                            .is_some_and(|other| value.fhirpath_equivalent(other))
+                    })
+            }
+            _ => {
+                if let (Some(a), Some(b)) = (self.as_decimal(), other.as_decimal()) {
+                    // Rounded half away from zero, as the spec rounds 2.5 to 3
+                    let scale = a.scale().min(b.scale());
+                    let round = |value: rust_decimal::Decimal| {
+                        value.round_dp_with_strategy(scale, RoundingStrategy::MidpointAwayFromZero)
+                    };
+                    return round(a) == round(b);
+                }
+                self.fhirpath_compare(other) == Some(Ordering::Equal)
+            }
+        }
+    }
+
+    /// Whether an undetermined comparison comes from precision or units rather than types
+    fn is_comparable_with(&self, other: &Self) -> bool {
+        let temporal = |value: &Self| {
+            matches!(
+                value,
+                EvaluationResult::Date(..) | EvaluationResult::DateTime(..)
+            )
+        };
+        (temporal(self) && temporal(other))
+            || matches!(
+                (self, other),
+                (EvaluationResult::Time(..), EvaluationResult::Time(..))
+            )
+            || (self.as_quantity().is_some() && other.as_quantity().is_some())
+    }
+
+    /// Items of a collection, the value itself, or nothing for Empty
+    fn items(&self) -> Vec<&EvaluationResult> {
+        match self {
+            EvaluationResult::Empty => Vec::new(),
+            EvaluationResult::Collection { items, .. } => items.iter().collect(),
+            value => vec![value],
+        }
+    }
+
+    /// The single value, looking through one-item collections
+    fn singleton(&self) -> Option<&EvaluationResult> {
+        match self {
+            EvaluationResult::Empty => None,
+            EvaluationResult::Collection { items, .. } => match items.as_slice() {
+                [item] => item.singleton(),
+                _ => None,
+            },
+            value => Some(value),
+        }
+    }
+
+    fn as_decimal(&self) -> Option<rust_decimal::Decimal> {
+        match self {
+            EvaluationResult::Integer(i, _) | EvaluationResult::Integer64(i, _) => {
+                Some(rust_decimal::Decimal::from(*i))
+            }
+            EvaluationResult::Decimal(d, _) => Some(*d),
+            _ => None,
+        }
+    }
+
+    fn as_quantity(&self) -> Option<(rust_decimal::Decimal, &str)> {
+        match self {
+            EvaluationResult::Quantity(value, unit, _) => Some((*value, unit)),
+            _ => self.as_decimal().map(|value| (value, "1")),
+        }
+    }
+}
+
+type JsonConversion<'a> = Pin<Box<dyn Future<Output = Result<EvaluationResult>> + Send + 'a>>;
+
+/// Convert FHIR JSON, typed when `type_info` is known
+fn from_json<'a>(
+    value: &'a JsonNode,
+    type_info: Option<&'a TypeInfo>,
+    model: &'a dyn ModelProvider,
+) -> JsonConversion<'a> {
+    Box::pin(async move {
+        match value {
+            JsonNode::Null => Ok(EvaluationResult::Empty),
+            JsonNode::Array(values) => {
+                let mut items = Vec::with_capacity(values.len());
+                for item in values {
+                    items.push(from_json(item, type_info, model).await?);
+                }
+                Ok(EvaluationResult::collection(items))
+            }
+            JsonNode::Object(properties) => {
+                let resource_type = match properties.get("resourceType") {
+                    Some(JsonNode::String(resource_type)) => model.get_type(resource_type).await?,
+                    _ => None,
+                };
+                let object_type = resource_type.as_ref().or(type_info);
+
+                let mut map = IndexMap::with_capacity(properties.len());
+                for (key, property) in properties {
+                    let property_type = match object_type {
+                        Some(parent) if key != "resourceType" && !key.starts_with('_') => {
+                            model.get_element_type(parent, key).await?
+                        }
+                        _ => None,
+                    };
+                    map.insert(
+                        key.clone(),
+                        from_json(property, property_type.as_ref(), model).await?,
+                    );
+                }
+                Ok(EvaluationResult::Object {
+                    map,
+                    type_info: object_type.map(object_type_info),
+                })
+            }
+            primitive => primitive_from_json(primitive, type_info),
+        }
+    })
+}
+
+/// Convert a JSON primitive, checking it against its FHIR primitive type
+fn primitive_from_json(value: &JsonNode, type_info: Option<&TypeInfo>) -> Result<EvaluationResult> {
+    let fhir_type = type_info
+        .filter(|type_info| {
+            type_info
+                .namespace
+                .as_ref()
+                .is_some_and(|namespace| namespace == "FHIR")
+        })
+        .and_then(|type_info| type_info.name.clone());
+    let Some(fhir_type) = fhir_type else {
+        return Ok(match value {
+            JsonNode::Bool(b) => EvaluationResult::boolean(*b),
+            JsonNode::Number(n) => match n.parse() {
+                Ok(i) if JsonNode::is_integer_text(n) => EvaluationResult::integer(i),
+                _ => EvaluationResult::decimal(json_decimal(n)?),
+            },
+            JsonNode::String(s) => EvaluationResult::string(s.clone()),
+            _ => EvaluationResult::Empty,
+        });
+    };
+
+    let Some(primitive) = type_constants::primitive_type(&fhir_type) else {
+        return Err(ModelError::type_incompatibility(
+            fhir_type.as_str(),
+            value.to_string(),
+        ));
+    };
+    let type_result = Some(TypeInfoResult::fhir(&fhir_type));
+    let mismatch = || ModelError::type_incompatibility(fhir_type.as_str(), value.to_string());
+    // Integers are checked against the range of their FHIR type (`positiveInt` >= 1)
+    let integer = |text: &str| {
+        text.parse::<i64>()
+            .ok()
+            .filter(|i| primitive.is_in_range(*i))
+            .ok_or_else(mismatch)
+    };
+    match (primitive.system_type, value) {
+        ("Boolean", JsonNode::Bool(b)) => Ok(EvaluationResult::Boolean(*b, type_result)),
+        ("Integer", JsonNode::Number(n)) => {
+            integer(n).map(|i| EvaluationResult::Integer(i, type_result))
+        }
+        ("Long", JsonNode::String(n) | JsonNode::Number(n)) => {
+            integer(n).map(|i| EvaluationResult::Integer64(i, type_result))
+        }
+        ("Decimal", JsonNode::Number(n)) => {
+            Ok(EvaluationResult::Decimal(json_decimal(n)?, type_result))
+        }
+        ("String", JsonNode::String(s)) => Ok(EvaluationResult::String(s.clone(), type_result)),
+        ("Date" | "DateTime" | "Time", JsonNode::String(s)) => {
+            EvaluationResult::fhir_temporal(s, &fhir_type)
+        }
+        _ => Err(mismatch()),
+    }
+}
+
+/// Type of an object: its model type, or `BackboneElement` for backbone elements
+fn object_type_info(type_info: &TypeInfo) -> TypeInfoResult {
+    if type_info.is_backbone_element() {
+        return TypeInfoResult::fhir("BackboneElement");
+    }
+    let namespace = type_info
+        .namespace
+        .as_ref()
+        .map_or("FHIR", |namespace| namespace.as_str());
+    TypeInfoResult::new(
+        namespace,
+        type_info.name.as_ref().unwrap_or(&type_info.type_name),
+    )
+}
+
+/// Decimal from a JSON number, keeping the digits as written
+fn json_decimal(text: &str) -> Result<rust_decimal::Decimal> {
+    text.parse()
+        .or_else(|_| rust_decimal::Decimal::from_scientific(text))
+        .map_err(|_| ModelError::validation_error(format!("Invalid decimal '{text}'")))
+}
+
+/// JSON number with the digits of a decimal, including trailing zeros
+fn decimal_to_json(value: &rust_decimal::Decimal) -> Result<JsonNode> {
+    match JsonNode::parse(&value.to_string())? {
+        number @ JsonNode::Number(_) => Ok(number),
+        _ => Err(ModelError::validation_error(format!(
+            "Decimal {value} is not a JSON number"
+        ))),
+    }
+}
+
+/// Combine item equalities: false if any is false, else empty if any is empty
+fn all_of(results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    let mut result = Some(true);
+    for item in results {
+        match item {
+            Some(false) => return Some(false),
+            None => result = None,
+            Some(true) => {}
+        }
+    }
+    result
+}
+
+/// String for equivalence: lowercase with runs of whitespace as single spaces
+fn normalize_string(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
 }
 
 /// Universal conversion trait for transforming values into FHIRPath evaluation results
@@ -315,6 +802,12 @@ impl IntoEvaluationResult for &str {
     }
 }
 
+impl IntoEvaluationResult for Symbol {
+    fn to_evaluation_result(&self) -> EvaluationResult {
+        EvaluationResult::string(self.to_string())
+    }
+}
+
 impl IntoEvaluationResult for bool {
     fn to_evaluation_result(&self) -> EvaluationResult {
         EvaluationResult::boolean(*self)
@@ -347,6 +840,24 @@ impl IntoEvaluationResult for rust_decimal::Decimal {
     }
 }
 
+impl IntoEvaluationResult for PrecisionDate {
+    fn to_evaluation_result(&self) -> EvaluationResult {
+        EvaluationResult::date(*self)
+    }
+}
+
+impl IntoEvaluationResult for PrecisionDateTime {
+    fn to_evaluation_result(&self) -> EvaluationResult {
+        EvaluationResult::datetime(*self)
+    }
+}
+
+impl IntoEvaluationResult for PrecisionTime {
+    fn to_evaluation_result(&self) -> EvaluationResult {
+        EvaluationResult::time(*self)
+    }
+}
+
 // === Generic Container Implementations ===
 
 impl<T> IntoEvaluationResult for Option<T>
@@ -408,8 +919,8 @@ impl PartialEq for EvaluationResult {
                 EvaluationResult::Quantity(val_a, unit_a, _),
                 EvaluationResult::Quantity(val_b, unit_b, _),
             ) => {
-                // Quantities are equal if both value and unit match (normalized values)
-                val_a.normalize() == val_b.normalize() && unit_a == unit_b
+                // Quantities in commensurable units are equal if their canonical values match
+                ucum::comparison_key(*val_a, unit_a) == ucum::comparison_key(*val_b, unit_b)
             }
             (
                 EvaluationResult::Collection {
@@ -444,6 +955,9 @@ impl PartialOrd for EvaluationResult {
 }
 
 /// Implement total ordering for EvaluationResult
+///
+/// This order ranks values by variant first and is meant for sorting and
+/// collections; FHIRPath comparisons use `fhirpath_compare` instead.
 impl Ord for EvaluationResult {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
@@ -488,11 +1002,8 @@ impl Ord for EvaluationResult {
                 EvaluationResult::Quantity(val_a, unit_a, _),
                 EvaluationResult::Quantity(val_b, unit_b, _),
             ) => {
-                // Order by value first, then by unit string
-                match val_a.cmp(val_b) {
-                    Ordering::Equal => unit_a.cmp(unit_b),
-                    other => other,
-                }
+                // Order by dimension first, then by value in the canonical unit
+                ucum::comparison_key(*val_a, unit_a).cmp(&ucum::comparison_key(*val_b, unit_b))
             }
             (EvaluationResult::Quantity(_, _, _), _) => Ordering::Less,
             (_, EvaluationResult::Quantity(_, _, _)) => Ordering::Greater,
@@ -560,9 +1071,8 @@ impl Hash for EvaluationResult {
             EvaluationResult::DateTime(dt, _) => dt.hash(state),
             EvaluationResult::Time(t, _) => t.hash(state),
             EvaluationResult::Quantity(val, unit, _) => {
-                // Hash both normalized value and unit
-                val.normalize().hash(state);
-                unit.hash(state);
+                // Hash the dimension and canonical value for consistency with equality
+                ucum::comparison_key(*val, unit).hash(state);
             }
             EvaluationResult::Collection {
                 items,
@@ -590,6 +1100,250 @@ impl Hash for EvaluationResult {
     }
 }
 
+/// Serialized form of a borrowed `EvaluationResult`
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(rename = "EvaluationResult", rename_all_fields = "camelCase")]
+enum TaggedResultRef<'a> {
+    Empty,
+    Boolean {
+        value: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    String {
+        value: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Decimal {
+        value: rust_decimal::Decimal,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Integer {
+        value: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Integer64 {
+        value: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Date {
+        value: &'a PrecisionDate,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    DateTime {
+        value: &'a PrecisionDateTime,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Time {
+        value: &'a PrecisionTime,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Quantity {
+        value: rust_decimal::Decimal,
+        unit: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Collection {
+        items: &'a [EvaluationResult],
+        has_undefined_order: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+    Object {
+        properties: &'a IndexMap<String, EvaluationResult>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        type_info: Option<TypeInfoResult>,
+    },
+}
+
+/// Serialized form of an owned `EvaluationResult`
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "EvaluationResult", rename_all_fields = "camelCase")]
+enum TaggedResult {
+    Empty,
+    Boolean {
+        value: bool,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    String {
+        value: String,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Decimal {
+        value: rust_decimal::Decimal,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Integer {
+        value: i64,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Integer64 {
+        value: i64,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Date {
+        value: PrecisionDate,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    DateTime {
+        value: PrecisionDateTime,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Time {
+        value: PrecisionTime,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Quantity {
+        value: rust_decimal::Decimal,
+        unit: String,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Collection {
+        items: Vec<EvaluationResult>,
+        #[serde(default)]
+        has_undefined_order: bool,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+    Object {
+        properties: IndexMap<String, EvaluationResult>,
+        #[serde(default)]
+        type_info: Option<TypeInfoResult>,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for EvaluationResult {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let tagged = match self {
+            EvaluationResult::Empty => TaggedResultRef::Empty,
+            EvaluationResult::Boolean(value, type_info) => TaggedResultRef::Boolean {
+                value: *value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::String(value, type_info) => TaggedResultRef::String {
+                value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Decimal(value, type_info) => TaggedResultRef::Decimal {
+                value: *value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Integer(value, type_info) => TaggedResultRef::Integer {
+                value: *value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Integer64(value, type_info) => TaggedResultRef::Integer64 {
+                value: *value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Date(value, type_info) => TaggedResultRef::Date {
+                value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::DateTime(value, type_info) => TaggedResultRef::DateTime {
+                value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Time(value, type_info) => TaggedResultRef::Time {
+                value,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Quantity(value, unit, type_info) => TaggedResultRef::Quantity {
+                value: *value,
+                unit,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Collection {
+                items,
+                has_undefined_order,
+                type_info,
+            } => TaggedResultRef::Collection {
+                items,
+                has_undefined_order: *has_undefined_order,
+                type_info: type_info.clone(),
+            },
+            EvaluationResult::Object { map, type_info } => TaggedResultRef::Object {
+                properties: map,
+                type_info: type_info.clone(),
+            },
+        };
+        tagged.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for EvaluationResult {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        Ok(match TaggedResult::deserialize(deserializer)? {
+            TaggedResult::Empty => EvaluationResult::Empty,
+            TaggedResult::Boolean { value, type_info } => {
+                EvaluationResult::Boolean(value, type_info)
+            }
+            TaggedResult::String { value, type_info } => EvaluationResult::String(value, type_info),
+            TaggedResult::Decimal { value, type_info } => {
+                EvaluationResult::Decimal(value, type_info)
+            }
+            TaggedResult::Integer { value, type_info } => {
+                EvaluationResult::Integer(value, type_info)
+            }
+            TaggedResult::Integer64 { value, type_info } => {
+                EvaluationResult::Integer64(value, type_info)
+            }
+            TaggedResult::Date { value, type_info } => EvaluationResult::Date(value, type_info),
+            TaggedResult::DateTime { value, type_info } => {
+                EvaluationResult::DateTime(value, type_info)
+            }
+            TaggedResult::Time { value, type_info } => EvaluationResult::Time(value, type_info),
+            TaggedResult::Quantity {
+                value,
+                unit,
+                type_info,
+            } => EvaluationResult::Quantity(value, unit, type_info),
+            TaggedResult::Collection {
+                items,
+                has_undefined_order,
+                type_info,
+            } => EvaluationResult::Collection {
+                items,
+                has_undefined_order,
+                type_info,
+            },
+            TaggedResult::Object {
+                properties,
+                type_info,
+            } => EvaluationResult::Object {
+                map: properties,
+                type_info,
+            },
+        })
+    }
+}
+
 /// Convenience function for converting values to evaluation results
 pub fn convert_value_to_evaluation_result<T>(value: &T) -> EvaluationResult
 where
@@ -632,10 +1386,313 @@ mod tests {
         assert_eq!(result.count(), 3);
     }
 
+    #[test]
+    fn test_temporal_values() {
+        let late =
+            EvaluationResult::fhir_temporal("2020-01-01T10:00:00+02:00", "dateTime").unwrap();
+        let early = EvaluationResult::fhir_temporal("2020-01-01T09:00:00Z", "instant").unwrap();
+        assert!(late < early);
+        assert_eq!(late.to_string_value(), "2020-01-01T10:00:00+02:00");
+
+        let month = EvaluationResult::date("2020-01".parse().unwrap());
+        assert_eq!(month.to_string_value(), "2020-01");
+        assert!(EvaluationResult::fhir_temporal("2020-01-01T10:00", "dateTime").is_err());
+        assert!(EvaluationResult::fhir_temporal("2020-02-30", "date").is_err());
+        assert!(EvaluationResult::fhir_temporal("10:00:00", "string").is_err());
+    }
+
+    #[test]
+    fn test_quantity_unit_equivalence() {
+        use std::collections::HashSet;
+
+        let quantity = |value: &str, unit: &str| {
+            EvaluationResult::quantity(value.parse().unwrap(), unit.into())
+        };
+        assert_eq!(quantity("1000", "mg"), quantity("1", "g"));
+        assert!(quantity("1500", "mg") < quantity("2", "g"));
+        assert_ne!(quantity("1", "year"), quantity("1", "a"));
+        assert_ne!(quantity("1", "furlong"), quantity("1", "g"));
+
+        // Units outside the UCUM table are still equal to themselves
+        let tablets = quantity("2", "tablet");
+        assert_eq!(tablets.fhirpath_equals(&tablets), Some(true));
+        assert!(tablets.fhirpath_equivalent(&quantity("2.0", "tablet")));
+        assert_eq!(tablets.fhirpath_equals(&quantity("2", "[CFU]")), None);
+
+        let set: HashSet<_> = [
+            quantity("1000", "mg"),
+            quantity("1.0", "g"),
+            quantity("0", "Cel"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&quantity("273.15", "K")));
+    }
+
+    #[test]
+    fn test_fhirpath_comparison_semantics() {
+        let decimal = |value: &str| EvaluationResult::decimal(value.parse().unwrap());
+        let datetime = |value: &str| EvaluationResult::datetime(value.parse().unwrap());
+        let quantity = |value: &str, unit: &str| {
+            EvaluationResult::quantity(value.parse().unwrap(), unit.into())
+        };
+        let string = |value: &str| EvaluationResult::string(value.to_string());
+
+        // Integers and Decimals compare numerically, unlike the total order
+        assert_eq!(
+            EvaluationResult::integer(1).fhirpath_equals(&decimal("1.0")),
+            Some(true)
+        );
+        assert_eq!(
+            EvaluationResult::integer(2).fhirpath_compare(&decimal("1.5")),
+            Some(Ordering::Greater)
+        );
+        assert!(EvaluationResult::integer(2) < decimal("1.5"));
+        assert!(decimal("1.2").fhirpath_equivalent(&decimal("1.24")));
+        assert!(decimal("2.5").fhirpath_equivalent(&EvaluationResult::integer(3)));
+        assert!(decimal("0.5").fhirpath_equivalent(&decimal("1")));
+        assert!(decimal("-2.5").fhirpath_equivalent(&decimal("-3")));
+        assert!(!decimal("2.5").fhirpath_equivalent(&decimal("2")));
+        assert_eq!(
+            decimal("1.2").fhirpath_equals(&decimal("1.24")),
+            Some(false)
+        );
+
+        // Incomparable values give empty
+        assert_eq!(EvaluationResult::Empty.fhirpath_equals(&string("a")), None);
+        assert_eq!(
+            datetime("2020-01").fhirpath_equals(&datetime("2020-01-15")),
+            None
+        );
+        assert!(!datetime("2020-01").fhirpath_equivalent(&datetime("2020-01-15")));
+        assert_eq!(
+            quantity("1", "g").fhirpath_compare(&quantity("1", "m")),
+            None
+        );
+        assert_eq!(
+            string("1").fhirpath_equals(&EvaluationResult::integer(1)),
+            Some(false)
+        );
+        assert_eq!(
+            string("1").fhirpath_compare(&EvaluationResult::integer(1)),
+            None
+        );
+        assert_eq!(
+            EvaluationResult::boolean(true).fhirpath_compare(&EvaluationResult::boolean(false)),
+            None
+        );
+
+        assert_eq!(
+            EvaluationResult::date("2020-01-01".parse().unwrap())
+                .fhirpath_compare(&datetime("2020-01-01T10:00:00Z")),
+            None
+        );
+        assert_eq!(
+            quantity("1000", "mg").fhirpath_equals(&quantity("1", "g")),
+            Some(true)
+        );
+        assert_eq!(
+            quantity("1", "year").fhirpath_equals(&quantity("1", "a")),
+            None
+        );
+        assert!(quantity("1", "year").fhirpath_equivalent(&quantity("1", "a")));
+
+        assert!(string("Hello  World").fhirpath_equivalent(&string(" hello world")));
+        assert_eq!(
+            string("Hello").fhirpath_equals(&string("hello")),
+            Some(false)
+        );
+
+        // Collections: ordered for equality, unordered for equivalence
+        let ab = EvaluationResult::collection(vec![string("a"), string("b")]);
+        let ba = EvaluationResult::collection(vec![string("b"), string("a")]);
+        assert_eq!(ab.fhirpath_equals(&ba), Some(false));
+        assert!(ab.fhirpath_equivalent(&ba));
+        assert_eq!(ab.fhirpath_compare(&ba), None);
+        assert!(EvaluationResult::Empty.fhirpath_equivalent(&EvaluationResult::collection(vec![])));
+        assert_eq!(
+            EvaluationResult::collection(vec![string("a")]).fhirpath_compare(&string("b")),
+            Some(Ordering::Less)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fhir_json_round_trip() {
+        use crate::package_provider::PackageModelProvider;
+        use crate::provider::ModelProvider;
+        use crate::test_support::{el, fixture_definitions, sd};
+        use serde_json::json;
+
+        let mut definitions = fixture_definitions();
+        definitions.push(sd(
+            "Observation",
+            "resource",
+            Some("DomainResource"),
+            json!([
+                el("Observation", "*", &[]),
+                el("Observation.value[x]", "1", &["decimal", "time"]),
+                el("Observation.issued", "1", &["instant"]),
+                el("Observation.component", "*", &["BackboneElement"]),
+                el("Observation.component.count", "1", &["integer64"]),
+                el("Observation.component.rank", "1", &["positiveInt"]),
+                el("Observation.component.offset", "1", &["unsignedInt"]),
+            ]),
+        ));
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+        let resource = provider.get_type("Resource").await.unwrap().unwrap();
+
+        let source = r#"{"resourceType":"Observation","valueDecimal":1.50,"issued":"2020-01-01T10:00:00.000+02:00","component":[{"count":"9007199254740993","rank":1,"offset":0}],"extra":{"b":1,"a":2.0}}"#;
+        let result = EvaluationResult::from_fhir_str(source, &resource, &provider)
+            .await
+            .unwrap();
+        let EvaluationResult::Object { map, type_info } = &result else {
+            panic!("expected an object");
+        };
+        assert_eq!(type_info.as_ref().unwrap().name, "Observation");
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            vec![
+                "resourceType",
+                "valueDecimal",
+                "issued",
+                "component",
+                "extra"
+            ]
+        );
+        assert_eq!(
+            map["valueDecimal"],
+            EvaluationResult::fhir_decimal("1.50".parse().unwrap())
+        );
+        assert_eq!(map["valueDecimal"].to_string_value(), "1.50");
+        assert_eq!(map["issued"].type_name(), "DateTime");
+        let component = &map["component"];
+        let EvaluationResult::Collection { items, .. } = component else {
+            panic!("expected a collection");
+        };
+        let EvaluationResult::Object {
+            map: component,
+            type_info,
+        } = &items[0]
+        else {
+            panic!("expected an object");
+        };
+        assert_eq!(type_info.as_ref().unwrap().name, "BackboneElement");
+        assert_eq!(component["count"].type_name(), "Integer64");
+        assert_eq!(result.to_fhir_string().unwrap(), source);
+        assert_eq!(
+            result.to_fhir_json().unwrap(),
+            serde_json::from_str::<JsonValue>(source).unwrap()
+        );
+
+        // Primitive extensions and nulls in primitive arrays survive the round trip
+        let patient = json!({
+            "resourceType": "Patient",
+            "name": [{
+                "given": ["Ann", null],
+                "_given": [null, {"extension": [{"url": "http://example.org", "valueString": "x"}]}],
+                "family": "Doe"
+            }],
+            "deceasedDateTime": "2020-01"
+        });
+        let result = EvaluationResult::from_fhir_json(&patient, &resource, &provider)
+            .await
+            .unwrap();
+        assert_eq!(result.to_fhir_json().unwrap(), patient);
+
+        for invalid in [
+            r#"{"resourceType":"Observation","issued":"2020-01-01"}"#,
+            r#"{"resourceType":"Observation","valueDecimal":"1.5"}"#,
+            r#"{"resourceType":"Observation","valueDecimal":1.5"#,
+            // Integers outside the range of their FHIR type
+            r#"{"resourceType":"Observation","component":[{"rank":0}]}"#,
+            r#"{"resourceType":"Observation","component":[{"rank":2147483648}]}"#,
+            r#"{"resourceType":"Observation","component":[{"offset":-1}]}"#,
+            r#"{"resourceType":"Observation","component":[{"offset":1.5}]}"#,
+        ] {
+            assert!(
+                EvaluationResult::from_fhir_str(invalid, &resource, &provider)
+                    .await
+                    .is_err(),
+                "{invalid}"
+            );
+        }
+        let bounds =
+            r#"{"resourceType":"Observation","component":[{"rank":2147483647,"offset":0}]}"#;
+        assert!(
+            EvaluationResult::from_fhir_str(bounds, &resource, &provider)
+                .await
+                .is_ok()
+        );
+    }
+
     #[test]
     fn test_equality_with_decimal_normalization() {
         let d1 = EvaluationResult::decimal(rust_decimal::Decimal::new(100, 2)); // 1.00
         let d2 = EvaluationResult::decimal(rust_decimal::Decimal::new(1, 0)); // 1
         assert_eq!(d1, d2); // Should be equal due to normalization
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        use serde_json::json;
+
+        let mut properties = IndexMap::new();
+        properties.insert("status".to_string(), EvaluationResult::Empty);
+        properties.insert(
+            "count".to_string(),
+            EvaluationResult::Integer64(
+                9_007_199_254_740_993,
+                Some(TypeInfoResult::fhir("integer64")),
+            ),
+        );
+        let result = EvaluationResult::Collection {
+            items: vec![
+                EvaluationResult::Empty,
+                EvaluationResult::fhir_boolean(false),
+                EvaluationResult::String("untyped".to_string(), None),
+                EvaluationResult::decimal("1.50".parse().unwrap()),
+                EvaluationResult::integer(-3),
+                EvaluationResult::date("2020-01".parse().unwrap()),
+                EvaluationResult::datetime("2020-01-01T10:00:00.120+02:00".parse().unwrap()),
+                EvaluationResult::time("10:30".parse().unwrap()),
+                EvaluationResult::quantity("5.0".parse().unwrap(), "mg".to_string()),
+                EvaluationResult::typed_object(properties, "FHIR", "Observation"),
+            ],
+            has_undefined_order: true,
+            type_info: None,
+        };
+
+        let serialized = serde_json::to_string(&result).unwrap();
+        let deserialized: EvaluationResult = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, result);
+        // Type info and decimal precision are not part of equality
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+
+        assert_eq!(
+            serde_json::to_value(EvaluationResult::collection(vec![
+                EvaluationResult::decimal("1.50".parse().unwrap()),
+                EvaluationResult::Quantity("5".parse().unwrap(), "mg".to_string(), None),
+            ]))
+            .unwrap(),
+            json!({"Collection": {
+                "items": [
+                    {"Decimal": {"value": "1.50", "typeInfo": {"namespace": "System", "name": "Decimal"}}},
+                    {"Quantity": {"value": "5", "unit": "mg"}}
+                ],
+                "hasUndefinedOrder": false
+            }})
+        );
+
+        // Unknown type names stay owned instead of growing the symbol table
+        let untrusted = json!({"String": {
+            "value": "x",
+            "typeInfo": {"namespace": "Untrusted-7f3a", "name": "Untrusted-9c1e"}
+        }});
+        let deserialized: EvaluationResult = serde_json::from_value(untrusted.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&deserialized).unwrap(), untrusted);
+        assert!(Symbol::lookup("Untrusted-7f3a").is_none());
+        assert!(Symbol::lookup("Untrusted-9c1e").is_none());
+    }
 }
@@ -0,0 +1,368 @@
+//! Lossless JSON tree for FHIR JSON conversions
+//!
+//! `serde_json::Value` reads numbers as `f64` and sorts object properties
+//! unless the `arbitrary_precision` and `preserve_order` features are on for
+//! the whole build. FHIR decimals must keep their digits (`1.50` is not `1.5`)
+//! and resources read best in their original order, so `EvaluationResult`
+//! converts through this tree instead: numbers keep their text and objects are
+//! `IndexMap`s. It is read from JSON text with a small parser, or from a
+//! `serde_json::Value` with whatever precision and order that value has.
+
+use std::fmt::{self, Write};
+
+use indexmap::IndexMap;
+use serde_json::Value as JsonValue;
+
+use crate::error::{ModelError, Result};
+
+/// Deepest nesting of arrays and objects accepted in JSON text
+const MAX_DEPTH: usize = 128;
+
+/// JSON value keeping number text and property order
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonNode {
+    Null,
+    Bool(bool),
+    /// Number as written, e.g. `1.50` or `-2e3`
+    Number(String),
+    String(String),
+    Array(Vec<JsonNode>),
+    Object(IndexMap<String, JsonNode>),
+}
+
+impl JsonNode {
+    /// Parse JSON text
+    pub(crate) fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser {
+            input: text,
+            position: 0,
+            depth: 0,
+        };
+        parser.whitespace();
+        let node = parser.value()?;
+        parser.whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(node)
+    }
+
+    /// Tree of a `serde_json::Value`, in its property order and number precision
+    pub(crate) fn from_value(value: &JsonValue) -> Self {
+        match value {
+            JsonValue::Null => JsonNode::Null,
+            JsonValue::Bool(b) => JsonNode::Bool(*b),
+            JsonValue::Number(n) => JsonNode::Number(n.to_string()),
+            JsonValue::String(s) => JsonNode::String(s.clone()),
+            JsonValue::Array(items) => {
+                JsonNode::Array(items.iter().map(Self::from_value).collect())
+            }
+            JsonValue::Object(properties) => JsonNode::Object(
+                properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::from_value(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Convert to a `serde_json::Value`, with its number precision and order
+    pub(crate) fn to_value(&self) -> Result<JsonValue> {
+        Ok(match self {
+            JsonNode::Null => JsonValue::Null,
+            JsonNode::Bool(b) => JsonValue::Bool(*b),
+            JsonNode::Number(text) => JsonValue::Number(text.parse().map_err(|_| {
+                ModelError::validation_error(format!("Invalid JSON number '{text}'"))
+            })?),
+            JsonNode::String(s) => JsonValue::String(s.clone()),
+            JsonNode::Array(items) => {
+                JsonValue::Array(items.iter().map(Self::to_value).collect::<Result<_>>()?)
+            }
+            JsonNode::Object(properties) => JsonValue::Object(
+                properties
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), value.to_value()?)))
+                    .collect::<Result<_>>()?,
+            ),
+        })
+    }
+
+    /// Whether a number is written as an integer, without fraction or exponent
+    pub(crate) fn is_integer_text(text: &str) -> bool {
+        !text.contains(['.', 'e', 'E'])
+    }
+}
+
+/// Compact JSON text, with numbers as they were read
+impl fmt::Display for JsonNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonNode::Null => f.write_str("null"),
+            JsonNode::Bool(b) => write!(f, "{b}"),
+            JsonNode::Number(text) => f.write_str(text),
+            JsonNode::String(s) => write_string(f, s),
+            JsonNode::Array(items) => {
+                f.write_char('[')?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_char(']')
+            }
+            JsonNode::Object(properties) => {
+                f.write_char('{')?;
+                for (index, (key, value)) in properties.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", u32::from(c))?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Recursive-descent parser for JSON text
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<JsonNode> {
+        let rest = &self.input[self.position..];
+        match rest.chars().next() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => self.string().map(JsonNode::String),
+            Some('-' | '0'..='9') => self.number(),
+            _ if self.keyword("true") => Ok(JsonNode::Bool(true)),
+            _ if self.keyword("false") => Ok(JsonNode::Bool(false)),
+            _ if self.keyword("null") => Ok(JsonNode::Null),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<JsonNode>) -> Result<JsonNode> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nesting is too deep"));
+        }
+        self.depth += 1;
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    fn object(&mut self) -> Result<JsonNode> {
+        self.expect('{')?;
+        let mut properties = IndexMap::new();
+        self.whitespace();
+        if self.eat('}') {
+            return Ok(JsonNode::Object(properties));
+        }
+        loop {
+            self.whitespace();
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(':')?;
+            self.whitespace();
+            let value = self.value()?;
+            properties.insert(key, value);
+            self.whitespace();
+            if self.eat('}') {
+                return Ok(JsonNode::Object(properties));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonNode> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.whitespace();
+        if self.eat(']') {
+            return Ok(JsonNode::Array(items));
+        }
+        loop {
+            self.whitespace();
+            items.push(self.value()?);
+            self.whitespace();
+            if self.eat(']') {
+                return Ok(JsonNode::Array(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    /// A string token, decoded by `serde_json` once its end is found
+    fn string(&mut self) -> Result<String> {
+        let rest = &self.input[self.position..];
+        if !rest.starts_with('"') {
+            return Err(self.error("expected a string"));
+        }
+        let mut escaped = false;
+        let end = rest[1..]
+            .char_indices()
+            .find_map(|(index, c)| match c {
+                _ if escaped => {
+                    escaped = false;
+                    None
+                }
+                '\\' => {
+                    escaped = true;
+                    None
+                }
+                '"' => Some(index + 2),
+                _ => None,
+            })
+            .ok_or_else(|| self.error("unclosed string"))?;
+        let value = serde_json::from_str(&rest[..end]).map_err(|_| self.error("invalid string"))?;
+        self.position += end;
+        Ok(value)
+    }
+
+    /// `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`
+    fn number(&mut self) -> Result<JsonNode> {
+        let start = self.position;
+        self.eat('-');
+        if !self.eat('0') && self.digits() == 0 {
+            return Err(self.error("expected a digit"));
+        }
+        if self.eat('.') && self.digits() == 0 {
+            return Err(self.error("expected a digit after '.'"));
+        }
+        if self.eat('e') || self.eat('E') {
+            if !self.eat('+') {
+                self.eat('-');
+            }
+            if self.digits() == 0 {
+                return Err(self.error("expected an exponent"));
+            }
+        }
+        Ok(JsonNode::Number(
+            self.input[start..self.position].to_string(),
+        ))
+    }
+
+    fn digits(&mut self) -> usize {
+        let count = self.input[self.position..]
+            .bytes()
+            .take_while(u8::is_ascii_digit)
+            .count();
+        self.position += count;
+        count
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let matched = self.input[self.position..].starts_with(keyword);
+        if matched {
+            self.position += keyword.len();
+        }
+        matched
+    }
+
+    fn whitespace(&mut self) {
+        self.position += self.input[self.position..]
+            .bytes()
+            .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+            .count();
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{c}'")))
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.input[self.position..].starts_with(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> ModelError {
+        ModelError::validation_error(format!(
+            "Invalid JSON at position {}: {message}",
+            self.position
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keeps_text_and_order() {
+        let text = r#"{"b":[1.50,-0,2E+3,true,null],"a":{"s":"x\"é\n"},"":[]}"#;
+        let node = JsonNode::parse(text).unwrap();
+        let JsonNode::Object(properties) = &node else {
+            panic!("expected an object");
+        };
+        assert_eq!(properties.keys().collect::<Vec<_>>(), vec!["b", "a", ""]);
+        assert_eq!(properties["b"], {
+            JsonNode::Array(vec![
+                JsonNode::Number("1.50".to_string()),
+                JsonNode::Number("-0".to_string()),
+                JsonNode::Number("2E+3".to_string()),
+                JsonNode::Bool(true),
+                JsonNode::Null,
+            ])
+        });
+        assert_eq!(
+            node.to_string(),
+            r#"{"b":[1.50,-0,2E+3,true,null],"a":{"s":"x\"é\n"},"":[]}"#
+        );
+        assert_eq!(
+            JsonNode::parse(&node.to_string()).unwrap(),
+            node,
+            "written text reads back"
+        );
+
+        for invalid in [
+            "",
+            "{",
+            "[1,]",
+            "01",
+            "1.",
+            "-",
+            "\"x",
+            "tru",
+            "{\"a\" 1}",
+            "1 2",
+        ] {
+            assert!(JsonNode::parse(invalid).is_err(), "{invalid}");
+        }
+        let deep = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+        assert!(JsonNode::parse(&deep).is_err());
+    }
+}
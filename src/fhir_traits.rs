@@ -2,6 +2,10 @@
 //!
 //! This module provides traits for FHIR choice elements and resource metadata
 //! to enable polymorphic access in FHIRPath expressions.
+//!
+//! With the `derive` feature, all of them can be derived from the model
+//! definitions (`#[derive(ChoiceElement)]`, `#[derive(FhirResourceMetadata)]`,
+//! ...), configured with `#[fhir(...)]` attributes.
 
 /// Trait for FHIR choice element types.
 ///
@@ -84,6 +88,49 @@ pub trait FhirResourceMetadata {
 pub trait ToFhirJson {
     /// Convert this type to a JSON representation suitable for FHIRPath evaluation
     fn to_fhir_json(&self) -> serde_json::Value;
+
+    /// Convert this type to JSON, failing on values JSON cannot represent
+    ///
+    /// Derived implementations report serialization errors here, while their
+    /// `to_fhir_json` panics on them.
+    fn try_to_fhir_json(&self) -> serde_json::Result<serde_json::Value> {
+        Ok(self.to_fhir_json())
+    }
+}
+
+impl<T: ToFhirJson> ToFhirJson for Option<T> {
+    fn to_fhir_json(&self) -> serde_json::Value {
+        self.as_ref()
+            .map_or(serde_json::Value::Null, ToFhirJson::to_fhir_json)
+    }
+
+    fn try_to_fhir_json(&self) -> serde_json::Result<serde_json::Value> {
+        self.as_ref()
+            .map_or(Ok(serde_json::Value::Null), ToFhirJson::try_to_fhir_json)
+    }
+}
+
+impl<T: ToFhirJson + ?Sized> ToFhirJson for Box<T> {
+    fn to_fhir_json(&self) -> serde_json::Value {
+        (**self).to_fhir_json()
+    }
+
+    fn try_to_fhir_json(&self) -> serde_json::Result<serde_json::Value> {
+        (**self).try_to_fhir_json()
+    }
+}
+
+impl<T: ToFhirJson> ToFhirJson for Vec<T> {
+    fn to_fhir_json(&self) -> serde_json::Value {
+        serde_json::Value::Array(self.iter().map(ToFhirJson::to_fhir_json).collect())
+    }
+
+    fn try_to_fhir_json(&self) -> serde_json::Result<serde_json::Value> {
+        self.iter()
+            .map(ToFhirJson::try_to_fhir_json)
+            .collect::<serde_json::Result<_>>()
+            .map(serde_json::Value::Array)
+    }
 }
 
 /// Trait for FHIR primitive types that may have extensions
@@ -176,4 +223,161 @@ mod tests {
         assert!(TestObservation::is_choice_element("value"));
         assert!(!TestObservation::is_choice_element("status"));
     }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derived_traits() {
+        use crate::{
+            BackboneElement, ChoiceElement, FhirPrimitive, FhirReference, FhirResourceMetadata,
+            ToFhirJson,
+        };
+        use serde::Serialize;
+        use serde_json::json;
+
+        #[derive(ChoiceElement, ToFhirJson, Serialize)]
+        #[fhir(choice = "value")]
+        #[allow(dead_code)]
+        enum ObservationValue {
+            Quantity(f64),
+            String(String),
+            #[fhir(rename = "valueDateTime")]
+            DateTime(String),
+        }
+
+        #[derive(ChoiceElement, ToFhirJson, Serialize)]
+        #[fhir(choice = "effective")]
+        #[allow(dead_code)]
+        enum ObservationEffective {
+            DateTime(String),
+            Period(serde_json::Value),
+        }
+
+        #[derive(ChoiceElement, ToFhirJson, Serialize)]
+        #[fhir(choice = "value")]
+        #[allow(dead_code)]
+        enum ComponentValue {
+            Quantity(f64),
+            String(String),
+        }
+
+        #[derive(BackboneElement, ToFhirJson, Serialize)]
+        struct ObservationComponent {
+            id: Option<String>,
+            modifier_extension: Option<Vec<serde_json::Value>>,
+            #[fhir(choice)]
+            value: Option<ComponentValue>,
+        }
+
+        #[derive(FhirResourceMetadata, ToFhirJson, Serialize)]
+        #[serde(rename_all = "camelCase")]
+        #[fhir(choice = "component.value")]
+        struct Observation {
+            status: String,
+            #[fhir(choice)]
+            value: Option<ObservationValue>,
+            #[fhir(choice, rename = "effective")]
+            effective_value: Option<Box<ObservationEffective>>,
+            #[fhir(nested)]
+            component: Vec<ObservationComponent>,
+        }
+
+        #[derive(ToFhirJson)]
+        struct Unrepresentable {
+            counts: std::collections::BTreeMap<(u8, u8), u8>,
+        }
+
+        #[derive(FhirPrimitive)]
+        struct FhirString {
+            #[fhir(value)]
+            text: Option<String>,
+            extension: Option<Vec<serde_json::Value>>,
+        }
+
+        #[derive(FhirReference)]
+        struct Reference {
+            reference: Option<String>,
+            display: Option<String>,
+        }
+
+        assert_eq!(ObservationValue::base_name(), "value");
+        assert_eq!(
+            ObservationValue::possible_field_names(),
+            vec!["valueQuantity", "valueString", "valueDateTime"]
+        );
+
+        assert_eq!(Observation::resource_type(), "Observation");
+        assert_eq!(
+            Observation::choice_elements(),
+            &["value", "effective", "component.value"]
+        );
+        assert_eq!(
+            Observation::get_choice_field_names("effective"),
+            vec!["effectiveDateTime", "effectivePeriod"]
+        );
+        assert!(Observation::get_choice_field_names("status").is_empty());
+
+        let observation = Observation {
+            status: "final".to_string(),
+            value: Some(ObservationValue::String("high".to_string())),
+            effective_value: None,
+            component: vec![ObservationComponent {
+                id: Some("c1".to_string()),
+                modifier_extension: Some(vec![json!({"url": "http://example.org"})]),
+                value: Some(ComponentValue::Quantity(4.5)),
+            }],
+        };
+        assert_eq!(
+            observation.to_fhir_json(),
+            json!({
+                "status": "final",
+                "valueString": "high",
+                "component": [{
+                    "id": "c1",
+                    "modifierExtension": [{"url": "http://example.org"}],
+                    "valueQuantity": 4.5
+                }]
+            })
+        );
+        let dated = Observation {
+            status: "final".to_string(),
+            value: Some(ObservationValue::DateTime("2024-01-01".to_string())),
+            effective_value: Some(Box::new(ObservationEffective::Period(
+                json!({"start": "2024-01-01", "end": null}),
+            ))),
+            component: Vec::new(),
+        };
+        assert_eq!(
+            dated.to_fhir_json(),
+            json!({
+                "status": "final",
+                "valueDateTime": "2024-01-01",
+                "effectivePeriod": {"start": "2024-01-01"}
+            })
+        );
+
+        let unrepresentable = Unrepresentable {
+            counts: [((1, 2), 3)].into(),
+        };
+        assert!(unrepresentable.try_to_fhir_json().is_err());
+
+        let component = &observation.component[0];
+        assert_eq!(component.element_id(), Some("c1"));
+        assert!(component.has_modifier_extensions());
+
+        let string = FhirString {
+            text: Some("hello".to_string()),
+            extension: Some(Vec::new()),
+        };
+        assert_eq!(string.primitive_value(), Some(json!("hello")));
+        assert!(!string.has_extensions());
+        assert_eq!(string.get_extensions(), None);
+
+        let reference = Reference {
+            reference: Some("Patient/123".to_string()),
+            display: None,
+        };
+        assert_eq!(reference.referenced_type(), Some("Patient"));
+        assert_eq!(reference.referenced_id(), Some("123"));
+        assert_eq!(reference.display(), None);
+    }
 }
@@ -0,0 +1,308 @@
+//! Interned type names
+//!
+//! This module provides `Symbol`, a handle to an interned string used for type
+//! names and namespaces in `TypeInfo` and `TypeInfoResult`. Each distinct name
+//! is stored once for the lifetime of the process, so cloning a `Symbol` never
+//! allocates and comparing two interned symbols is a pointer comparison.
+//!
+//! The symbol table is shared by all providers: type annotations on
+//! `EvaluationResult` values must stay readable after the provider that
+//! produced them is gone. It starts with the System types and FHIR primitives,
+//! and only `Symbol::intern` adds to it, which providers call while loading
+//! their models, so the table stays bounded by the models. Every other way of
+//! making a symbol (`From<&str>`, deserialization, `TypeInfo::system_type`,
+//! `TypeInfoResult::new`) reuses an existing entry when there is one and
+//! otherwise keeps a reference-counted string that is freed with the value.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, LazyLock};
+
+use crate::provider::type_constants;
+
+/// Interned strings, keyed and valued by the single stored copy
+static SYMBOLS: LazyLock<papaya::HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    let symbols = papaya::HashMap::new();
+    {
+        let pinned = symbols.pin();
+        let primitives = type_constants::FHIR_PRIMITIVES
+            .iter()
+            .map(|primitive| primitive.name);
+        for name in WELL_KNOWN.iter().copied().chain(primitives) {
+            pinned.insert(name, name);
+        }
+    }
+    symbols
+});
+
+/// Names interned from the start, so annotating values with them never allocates
+const WELL_KNOWN: &[&str] = &[
+    "System",
+    "FHIR",
+    "Any",
+    "Boolean",
+    "String",
+    "Integer",
+    "Long",
+    "Decimal",
+    "Date",
+    "DateTime",
+    "Time",
+    "Quantity",
+    "Element",
+    "BackboneElement",
+    "Resource",
+    "DomainResource",
+    "Choice",
+];
+
+/// Interned type name or namespace
+///
+/// Equality between interned symbols compares addresses, so it costs the same
+/// as for an integer; symbols created by `Symbol::lookup_or_owned` fall back to
+/// comparing contents. Hashing, ordering and comparisons with plain strings
+/// use the string contents.
+#[derive(Clone)]
+pub struct Symbol(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    /// Entry of the process-wide symbol table
+    Interned(&'static str),
+    /// Name that was not in the table, owned by the values holding it
+    Owned(Arc<str>),
+}
+
+impl Symbol {
+    /// Intern a string, returning the existing symbol if it was interned before
+    pub fn intern(value: &str) -> Self {
+        if let Some(symbol) = Self::lookup(value) {
+            return symbol;
+        }
+        let leaked: &'static str = Box::leak(value.to_owned().into_boxed_str());
+        // Another thread may have interned the same string meanwhile; its copy wins
+        Symbol(Repr::Interned(SYMBOLS.pin().get_or_insert(leaked, leaked)))
+    }
+
+    /// The symbol for a string that was interned before, if any
+    pub fn lookup(value: &str) -> Option<Self> {
+        SYMBOLS
+            .pin()
+            .get(value)
+            .map(|interned| Symbol(Repr::Interned(interned)))
+    }
+
+    /// The interned symbol for a string, or an owned symbol without touching the table
+    ///
+    /// Use this for names from untrusted input, which must not grow the table.
+    pub fn lookup_or_owned(value: &str) -> Self {
+        Self::lookup(value).unwrap_or_else(|| Symbol(Repr::Owned(Arc::from(value))))
+    }
+
+    /// The symbol's string
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Interned(value) => value,
+            Repr::Owned(value) => value,
+        }
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Repr::Interned(a), Repr::Interned(b)) => std::ptr::eq(*a, *b),
+            _ => self.as_str() == other.as_str(),
+        }
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<Symbol> for str {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<Symbol> for &str {
+    fn eq(&self, other: &Symbol) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<Symbol> for String {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == other.as_str()
+    }
+}
+
+/// Conversions only reuse interned names; they never add to the table
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Symbol::lookup_or_owned(value)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(value: String) -> Self {
+        Symbol::lookup_or_owned(&value)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(value: &String) -> Self {
+        Symbol::lookup_or_owned(value)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(value: Symbol) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+        Ok(Symbol::lookup_or_owned(&value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interning_is_stable() {
+        let patient = Symbol::intern("Patient");
+        let again = Symbol::from(String::from("Patient"));
+        assert_eq!(patient, again);
+        assert!(std::ptr::eq(patient.as_str(), again.as_str()));
+        assert_ne!(patient, Symbol::intern("patient"));
+
+        assert_eq!(patient, "Patient");
+        assert_eq!(patient.to_string(), "Patient");
+        assert!(Symbol::intern("Observation") < patient);
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| Symbol::intern("Questionnaire.item")))
+            .collect();
+        let symbols: Vec<Symbol> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+        assert!(symbols.iter().all(|s| *s == symbols[0]));
+    }
+
+    #[test]
+    fn test_conversions_do_not_intern() {
+        use crate::provider::TypeInfo;
+
+        let string = Symbol::from("String");
+        assert!(std::ptr::eq(
+            string.as_str(),
+            Symbol::intern("String").as_str()
+        ));
+
+        let name = "Unknown.converted.type";
+        let type_info = TypeInfo::system_type(name, true);
+        assert_eq!(type_info.type_name, name);
+        assert_eq!(Symbol::from(name.to_string()), name);
+        assert!(Symbol::lookup(name).is_none());
+    }
+
+    #[test]
+    fn test_lookup_does_not_intern() {
+        let name = "Interner.lookup.only";
+        assert!(Symbol::lookup(name).is_none());
+        let owned = Symbol::lookup_or_owned(name);
+        assert_eq!(owned, name);
+        assert!(Symbol::lookup(name).is_none());
+
+        let interned = Symbol::intern(name);
+        assert_eq!(owned, interned);
+        let hash = |symbol: &Symbol| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            symbol.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&owned), hash(&interned));
+        assert!(std::ptr::eq(
+            Symbol::lookup_or_owned(name).as_str(),
+            interned.as_str()
+        ));
+    }
+}
@@ -30,15 +30,44 @@
 
 #![warn(missing_docs)]
 
+// Lets the derive macros' generated paths resolve in this crate's own tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as octofhir_fhir_model;
+
+pub mod cached_provider;
+pub mod canonical;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod error;
 pub mod evaluation;
 pub mod evaluator;
+mod fhir_json;
 pub mod fhir_traits;
+pub mod interner;
+pub mod package;
+pub mod package_provider;
 pub mod provider;
+pub mod reflection;
+pub mod registry;
+pub mod search;
 pub mod server;
+pub mod static_provider;
+pub mod structure_definition;
+pub mod temporal;
 pub mod terminology;
+#[cfg(test)]
+mod test_support;
+pub mod ucum;
+pub mod version_map;
 
 // Re-export core types
+pub use cached_provider::{ModelCacheConfig, ModelCacheStats};
+pub use canonical::{
+    Canonical, CanonicalResolver, CanonicalResourceType, InMemoryCanonicalResolver,
+    ResolvedCanonical, VersionPolicy,
+};
 pub use error::{ModelError, Result};
 pub use evaluation::{
     EvaluationResult, IntoEvaluationResult, TypeInfoResult, convert_value_to_evaluation_result,
@@ -51,13 +80,38 @@ pub use evaluator::{
 pub use fhir_traits::{
     BackboneElement, ChoiceElement, FhirPrimitive, FhirReference, FhirResourceMetadata, ToFhirJson,
 };
+pub use interner::Symbol;
+#[cfg(feature = "derive")]
+pub use octofhir_fhir_model_derive::{
+    BackboneElement, ChoiceElement, FhirPrimitive, FhirReference, FhirResourceMetadata, ToFhirJson,
+};
+pub use package::{
+    CanonicalEntry, FhirPackage, PackageCache, PackageId, PackageIndexEntry, PackageManifest,
+    PackageSet,
+};
+pub use package_provider::PackageModelProvider;
 pub use provider::{
-    ElementInfo, EmptyModelProvider, FhirVersion, LiteModelProvider, ModelProvider, TypeInfo,
-    type_constants,
+    BindingStrength, Cardinality, ElementBinding, ElementInfo, EmptyModelProvider,
+    ExtensionDefinition, FhirVersion, LiteModelProvider, ModelProvider, ProfileElementInfo,
+    SliceDefinition, SyncModelProvider, TypeInfo, type_constants,
+};
+pub use reflection::{ElementReflection, FhirType, TypeReflection};
+pub use registry::ModelProviderRegistry;
+pub use search::{
+    SearchParamType, SearchParameterDefinition, SearchParameterIssue, SearchParameterProvider,
+    check_search_parameter,
 };
 #[cfg(feature = "http-client")]
 pub use server::HttpServerProvider;
 pub use server::{NoOpServerProvider, ServerProvider};
+pub use static_provider::{StaticModel, StaticModelProvider};
+pub use structure_definition::{
+    ElementDefinition, ElementSlicing, ElementType, ExtensionContext, SlicingDiscriminator,
+    StructureDefinition, StructureDefinitionKind, TypeDerivation,
+};
+pub use temporal::{
+    PrecisionDate, PrecisionDateTime, PrecisionTime, TemporalPrecision, TimezoneOffset,
+};
 pub use terminology::{
     ConceptProperty, ConnectionStatus, EquivalenceLevel, ExpansionParameter, ExpansionParameters,
     LookupResult, NoOpTerminologyProvider, SubsumptionOutcome, SubsumptionResult,
@@ -69,12 +123,50 @@ pub use terminology::{
 #[cfg(feature = "http-client")]
 pub use terminology::HttpTerminologyProvider;
 
+#[cfg(feature = "caching")]
+pub use cached_provider::CachedModelProvider;
 #[cfg(feature = "caching")]
 pub use terminology::{CachedTerminologyProvider, LookupCacheKey, ValidationCacheKey};
 
 #[cfg(all(feature = "http-client", feature = "caching"))]
 pub use terminology::DefaultTerminologyProvider;
 
+pub use version_map::{ElementMapping, VersionMap};
+
+/// Items used by code generated from the derive macros
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+
+    use serde_json::Value;
+
+    /// Drop null members and empty arrays and objects, `None` if nothing is left
+    ///
+    /// Nulls inside arrays are kept, as they align primitive values with their
+    /// `_element` extensions.
+    pub fn prune(value: Value) -> Option<Value> {
+        match value {
+            Value::Null => None,
+            Value::Array(items) if items.is_empty() => None,
+            Value::Array(items) => Some(Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| prune(item).unwrap_or(Value::Null))
+                    .collect(),
+            )),
+            Value::Object(members) => {
+                let members: serde_json::Map<String, Value> = members
+                    .into_iter()
+                    .filter_map(|(name, member)| Some((name, prune(member)?)))
+                    .collect();
+                (!members.is_empty()).then_some(Value::Object(members))
+            }
+            value => Some(value),
+        }
+    }
+}
+
 /// Version information for this crate
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -0,0 +1,791 @@
+//! Offline FHIR NPM package support
+//!
+//! This module reads FHIR packages (as distributed on packages.fhir.org) from
+//! unpacked directories, `.tgz` archives (with the `package-archive` feature) and
+//! `~/.fhir/packages`-style cache directories. Loaded packages can be combined
+//! into a `PackageSet` that follows dependency chains and indexes every
+//! conformance resource by canonical URL and version.
+
+use indexmap::IndexMap;
+use serde_json::Value as JsonValue;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::canonical::Canonical;
+use crate::error::{ModelError, Result};
+use crate::provider::FhirVersion;
+
+/// Package identifier (`name#version`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackageId {
+    /// Package name (e.g. `hl7.fhir.r4.core`)
+    pub name: String,
+    /// Package version (e.g. `4.0.1`)
+    pub version: String,
+}
+
+impl PackageId {
+    /// Create a new package id
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+        }
+    }
+
+    /// Parse a `name#version` package id
+    pub fn parse(id: &str) -> Option<Self> {
+        let (name, version) = id.split_once('#')?;
+        if name.is_empty() || version.is_empty() {
+            return None;
+        }
+        Some(Self::new(name, version))
+    }
+}
+
+impl std::fmt::Display for PackageId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.name, self.version)
+    }
+}
+
+/// Parsed `package.json` manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageManifest {
+    /// Package name
+    pub name: String,
+    /// Package version
+    pub version: String,
+    /// FHIR versions the package targets (e.g. `["4.0.1"]`)
+    pub fhir_versions: Vec<String>,
+    /// Dependencies: package name -> version (or version pattern)
+    pub dependencies: IndexMap<String, String>,
+    /// Canonical base URL of the package
+    pub canonical: Option<String>,
+    /// Package description
+    pub description: Option<String>,
+}
+
+impl PackageManifest {
+    /// Parse a manifest from `package.json` content
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        let field = |name: &str| value.get(name).and_then(|v| v.as_str()).map(String::from);
+        let name = field("name")
+            .ok_or_else(|| ModelError::schema_load_error("package.json is missing 'name'"))?;
+        let version = field("version").ok_or_else(|| {
+            ModelError::schema_load_error(format!("package.json of {name} is missing 'version'"))
+        })?;
+
+        // Older packages use "fhir-version-list" instead of "fhirVersions"
+        let fhir_versions = ["fhirVersions", "fhir-version-list"]
+            .iter()
+            .find_map(|key| value.get(*key).and_then(|v| v.as_array()))
+            .map(|versions| {
+                versions
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let dependencies = value
+            .get("dependencies")
+            .and_then(|d| d.as_object())
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|(name, version)| {
+                        version.as_str().map(|v| (name.clone(), v.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            canonical: field("canonical"),
+            description: field("description"),
+            name,
+            version,
+            fhir_versions,
+            dependencies,
+        })
+    }
+
+    /// Package id of this manifest
+    pub fn id(&self) -> PackageId {
+        PackageId::new(&self.name, &self.version)
+    }
+
+    /// FHIR version targeted by the package
+    ///
+    /// Uses the declared `fhirVersions`, falling back to the core package
+    /// (e.g. `hl7.fhir.r4.core`) the package depends on or is.
+    pub fn fhir_version(&self) -> Option<FhirVersion> {
+        self.fhir_versions
+            .first()
+            .map(|v| FhirVersion::from_version_str(v))
+            .or_else(|| FhirVersion::from_core_package(&self.name))
+            .or_else(|| {
+                self.dependencies
+                    .keys()
+                    .find_map(|name| FhirVersion::from_core_package(name))
+            })
+    }
+}
+
+/// Entry of a package `.index.json`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageIndexEntry {
+    /// File name inside the `package/` folder
+    pub filename: String,
+    /// Resource type
+    pub resource_type: String,
+    /// Resource id
+    pub id: Option<String>,
+    /// Canonical URL
+    pub url: Option<String>,
+    /// Business version
+    pub version: Option<String>,
+    /// StructureDefinition kind
+    pub kind: Option<String>,
+    /// StructureDefinition type
+    pub type_name: Option<String>,
+}
+
+impl PackageIndexEntry {
+    fn from_resource(filename: &str, resource: &JsonValue) -> Option<Self> {
+        let field = |name: &str| {
+            resource
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+        Some(Self {
+            filename: filename.to_string(),
+            resource_type: field("resourceType")?,
+            id: field("id"),
+            url: field("url"),
+            version: field("version"),
+            kind: field("kind"),
+            type_name: field("type"),
+        })
+    }
+}
+
+/// A loaded FHIR package with its resources
+#[derive(Debug, Clone)]
+pub struct FhirPackage {
+    manifest: PackageManifest,
+    index: Vec<PackageIndexEntry>,
+    resources: Vec<Arc<JsonValue>>,
+}
+
+impl FhirPackage {
+    /// Load a package from an unpacked directory
+    ///
+    /// Accepts either the package root (containing `package/`) or the
+    /// `package/` directory itself. When `.index.json` is present only the
+    /// indexed files are read.
+    pub fn from_directory(path: impl AsRef<Path>) -> Result<Self> {
+        let mut dir = path.as_ref().to_path_buf();
+        if dir.join("package").is_dir() {
+            dir.push("package");
+        }
+
+        let manifest = read_json_file(&dir.join("package.json"))?;
+        let index_path = dir.join(".index.json");
+        let filenames: Vec<String> = if index_path.is_file() {
+            parse_index(&read_json_file(&index_path)?)
+                .into_iter()
+                .map(|entry| entry.filename)
+                .collect()
+        } else {
+            let mut names: Vec<String> = std::fs::read_dir(&dir)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| entry.file_name().to_str().map(String::from))
+                .filter(|name| name.ends_with(".json") && !is_metadata_file(name))
+                .collect();
+            names.sort();
+            names
+        };
+
+        let mut files = Vec::with_capacity(filenames.len());
+        for filename in filenames {
+            files.push((filename.clone(), read_json_file(&dir.join(&filename))?));
+        }
+        Self::from_parts(&manifest, files)
+    }
+
+    /// Load a package from a `.tgz` archive on disk
+    #[cfg(feature = "package-archive")]
+    pub fn from_tgz(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::File::open(path.as_ref())?;
+        Self::from_tgz_reader(file)
+    }
+
+    /// Load a package from a gzip-compressed tar stream
+    #[cfg(feature = "package-archive")]
+    pub fn from_tgz_reader(reader: impl std::io::Read) -> Result<Self> {
+        let mut manifest = None;
+        let mut files = Vec::new();
+
+        for (filename, content) in read_tgz_entries(reader)? {
+            if !filename.ends_with(".json") {
+                continue;
+            }
+            let json: JsonValue = serde_json::from_slice(&content).map_err(|e| {
+                ModelError::schema_load_error(format!("Failed to parse {filename}: {e}"))
+            })?;
+            match filename.as_str() {
+                "package.json" => manifest = Some(json),
+                ".index.json" => {}
+                _ => files.push((filename, json)),
+            }
+        }
+
+        let manifest = manifest.ok_or_else(|| {
+            ModelError::schema_load_error("Package archive does not contain package/package.json")
+        })?;
+        Self::from_parts(&manifest, files)
+    }
+
+    fn from_parts(manifest: &JsonValue, files: Vec<(String, JsonValue)>) -> Result<Self> {
+        let manifest = PackageManifest::from_json(manifest)?;
+        let mut index = Vec::with_capacity(files.len());
+        let mut resources = Vec::with_capacity(files.len());
+
+        for (filename, resource) in files {
+            if let Some(entry) = PackageIndexEntry::from_resource(&filename, &resource) {
+                index.push(entry);
+                resources.push(Arc::new(resource));
+            }
+        }
+
+        Ok(Self {
+            manifest,
+            index,
+            resources,
+        })
+    }
+
+    /// Package manifest
+    pub fn manifest(&self) -> &PackageManifest {
+        &self.manifest
+    }
+
+    /// Package id (`name#version`)
+    pub fn id(&self) -> PackageId {
+        self.manifest.id()
+    }
+
+    /// Index entries, one per resource
+    pub fn index(&self) -> &[PackageIndexEntry] {
+        &self.index
+    }
+
+    /// All resources in the package
+    pub fn resources(&self) -> impl Iterator<Item = &Arc<JsonValue>> {
+        self.resources.iter()
+    }
+
+    /// Resources of the given resource type
+    pub fn resources_of_type<'a>(
+        &'a self,
+        resource_type: &'a str,
+    ) -> impl Iterator<Item = &'a Arc<JsonValue>> + 'a {
+        self.index
+            .iter()
+            .zip(&self.resources)
+            .filter(move |(entry, _)| entry.resource_type == resource_type)
+            .map(|(_, resource)| resource)
+    }
+
+    /// StructureDefinition resources in the package
+    pub fn structure_definitions(&self) -> impl Iterator<Item = &Arc<JsonValue>> {
+        self.resources_of_type("StructureDefinition")
+    }
+}
+
+/// A conformance resource indexed by canonical URL
+#[derive(Debug, Clone)]
+pub struct CanonicalEntry {
+    /// Canonical URL
+    pub url: String,
+    /// Business version
+    pub version: Option<String>,
+    /// Resource type
+    pub resource_type: String,
+    /// Package the resource was loaded from
+    pub package: PackageId,
+    /// The resource itself
+    pub resource: Arc<JsonValue>,
+}
+
+/// A set of loaded packages with a canonical index over their resources
+///
+/// Packages are kept in load order; when the same canonical URL and version is
+/// defined by several packages the first loaded one wins.
+#[derive(Debug, Clone, Default)]
+pub struct PackageSet {
+    packages: Vec<FhirPackage>,
+    canonical_index: HashMap<String, Vec<CanonicalEntry>>,
+}
+
+impl PackageSet {
+    /// Create an empty package set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a package and index its conformance resources
+    ///
+    /// Adding a package whose name is already present is a no-op.
+    pub fn add_package(&mut self, package: FhirPackage) {
+        let id = package.id();
+        if self.contains(&id.name) {
+            return;
+        }
+
+        for (entry, resource) in package.index.iter().zip(&package.resources) {
+            let Some(url) = &entry.url else {
+                continue;
+            };
+            let versions = self.canonical_index.entry(url.clone()).or_default();
+            if versions.iter().any(|e| e.version == entry.version) {
+                continue;
+            }
+            versions.push(CanonicalEntry {
+                url: url.clone(),
+                version: entry.version.clone(),
+                resource_type: entry.resource_type.clone(),
+                package: id.clone(),
+                resource: Arc::clone(resource),
+            });
+        }
+        self.packages.push(package);
+    }
+
+    /// Whether a package with this name is loaded
+    pub fn contains(&self, name: &str) -> bool {
+        self.packages.iter().any(|p| p.manifest.name == name)
+    }
+
+    /// Loaded packages in load order
+    pub fn packages(&self) -> &[FhirPackage] {
+        &self.packages
+    }
+
+    /// FHIR version of the first package that declares one
+    pub fn fhir_version(&self) -> Option<FhirVersion> {
+        self.packages.iter().find_map(|p| p.manifest.fhir_version())
+    }
+
+    /// All indexed versions of a canonical URL
+    pub fn canonical_versions(&self, url: &str) -> &[CanonicalEntry] {
+        self.canonical_index
+            .get(url)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Resolve a canonical reference (`url`, `url|version` or `url|partial-version`)
+    ///
+    /// The highest version matching the requested one is returned.
+    pub fn resolve(&self, canonical: &str) -> Option<&CanonicalEntry> {
+        let canonical = Canonical::parse(canonical);
+        self.canonical_index
+            .get(&canonical.url)?
+            .iter()
+            .filter(|e| canonical.matches_version(e.version.as_deref()))
+            .max_by(|a, b| {
+                compare_versions(
+                    a.version.as_deref().unwrap_or(""),
+                    b.version.as_deref().unwrap_or(""),
+                )
+            })
+    }
+
+    /// Iterate over every indexed conformance resource
+    pub fn canonical_entries(&self) -> impl Iterator<Item = &CanonicalEntry> {
+        self.canonical_index.values().flatten()
+    }
+}
+
+/// FHIR package cache laid out like `~/.fhir/packages`
+///
+/// Each package lives in a `<name>#<version>/package/` folder below the root.
+#[derive(Debug, Clone)]
+pub struct PackageCache {
+    root: PathBuf,
+}
+
+impl PackageCache {
+    /// Create a cache rooted at the given directory
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The user's default package cache (`~/.fhir/packages`)
+    pub fn user_default() -> Result<Self> {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .ok_or_else(|| {
+                ModelError::invalid_configuration("Cannot locate home directory for package cache")
+            })?;
+        Ok(Self::new(
+            PathBuf::from(home).join(".fhir").join("packages"),
+        ))
+    }
+
+    /// Root directory of the cache
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory of a package in the cache
+    pub fn package_path(&self, id: &PackageId) -> PathBuf {
+        self.root.join(id.to_string())
+    }
+
+    /// Whether a package version is present in the cache
+    pub fn contains(&self, id: &PackageId) -> bool {
+        self.package_path(id).join("package").is_dir()
+    }
+
+    /// All packages present in the cache, sorted by id
+    pub fn list_packages(&self) -> Result<Vec<PackageId>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut ids: Vec<PackageId> = std::fs::read_dir(&self.root)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().join("package").is_dir())
+            .filter_map(|entry| entry.file_name().to_str().and_then(PackageId::parse))
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Cached versions of a package, lowest first
+    pub fn versions(&self, name: &str) -> Result<Vec<String>> {
+        let mut versions: Vec<String> = self
+            .list_packages()?
+            .into_iter()
+            .filter(|id| id.name == name)
+            .map(|id| id.version)
+            .collect();
+        versions.sort_by(|a, b| compare_versions(a, b));
+        Ok(versions)
+    }
+
+    /// Find the highest cached version matching a version spec
+    ///
+    /// Supports exact versions, `x`/`*` wildcards (e.g. `4.0.x`) and `latest`.
+    pub fn resolve_version(&self, name: &str, spec: &str) -> Result<String> {
+        self.versions(name)?
+            .into_iter()
+            .rev()
+            .find(|version| version_matches(spec, version))
+            .ok_or_else(|| ModelError::package_not_found(format!("{name}#{spec}")))
+    }
+
+    /// Load a single package from the cache
+    pub fn load(&self, name: &str, version_spec: &str) -> Result<FhirPackage> {
+        let version = self.resolve_version(name, version_spec)?;
+        FhirPackage::from_directory(self.package_path(&PackageId::new(name, version)))
+    }
+
+    /// Load a package and, transitively, all of its dependencies
+    ///
+    /// The requested package is loaded first, followed by its dependencies in
+    /// breadth-first order. A dependency already loaded under another version is
+    /// not loaded again.
+    pub fn load_with_dependencies(&self, name: &str, version_spec: &str) -> Result<PackageSet> {
+        let mut set = PackageSet::new();
+        let mut queue = VecDeque::from([(name.to_string(), version_spec.to_string())]);
+        let mut seen = HashSet::new();
+
+        while let Some((name, spec)) = queue.pop_front() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let package = self.load(&name, &spec)?;
+            for (dep_name, dep_spec) in &package.manifest.dependencies {
+                queue.push_back((dep_name.clone(), dep_spec.clone()));
+            }
+            set.add_package(package);
+        }
+
+        Ok(set)
+    }
+
+    /// Extract a `.tgz` package into the cache and return its id
+    #[cfg(feature = "package-archive")]
+    pub fn install_tgz(&self, path: impl AsRef<Path>) -> Result<PackageId> {
+        let entries = read_tgz_entries(std::fs::File::open(path.as_ref())?)?;
+        let manifest = entries
+            .iter()
+            .find(|(name, _)| name == "package.json")
+            .ok_or_else(|| {
+                ModelError::schema_load_error(
+                    "Package archive does not contain package/package.json",
+                )
+            })?;
+        let manifest =
+            PackageManifest::from_json(&serde_json::from_slice(&manifest.1).map_err(|e| {
+                ModelError::schema_load_error(format!("Failed to parse package.json: {e}"))
+            })?)?;
+
+        let id = manifest.id();
+        let target = self.package_path(&id).join("package");
+        std::fs::create_dir_all(&target)?;
+        for (filename, content) in entries {
+            std::fs::write(target.join(filename), content)?;
+        }
+        Ok(id)
+    }
+}
+
+/// Compare two package/resource versions
+///
+/// Numeric dot-separated segments are compared numerically; a pre-release
+/// suffix (`-ballot`, `-snapshot1`) sorts before the release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_core, a_pre) = a.split_once('-').map_or((a, None), |(c, p)| (c, Some(p)));
+    let (b_core, b_pre) = b.split_once('-').map_or((b, None), |(c, p)| (c, Some(p)));
+
+    let mut a_parts = a_core.split('.');
+    let mut b_parts = b_core.split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => break,
+            (a_part, b_part) => {
+                let a_part = a_part.unwrap_or("0");
+                let b_part = b_part.unwrap_or("0");
+                let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+                    (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+                    _ => a_part.cmp(b_part),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a_pre), Some(b_pre)) => a_pre.cmp(b_pre),
+    }
+}
+
+/// Whether a version satisfies a version spec (`4.0.1`, `4.0.x`, `latest`)
+pub fn version_matches(spec: &str, version: &str) -> bool {
+    if matches!(spec, "latest" | "*" | "") || spec == version {
+        return true;
+    }
+    let mut spec_parts = spec.split('.');
+    let mut version_parts = version.split('.');
+    loop {
+        match (spec_parts.next(), version_parts.next()) {
+            (None, None) => return true,
+            (Some("x" | "X" | "*"), _) => return true,
+            (Some(s), Some(v)) if s == v => continue,
+            _ => return false,
+        }
+    }
+}
+
+fn is_metadata_file(name: &str) -> bool {
+    matches!(name, "package.json" | ".index.json")
+}
+
+fn read_json_file(path: &Path) -> Result<JsonValue> {
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        ModelError::schema_load_error(format!("Failed to parse {}: {e}", path.display()))
+    })
+}
+
+fn parse_index(index: &JsonValue) -> Vec<PackageIndexEntry> {
+    index
+        .get("files")
+        .and_then(|f| f.as_array())
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|entry| {
+                    let filename = entry.get("filename")?.as_str()?;
+                    PackageIndexEntry::from_resource(filename, entry)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Read the files directly below the top-level folder of a package archive
+#[cfg(feature = "package-archive")]
+fn read_tgz_entries(reader: impl std::io::Read) -> Result<Vec<(String, Vec<u8>)>> {
+    use std::io::Read;
+
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    let mut entries = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let mut components = path.components();
+        // Skip the top-level folder (normally `package/`); ignore nested folders
+        // such as `package/example/` or `package/other/`.
+        if components.next().is_none() {
+            continue;
+        }
+        let rest: Vec<_> = components.collect();
+        let [file] = rest.as_slice() else {
+            continue;
+        };
+        let Some(filename) = file.as_os_str().to_str().map(String::from) else {
+            continue;
+        };
+
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        entries.push((filename, content));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_package(root: &Path, name: &str, version: &str, deps: JsonValue) {
+        let dir = root.join(format!("{name}#{version}")).join("package");
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = json!({
+            "name": name,
+            "version": version,
+            "fhirVersions": ["4.0.1"],
+            "dependencies": deps
+        });
+        std::fs::write(dir.join("package.json"), manifest.to_string()).unwrap();
+        let value_set = json!({
+            "resourceType": "ValueSet",
+            "id": format!("{name}-vs"),
+            "url": format!("http://example.org/{name}/ValueSet/vs"),
+            "version": version
+        });
+        std::fs::write(dir.join("ValueSet-vs.json"), value_set.to_string()).unwrap();
+    }
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fhir-model-{label}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_version_ordering_and_matching() {
+        assert_eq!(compare_versions("4.0.1", "4.0.10"), Ordering::Less);
+        assert_eq!(compare_versions("5.0.0", "5.0.0-ballot"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert!(version_matches("4.0.x", "4.0.1"));
+        assert!(!version_matches("4.0.x", "4.3.0"));
+        assert!(version_matches("latest", "6.1.0"));
+        assert_eq!(
+            PackageId::parse("hl7.fhir.r4.core#4.0.1"),
+            Some(PackageId::new("hl7.fhir.r4.core", "4.0.1"))
+        );
+    }
+
+    #[test]
+    fn test_cache_resolves_dependencies() {
+        let root = temp_dir("cache");
+        write_package(&root, "base.core", "1.0.0", json!({}));
+        write_package(&root, "base.core", "1.1.0", json!({}));
+        write_package(&root, "my.ig", "0.1.0", json!({"base.core": "1.x"}));
+
+        let cache = PackageCache::new(&root);
+        assert_eq!(cache.list_packages().unwrap().len(), 3);
+        assert_eq!(cache.resolve_version("base.core", "1.x").unwrap(), "1.1.0");
+
+        let set = cache.load_with_dependencies("my.ig", "0.1.0").unwrap();
+        assert!(matches!(
+            cache.load("missing.pkg", "1.0.0"),
+            Err(ModelError::PackageNotFound { .. })
+        ));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(set.packages().len(), 2);
+        assert_eq!(set.fhir_version(), Some(FhirVersion::R4));
+        let entry = set
+            .resolve("http://example.org/base.core/ValueSet/vs")
+            .unwrap();
+        assert_eq!(entry.version.as_deref(), Some("1.1.0"));
+        assert_eq!(entry.package, PackageId::new("base.core", "1.1.0"));
+        assert!(
+            set.resolve("http://example.org/my.ig/ValueSet/vs|0.1.0")
+                .is_some()
+        );
+        assert!(
+            set.resolve("http://example.org/my.ig/ValueSet/vs|9.9.9")
+                .is_none()
+        );
+    }
+
+    #[cfg(feature = "package-archive")]
+    #[test]
+    fn test_tgz_package() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut append = |path: &str, content: JsonValue| {
+            let data = content.to_string().into_bytes();
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, data.as_slice())
+                .unwrap();
+        };
+        append(
+            "package/package.json",
+            json!({"name": "tgz.pkg", "version": "2.0.0"}),
+        );
+        append(
+            "package/CodeSystem-cs.json",
+            json!({"resourceType": "CodeSystem", "url": "http://example.org/cs"}),
+        );
+        append(
+            "package/example/Patient-p.json",
+            json!({"resourceType": "Patient", "id": "p"}),
+        );
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+
+        let package = FhirPackage::from_tgz_reader(bytes.as_slice()).unwrap();
+        assert_eq!(package.id(), PackageId::new("tgz.pkg", "2.0.0"));
+        assert_eq!(package.index().len(), 1);
+        assert_eq!(package.resources_of_type("CodeSystem").count(), 1);
+
+        let root = temp_dir("install");
+        let archive = root.join("tgz.pkg.tgz");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(&archive, &bytes).unwrap();
+        let cache = PackageCache::new(root.join("packages"));
+        let id = cache.install_tgz(&archive).unwrap();
+        let loaded = cache.load(&id.name, &id.version).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(loaded.resources().count(), 1);
+    }
+}
@@ -0,0 +1,1860 @@
+//! ModelProvider backed by StructureDefinitions from FHIR packages
+//!
+//! This module provides `PackageModelProvider`, which answers type queries from
+//! the snapshots of StructureDefinitions found in unpacked FHIR NPM packages
+//! (for example `hl7.fhir.r4.core`). Everything is loaded from disk up front, so
+//! the provider works completely offline.
+
+use async_trait::async_trait;
+use indexmap::IndexMap;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::canonical::Canonical;
+use crate::error::Result;
+use crate::interner::Symbol;
+use crate::package::{FhirPackage, PackageSet, compare_versions};
+use crate::provider::{
+    Cardinality, ChoiceTypeInfo, ElementInfo, ExtensionDefinition, FhirVersion, ModelProvider,
+    ProfileElementInfo, SliceDefinition, SyncModelProvider, TypeInfo, type_constants,
+};
+use crate::reflection::FhirType;
+use crate::search::{
+    SearchParameterDefinition, SearchParameterIssue, SearchParameterProvider,
+    check_search_parameter,
+};
+use crate::structure_definition::{
+    ElementDefinition, ExtensionContext, StructureDefinition, StructureDefinitionKind,
+    TypeDerivation, choice_suffix,
+};
+
+/// Canonical URL prefix of the core FHIR StructureDefinitions
+pub(crate) const CORE_STRUCTURE_DEFINITION_PREFIX: &str =
+    "http://hl7.org/fhir/StructureDefinition/";
+
+/// FHIRPath System types that can be looked up by name
+const SYSTEM_TYPES: &[&str] = &[
+    "Boolean", "String", "Integer", "Long", "Decimal", "Date", "DateTime", "Time", "Quantity",
+];
+
+/// Type definition indexed from a StructureDefinition
+#[derive(Debug, Clone)]
+pub(crate) struct TypeModel {
+    pub(crate) kind: StructureDefinitionKind,
+    pub(crate) is_abstract: bool,
+    base_url: Option<String>,
+    /// System type backing a primitive (from its `value` element)
+    pub(crate) system_type: Option<Symbol>,
+    /// Namespace the type is reported under (`FHIR` unless registered otherwise)
+    pub(crate) namespace: Symbol,
+}
+
+/// Profile (constraint) indexed from a StructureDefinition
+#[derive(Debug, Clone)]
+struct ProfileModel {
+    /// Canonical URL without version
+    url: String,
+    /// Business version of the profile
+    version: Option<String>,
+    /// Constrained type
+    type_name: String,
+    /// Canonical URL of the base definition (a type or another profile)
+    base_url: Option<String>,
+    /// Element id -> definition, from the snapshot or else the differential
+    elements: IndexMap<String, ElementDefinition>,
+    /// Where the extension may be used, for extension definitions
+    contexts: Vec<ExtensionContext>,
+}
+
+/// ModelProvider implementation backed by StructureDefinition snapshots
+///
+/// Specializations (base resources, data types and logical models) are used for
+/// type navigation. Profiles are kept separately and only consulted by the
+/// profile-aware methods such as `get_element_type_in_profile`.
+///
+/// Types are reported in the `FHIR` namespace unless their canonical URL matches
+/// a prefix registered with `with_namespace`, or they were added with
+/// `add_definition_in_namespace`. Type names must be unique across namespaces.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use octofhir_fhir_model::PackageModelProvider;
+///
+/// let provider = PackageModelProvider::from_directory("~/.fhir/packages/hl7.fhir.r4.core#4.0.1")?;
+/// let patient = provider.get_type("Patient").await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PackageModelProvider {
+    /// Explicitly configured FHIR version (otherwise detected from definitions)
+    fhir_version: Option<FhirVersion>,
+    /// Type name -> type model
+    types: HashMap<String, TypeModel>,
+    /// Canonical URL -> type name
+    url_to_type: HashMap<String, String>,
+    /// Parent element path -> child elements keyed by name
+    children: HashMap<String, IndexMap<String, ElementDefinition>>,
+    /// Profile canonical URL -> profiles, one per business version
+    profiles: HashMap<String, Vec<ProfileModel>>,
+    /// Canonical URL prefix -> namespace for definitions added without one
+    namespace_prefixes: Vec<(String, Symbol)>,
+    /// Namespaces other than `FHIR` and `System` that hold types
+    namespaces: HashSet<Symbol>,
+    /// SearchParameters in load order
+    search_parameters: Vec<SearchParameterDefinition>,
+}
+
+impl PackageModelProvider {
+    /// Create an empty provider
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a provider from an unpacked package directory
+    ///
+    /// Accepts either the package root (containing `package/`) or the
+    /// `package/` directory itself.
+    pub fn from_directory(path: impl AsRef<Path>) -> Result<Self> {
+        let mut provider = Self::new();
+        provider.load_directory(path)?;
+        Ok(provider)
+    }
+
+    /// Create a provider from StructureDefinition JSON resources
+    pub fn from_structure_definitions<'a>(
+        definitions: impl IntoIterator<Item = &'a JsonValue>,
+    ) -> Result<Self> {
+        let mut provider = Self::new();
+        for definition in definitions {
+            provider.add_structure_definition(definition)?;
+        }
+        Ok(provider)
+    }
+
+    /// Set the FHIR version reported by this provider
+    pub fn with_fhir_version(mut self, version: FhirVersion) -> Self {
+        self.fhir_version = Some(version);
+        self
+    }
+
+    /// Report types whose canonical URL starts with a prefix under a namespace
+    ///
+    /// Applies to definitions added afterwards, e.g.
+    /// `with_namespace("Acme", "http://acme.org/fhir/StructureDefinition/")`.
+    pub fn with_namespace(
+        mut self,
+        namespace: impl Into<Symbol>,
+        canonical_prefix: impl Into<String>,
+    ) -> Self {
+        self.namespace_prefixes
+            .push((canonical_prefix.into(), namespace.into()));
+        self
+    }
+
+    /// Add the type described by a compiled Rust type
+    pub fn with_type<T: FhirType>(mut self) -> Self {
+        self.register_type::<T>();
+        self
+    }
+
+    /// Create a provider from a loaded package
+    pub fn from_package(package: &FhirPackage) -> Result<Self> {
+        let mut provider = Self::new();
+        provider.add_package(package)?;
+        Ok(provider)
+    }
+
+    /// Create a provider from a package set (e.g. a package and its dependencies)
+    ///
+    /// The FHIR version declared by the package set takes precedence over the
+    /// versions found in individual StructureDefinitions.
+    pub fn from_package_set(packages: &PackageSet) -> Result<Self> {
+        let mut provider = Self::new();
+        provider.fhir_version = packages.fhir_version();
+        for package in packages.packages() {
+            provider.add_package(package)?;
+        }
+        Ok(provider)
+    }
+
+    /// Load all StructureDefinitions from an unpacked package directory
+    ///
+    /// Returns the number of definitions loaded.
+    pub fn load_directory(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        self.add_package(&FhirPackage::from_directory(path)?)
+    }
+
+    /// Add all StructureDefinitions and SearchParameters of a package
+    ///
+    /// Returns the number of StructureDefinitions loaded.
+    pub fn add_package(&mut self, package: &FhirPackage) -> Result<usize> {
+        let mut loaded = 0;
+        for definition in package.structure_definitions() {
+            self.add_structure_definition(definition)?;
+            loaded += 1;
+        }
+        for parameter in package.resources_of_type("SearchParameter") {
+            self.add_search_parameter(parameter)?;
+        }
+        Ok(loaded)
+    }
+
+    /// Add a SearchParameter resource, replacing one with the same URL
+    pub fn add_search_parameter(&mut self, parameter: &JsonValue) -> Result<()> {
+        let parameter = SearchParameterDefinition::from_json(parameter)?;
+        match self
+            .search_parameters
+            .iter_mut()
+            .find(|existing| existing.url == parameter.url)
+        {
+            Some(existing) => *existing = parameter,
+            None => self.search_parameters.push(parameter),
+        }
+        Ok(())
+    }
+
+    /// Check every search parameter's expression against the loaded types
+    pub async fn validate_search_parameters(&self) -> Result<Vec<SearchParameterIssue>> {
+        let mut issues = Vec::new();
+        for parameter in &self.search_parameters {
+            issues.extend(check_search_parameter(self, parameter).await?);
+        }
+        Ok(issues)
+    }
+
+    /// Add a single StructureDefinition resource
+    pub fn add_structure_definition(&mut self, definition: &JsonValue) -> Result<()> {
+        let definition = StructureDefinition::from_json(definition)?;
+        self.add_definition(definition);
+        Ok(())
+    }
+
+    /// Add the type described by a compiled Rust type
+    pub fn register_type<T: FhirType>(&mut self) {
+        self.add_definition(T::type_reflection().to_structure_definition());
+    }
+
+    /// Add an already parsed StructureDefinition
+    pub fn add_definition(&mut self, definition: StructureDefinition) {
+        let namespace = self
+            .namespace_prefixes
+            .iter()
+            .find(|(prefix, _)| definition.url.starts_with(prefix.as_str()))
+            .map_or_else(
+                || Symbol::intern("FHIR"),
+                |(_, namespace)| namespace.clone(),
+            );
+        self.add_definition_in_namespace(definition, namespace);
+    }
+
+    /// Add an already parsed StructureDefinition, reporting its type under a namespace
+    ///
+    /// Profiles are not types of their own, so the namespace only applies to
+    /// specializations such as logical models and custom resources.
+    pub fn add_definition_in_namespace(
+        &mut self,
+        definition: StructureDefinition,
+        namespace: impl Into<Symbol>,
+    ) {
+        if definition.derivation == Some(TypeDerivation::Constraint) {
+            self.add_profile(definition);
+            return;
+        }
+        // Namespaces of loaded models join the symbol table like their type names
+        let namespace = Symbol::intern(&namespace.into());
+        if namespace != "FHIR" && namespace != "System" {
+            self.namespaces.insert(namespace.clone());
+        }
+
+        if self.fhir_version.is_none()
+            && let Some(version) = &definition.fhir_version
+        {
+            self.fhir_version = Some(FhirVersion::from_version_str(version));
+        }
+
+        let root = definition
+            .snapshot
+            .first()
+            .map(|e| e.path.clone())
+            .unwrap_or_else(|| definition.type_name.clone());
+        // Logical models are typed by a URL; their elements are rooted at the model name
+        let type_name = if definition.type_name.contains("://") {
+            self.url_to_type
+                .insert(definition.type_name.clone(), root.clone());
+            root.clone()
+        } else {
+            definition.type_name.clone()
+        };
+
+        let mut system_type = None;
+        for element in definition.snapshot.into_iter().skip(1) {
+            if definition.kind == StructureDefinitionKind::PrimitiveType
+                && element.path == format!("{root}.value")
+            {
+                system_type = element
+                    .types
+                    .first()
+                    .and_then(|t| t.system_type())
+                    .map(Symbol::intern);
+            }
+            let Some(parent) = element.parent_path() else {
+                continue;
+            };
+            // Slices share the base element's name; they are not navigable properties
+            if element.slice_name.is_some() {
+                continue;
+            }
+            let parent = parent.to_string();
+            self.children
+                .entry(parent)
+                .or_default()
+                .insert(element.name().to_string(), element);
+        }
+
+        self.url_to_type
+            .insert(definition.url.clone(), type_name.clone());
+        self.types.insert(
+            type_name,
+            TypeModel {
+                kind: definition.kind,
+                is_abstract: definition.is_abstract,
+                base_url: definition.base_definition,
+                system_type,
+                namespace,
+            },
+        );
+    }
+
+    fn add_profile(&mut self, definition: StructureDefinition) {
+        let elements = if definition.snapshot.is_empty() {
+            definition.differential
+        } else {
+            definition.snapshot
+        };
+        let elements = elements
+            .into_iter()
+            .map(|element| {
+                let id = element.id.clone().unwrap_or_else(|| element.path.clone());
+                (id, element)
+            })
+            .collect();
+
+        let profile = ProfileModel {
+            url: definition.url,
+            version: definition.version,
+            type_name: definition.type_name,
+            base_url: definition.base_definition,
+            elements,
+            contexts: definition.contexts,
+        };
+        let versions = self.profiles.entry(profile.url.clone()).or_default();
+        match versions.iter_mut().find(|p| p.version == profile.version) {
+            Some(existing) => *existing = profile,
+            None => versions.push(profile),
+        }
+    }
+
+    /// Number of types known to this provider
+    pub fn type_count(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Number of profiles known to this provider
+    pub fn profile_count(&self) -> usize {
+        self.profiles.values().map(Vec::len).sum()
+    }
+
+    /// Number of search parameters known to this provider
+    pub fn search_parameter_count(&self) -> usize {
+        self.search_parameters.len()
+    }
+
+    /// Look up a profile by canonical URL
+    ///
+    /// A `|version` suffix selects the latest matching version (possibly
+    /// partial); without one, or if no version matches, the latest version wins.
+    fn profile(&self, profile_url: &str) -> Option<&ProfileModel> {
+        let canonical = Canonical::parse(profile_url);
+        let versions = self.profiles.get(&canonical.url)?;
+        latest_profile(
+            versions
+                .iter()
+                .filter(|profile| canonical.matches_version(profile.version.as_deref())),
+        )
+        .or_else(|| latest_profile(versions.iter()))
+    }
+
+    /// A profile followed by its base profiles, stopping at a cyclic `baseDefinition`
+    fn profile_chain<'a>(&'a self, profile: &'a ProfileModel) -> Vec<&'a ProfileModel> {
+        let mut visited = HashSet::from([profile.url.as_str()]);
+        let mut chain = vec![profile];
+        let mut current = profile;
+        while let Some(base) = current
+            .base_url
+            .as_deref()
+            .and_then(|url| self.profile(url))
+        {
+            if !visited.insert(base.url.as_str()) {
+                break;
+            }
+            chain.push(base);
+            current = base;
+        }
+        chain
+    }
+
+    /// Resolve an element of a profile by id, merging differentials with their base
+    fn profile_element(&self, profile: &ProfileModel, id: &str) -> Option<ElementDefinition> {
+        let mut inherited = self
+            .base_element_at(&path_of_id(id))
+            .map(|(element, _)| element.clone());
+
+        for profile in self.profile_chain(profile).into_iter().rev() {
+            inherited = match (profile.elements.get(id), inherited) {
+                (Some(own), Some(base)) => {
+                    let mut element = own.clone();
+                    element.inherit_from(&base);
+                    Some(element)
+                }
+                (Some(own), None) => Some(own.clone()),
+                (None, inherited) => inherited,
+            };
+        }
+        inherited
+    }
+
+    /// Whether a profile or one of its base profiles declares an element id
+    fn profile_declares(&self, profile: &ProfileModel, id: &str) -> bool {
+        self.profile_chain(profile)
+            .iter()
+            .any(|profile| profile.elements.contains_key(id))
+    }
+
+    /// Slices declared on an element id by a profile and its base profiles
+    fn profile_slices(&self, profile: &ProfileModel, id: &str, slices: &mut Vec<SliceDefinition>) {
+        for profile in self.profile_chain(profile).into_iter().rev() {
+            self.own_profile_slices(profile, id, slices);
+        }
+    }
+
+    /// Slices declared on an element id by a single profile, replacing inherited ones
+    fn own_profile_slices(
+        &self,
+        profile: &ProfileModel,
+        id: &str,
+        slices: &mut Vec<SliceDefinition>,
+    ) {
+        let prefix = format!("{id}:");
+        for (slice_id, element) in &profile.elements {
+            let Some(name) = element.slice_name.as_deref() else {
+                continue;
+            };
+            if slice_id.strip_prefix(&prefix) != Some(name) {
+                continue;
+            }
+            let Some(element) = self.profile_element(profile, slice_id) else {
+                continue;
+            };
+            let slice = SliceDefinition {
+                name: name.to_string(),
+                id: slice_id.clone(),
+                cardinality: element.cardinality(),
+                types: element.type_codes().map(String::from).collect(),
+                type_profiles: element
+                    .types
+                    .iter()
+                    .flat_map(|t| t.profiles.iter().cloned())
+                    .collect(),
+            };
+            match slices.iter_mut().find(|s| s.name == slice.name) {
+                Some(existing) => *existing = slice,
+                None => slices.push(slice),
+            }
+        }
+    }
+
+    /// Extension definition rooted at an element id of an extension profile:
+    /// `Extension`, or a sub-extension slice such as `Extension.extension:code`
+    ///
+    /// `visited` holds the versionless URLs of the extension definitions being
+    /// expanded; a reference back to one of them is reported without its nested
+    /// definition instead of recursing forever.
+    fn extension_definition(
+        &self,
+        profile: &ProfileModel,
+        id: &str,
+        url: &str,
+        contexts: Vec<ExtensionContext>,
+        visited: &mut Vec<String>,
+    ) -> ExtensionDefinition {
+        let cardinality = self
+            .profile_element(profile, id)
+            .map_or(Cardinality { min: 0, max: None }, |element| {
+                element.cardinality()
+            });
+        let value_types = self
+            .profile_element(profile, &format!("{id}.value[x]"))
+            .filter(|element| element.max.as_deref() != Some("0"))
+            .map(|element| {
+                element
+                    .type_codes()
+                    .map(|code| self.type_info_for_code(code, true))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut slices = Vec::new();
+        self.profile_slices(profile, &format!("{id}.extension"), &mut slices);
+        let extensions = slices
+            .into_iter()
+            .filter(|slice| slice.cardinality.max != Some(0))
+            .map(|slice| {
+                // Sub-extensions may reuse a separately defined extension
+                if let Some(profile_url) = slice.type_profiles.first()
+                    && let Some(referenced) = self.profile(profile_url)
+                {
+                    if visited.contains(&referenced.url) {
+                        return ExtensionDefinition {
+                            url: profile_url.clone(),
+                            contexts: referenced.contexts.clone(),
+                            cardinality: slice.cardinality,
+                            value_types: Vec::new(),
+                            extensions: Vec::new(),
+                        };
+                    }
+                    visited.push(referenced.url.clone());
+                    let definition = self.extension_definition(
+                        referenced,
+                        "Extension",
+                        profile_url,
+                        referenced.contexts.clone(),
+                        visited,
+                    );
+                    visited.pop();
+                    return ExtensionDefinition {
+                        cardinality: slice.cardinality,
+                        ..definition
+                    };
+                }
+                let sub_url = self
+                    .profile_element(profile, &format!("{}.url", slice.id))
+                    .and_then(|element| element.fixed?.as_str().map(String::from))
+                    .unwrap_or_else(|| slice.name.clone());
+                let context = ExtensionContext {
+                    context_type: "extension".to_string(),
+                    expression: url.to_string(),
+                };
+                self.extension_definition(profile, &slice.id, &sub_url, vec![context], visited)
+            })
+            .collect();
+
+        ExtensionDefinition {
+            url: url.to_string(),
+            contexts,
+            cardinality,
+            value_types,
+            extensions,
+        }
+    }
+
+    /// Base element definition at a path, following element types (`Patient.name.family`)
+    fn base_element_at(&self, path: &str) -> Option<(&ElementDefinition, Option<&str>)> {
+        let (parent, name) = path.rsplit_once('.')?;
+        self.find_element(&self.navigable_path(parent)?, name.trim_end_matches("[x]"))
+    }
+
+    /// Key into `children` for the element at a path: its own path for backbone
+    /// elements, otherwise the name of its type
+    fn navigable_path(&self, path: &str) -> Option<String> {
+        let mut segments = path.split('.');
+        let mut parent = segments.next()?.to_string();
+
+        for segment in segments {
+            let (element, choice_code) =
+                self.find_element(&parent, segment.trim_end_matches("[x]"))?;
+            parent = if let Some(backbone) = self.backbone_path(element) {
+                backbone.to_string()
+            } else {
+                self.resolve_code(choice_code.or_else(|| element.type_codes().next())?)
+                    .to_string()
+            };
+        }
+
+        Some(parent)
+    }
+
+    /// Synthetic type name of a backbone element: its own path, or the path its
+    /// `contentReference` points to (`Questionnaire.item.item` -> `Questionnaire.item`)
+    fn backbone_path<'a>(&'a self, element: &'a ElementDefinition) -> Option<&'a str> {
+        if let Some(target) = element.content_reference_path() {
+            return self.children.contains_key(target).then_some(target);
+        }
+        (!element.is_choice() && self.children.contains_key(&element.path))
+            .then_some(element.path.as_str())
+    }
+
+    /// Indexed types by name
+    #[cfg(feature = "codegen")]
+    pub(crate) fn types(&self) -> &HashMap<String, TypeModel> {
+        &self.types
+    }
+
+    /// Child elements by parent path
+    #[cfg(feature = "codegen")]
+    pub(crate) fn children(&self) -> &HashMap<String, IndexMap<String, ElementDefinition>> {
+        &self.children
+    }
+
+    /// Namespaces other than `FHIR` and `System` that hold types, sorted
+    #[cfg(feature = "codegen")]
+    pub(crate) fn custom_namespaces(&self) -> Vec<String> {
+        let mut namespaces: Vec<String> = self.namespaces.iter().map(|n| n.to_string()).collect();
+        namespaces.sort();
+        namespaces
+    }
+
+    /// Explicitly configured or detected FHIR version
+    #[cfg(feature = "codegen")]
+    pub(crate) fn fhir_version(&self) -> Option<&FhirVersion> {
+        self.fhir_version.as_ref()
+    }
+
+    /// Base type name of a type
+    pub(crate) fn base_type(&self, type_name: &str) -> Option<&str> {
+        let base_url = self.types.get(type_name)?.base_url.as_deref()?;
+        self.url_to_type
+            .get(base_url)
+            .map(String::as_str)
+            .or_else(|| base_url.strip_prefix(CORE_STRUCTURE_DEFINITION_PREFIX))
+    }
+
+    /// Type name for a type code, resolving the URLs logical models use as codes
+    pub(crate) fn resolve_code<'a>(&'a self, code: &'a str) -> &'a str {
+        if code.contains("://")
+            && !code.starts_with(crate::structure_definition::SYSTEM_TYPE_PREFIX)
+            && let Some(type_name) = self.url_to_type.get(code)
+        {
+            return type_name;
+        }
+        code
+    }
+
+    /// Namespace of the type an element path is rooted at
+    fn namespace_of(&self, path: &str) -> Symbol {
+        let root = path.split('.').next().unwrap_or(path);
+        self.types
+            .get(root)
+            .map_or_else(|| Symbol::intern("FHIR"), |t| t.namespace.clone())
+    }
+
+    /// Whether a namespace was registered for types other than FHIR and System ones
+    fn is_custom_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.iter().any(|known| *known == namespace)
+    }
+
+    /// Split an optionally namespace-qualified type name (`FHIR.Patient`, `Acme.Model`)
+    fn split_qualified<'a>(&self, type_name: &'a str) -> (Option<&'a str>, &'a str) {
+        match type_name.split_once('.') {
+            Some((namespace @ ("FHIR" | "System"), name)) => (Some(namespace), name),
+            Some((namespace, name)) if self.is_custom_namespace(namespace) => {
+                (Some(namespace), name)
+            }
+            _ => (None, type_name),
+        }
+    }
+
+    /// Type a type or backbone element directly derives from
+    fn parent_type(&self, type_name: &str) -> Option<&str> {
+        // Backbone elements derive from their declared type (`BackboneElement`)
+        self.base_type(type_name).or_else(|| {
+            self.base_element_at(type_name)
+                .and_then(|(element, _)| element.type_codes().next())
+                .map(|code| self.resolve_code(code))
+        })
+    }
+
+    /// Build the TypeInfo for a type code used in an element definition
+    fn type_info_for_code(&self, code: &str, singleton: bool) -> TypeInfo {
+        if let Some(system) = code.strip_prefix(crate::structure_definition::SYSTEM_TYPE_PREFIX) {
+            return TypeInfo::system_type(system, singleton);
+        }
+        let code = self.resolve_code(code);
+        let model = self.types.get(code);
+
+        let type_name = model
+            .and_then(|t| t.system_type.clone())
+            .or_else(|| type_constants::to_system_type(code).map(Symbol::intern))
+            .unwrap_or_else(|| Symbol::intern("Any"));
+
+        TypeInfo {
+            type_name,
+            singleton: Some(singleton),
+            is_empty: Some(false),
+            namespace: Some(model.map_or_else(|| Symbol::intern("FHIR"), |t| t.namespace.clone())),
+            name: Some(Symbol::intern(code)),
+        }
+    }
+
+    /// Resolve a property on a parent path, including typed choice names (`valueQuantity`)
+    fn find_element(
+        &self,
+        parent: &str,
+        property: &str,
+    ) -> Option<(&ElementDefinition, Option<&str>)> {
+        let children = self.children.get(parent)?;
+        if let Some(element) = children.get(property) {
+            return Some((element, None));
+        }
+
+        children
+            .values()
+            .filter(|e| e.is_choice())
+            .find_map(|element| {
+                let suffix = property.strip_prefix(element.name())?;
+                element
+                    .type_codes()
+                    .find(|code| choice_suffix(code) == suffix)
+                    .map(|code| (element, Some(code)))
+            })
+    }
+
+    fn choice_type_info(&self, element: &ElementDefinition) -> TypeInfo {
+        TypeInfo {
+            type_name: Symbol::intern("Choice"),
+            singleton: Some(!element.is_collection()),
+            is_empty: Some(false),
+            namespace: Some(self.namespace_of(&element.path)),
+            name: Some(Symbol::intern(&element.path)),
+        }
+    }
+
+    fn backbone_type_info(&self, path: &str, singleton: bool) -> TypeInfo {
+        TypeInfo {
+            namespace: Some(self.namespace_of(path)),
+            ..TypeInfo::backbone_element(path, singleton)
+        }
+    }
+
+    pub(crate) fn names_of_kind(
+        &self,
+        kind: StructureDefinitionKind,
+        concrete_only: bool,
+    ) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .types
+            .iter()
+            .filter(|(_, t)| t.kind == kind && !(concrete_only && t.is_abstract))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+#[async_trait]
+impl ModelProvider for PackageModelProvider {
+    async fn get_type(&self, type_name: &str) -> Result<Option<TypeInfo>> {
+        Ok(self.get_type_sync(type_name))
+    }
+
+    async fn get_element_type(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Result<Option<TypeInfo>> {
+        Ok(self.get_element_type_sync(parent_type, property_name))
+    }
+
+    fn of_type(&self, type_info: &TypeInfo, target_type: &str) -> Option<TypeInfo> {
+        let (namespace, target) = self.split_qualified(target_type);
+        // Types in registered namespaces only match when qualified with their own namespace
+        if let Some(namespace) = namespace
+            && self.is_custom_namespace(namespace)
+            && type_info
+                .namespace
+                .as_ref()
+                .is_none_or(|ns| ns != namespace)
+        {
+            return None;
+        }
+
+        if type_info.type_name == "Choice" {
+            let path = type_info.name.as_deref()?;
+            let (parent, _) = path.rsplit_once('.')?;
+            let (element, _) =
+                self.find_element(parent, path.rsplit('.').next()?.trim_end_matches("[x]"))?;
+            let singleton = type_info.singleton.unwrap_or(true);
+            return element
+                .type_codes()
+                .map(|code| self.resolve_code(code))
+                .find(|code| *code == target || self.is_type_derived_from(code, target))
+                .map(|code| self.type_info_for_code(code, singleton));
+        }
+
+        if type_info.type_name == target {
+            return Some(type_info.clone());
+        }
+
+        if let Some(ref name) = type_info.name
+            && self.is_type_derived_from(name, target)
+        {
+            return Some(type_info.clone());
+        }
+
+        if self.is_type_derived_from(&type_info.type_name, target) {
+            return Some(type_info.clone());
+        }
+
+        None
+    }
+
+    fn get_element_names(&self, parent_type: &TypeInfo) -> Vec<String> {
+        let parent = parent_type
+            .name
+            .as_deref()
+            .unwrap_or(&parent_type.type_name);
+        self.children
+            .get(parent)
+            .map(|children| children.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    async fn get_children_type(&self, parent_type: &TypeInfo) -> Result<Option<TypeInfo>> {
+        Ok(self.get_children_type_sync(parent_type))
+    }
+
+    async fn get_elements(&self, type_name: &str) -> Result<Vec<ElementInfo>> {
+        let Some(children) = self.children.get(type_name) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(children
+            .values()
+            .map(ElementDefinition::to_element_info)
+            .collect())
+    }
+
+    async fn get_resource_types(&self) -> Result<Vec<String>> {
+        Ok(self.names_of_kind(StructureDefinitionKind::Resource, true))
+    }
+
+    async fn get_complex_types(&self) -> Result<Vec<String>> {
+        Ok(self.names_of_kind(StructureDefinitionKind::ComplexType, false))
+    }
+
+    async fn get_primitive_types(&self) -> Result<Vec<String>> {
+        Ok(self.names_of_kind(StructureDefinitionKind::PrimitiveType, false))
+    }
+
+    async fn get_logical_types(&self) -> Result<Vec<String>> {
+        Ok(self.names_of_kind(StructureDefinitionKind::Logical, false))
+    }
+
+    async fn resource_type_exists(&self, resource_type: &str) -> Result<bool> {
+        Ok(self
+            .types
+            .get(resource_type)
+            .is_some_and(|t| t.kind == StructureDefinitionKind::Resource && !t.is_abstract))
+    }
+
+    async fn get_fhir_version(&self) -> Result<FhirVersion> {
+        Ok(self.fhir_version.clone().unwrap_or(FhirVersion::R4))
+    }
+
+    fn is_type_derived_from(&self, derived_type: &str, base_type: &str) -> bool {
+        let mut current = Some(derived_type);
+        // A chain can visit every type plus a backbone path; longer means a base cycle
+        for _ in 0..=self.types.len() {
+            let Some(type_name) = current else {
+                break;
+            };
+            if type_name == base_type {
+                return true;
+            }
+            current = self.parent_type(type_name);
+        }
+        false
+    }
+
+    fn get_type_ancestors(&self, type_name: &str) -> Vec<String> {
+        let mut ancestors: Vec<String> = Vec::new();
+        let mut current = self.parent_type(type_name);
+        while let Some(parent) = current {
+            if parent == type_name || ancestors.iter().any(|a| a == parent) {
+                break;
+            }
+            ancestors.push(parent.to_string());
+            current = self.parent_type(parent);
+        }
+        ancestors
+    }
+
+    fn get_direct_subtypes(&self, type_name: &str) -> Vec<String> {
+        let mut subtypes: Vec<String> = self
+            .types
+            .keys()
+            .filter(|name| self.base_type(name) == Some(type_name))
+            .cloned()
+            .collect();
+        subtypes.sort();
+        subtypes
+    }
+
+    fn get_concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        let mut subtypes: Vec<String> = self
+            .types
+            .iter()
+            .filter(|(name, model)| {
+                !model.is_abstract
+                    && name.as_str() != type_name
+                    && self.is_type_derived_from(name, type_name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        subtypes.sort();
+        subtypes
+    }
+
+    async fn get_choice_types(
+        &self,
+        parent_type: &str,
+        property_name: &str,
+    ) -> Result<Option<Vec<ChoiceTypeInfo>>> {
+        Ok(self
+            .children
+            .get(parent_type)
+            .and_then(|children| children.get(property_name))
+            .filter(|element| element.is_choice())
+            .map(|element| {
+                element
+                    .type_codes()
+                    .map(|code| ChoiceTypeInfo {
+                        suffix: choice_suffix(code),
+                        type_name: code.to_string(),
+                    })
+                    .collect()
+            }))
+    }
+
+    async fn get_union_types(&self, type_info: &TypeInfo) -> Result<Option<Vec<TypeInfo>>> {
+        if type_info.type_name != "Choice" {
+            return Ok(None);
+        }
+        let Some((parent, name)) = type_info.name.as_deref().and_then(|p| p.rsplit_once('.'))
+        else {
+            return Ok(None);
+        };
+        let singleton = type_info.singleton.unwrap_or(true);
+
+        Ok(self
+            .find_element(parent, name.trim_end_matches("[x]"))
+            .map(|(element, _)| {
+                element
+                    .type_codes()
+                    .map(|code| self.type_info_for_code(code, singleton))
+                    .collect()
+            }))
+    }
+
+    fn is_union_type(&self, type_info: &TypeInfo) -> bool {
+        type_info.type_name == "Choice"
+    }
+
+    async fn get_profile_type(&self, profile_url: &str) -> Result<Option<TypeInfo>> {
+        Ok(self
+            .profile(profile_url)
+            .map(|profile| self.type_info_for_code(&profile.type_name, true)))
+    }
+
+    async fn get_element_type_in_profile(
+        &self,
+        profile_url: &str,
+        parent_path: &str,
+        property_name: &str,
+    ) -> Result<Option<ProfileElementInfo>> {
+        let Some(profile) = self.profile(profile_url) else {
+            return Ok(None);
+        };
+
+        let mut id = format!("{parent_path}.{property_name}");
+        let mut choice_code = None;
+        if !self.profile_declares(profile, &id) {
+            // Choice names (`value`, `valueQuantity`) live on `value[x]`, possibly as a type slice
+            let path = format!("{}.{property_name}", path_of_id(parent_path));
+            if let Some((element, code)) = self.base_element_at(&path)
+                && element.is_choice()
+            {
+                let choice_id = format!("{parent_path}.{}[x]", element.name());
+                let type_slice = format!("{choice_id}:{property_name}");
+                id = if code.is_some() && self.profile_declares(profile, &type_slice) {
+                    type_slice
+                } else {
+                    choice_id
+                };
+                choice_code = code.map(String::from);
+            }
+        }
+
+        let Some(element) = self.profile_element(profile, &id) else {
+            return Ok(None);
+        };
+
+        let cardinality = element.cardinality();
+        let types: Vec<String> = element
+            .type_codes()
+            .filter(|code| choice_code.as_deref().is_none_or(|choice| choice == *code))
+            .map(String::from)
+            .collect();
+        let singleton = !cardinality.is_collection();
+        let type_info = match types.as_slice() {
+            [code] => self.type_info_for_code(code, singleton),
+            [] => return Ok(None),
+            _ => TypeInfo {
+                singleton: Some(singleton),
+                ..self.choice_type_info(&element)
+            },
+        };
+
+        let mut slices = Vec::new();
+        self.profile_slices(profile, &id, &mut slices);
+
+        Ok(Some(ProfileElementInfo {
+            type_profiles: element
+                .types
+                .iter()
+                .filter(|t| types.contains(&t.code))
+                .flat_map(|t| t.profiles.iter().cloned())
+                .collect(),
+            discriminators: element
+                .slicing
+                .as_ref()
+                .map(|slicing| slicing.discriminators.clone())
+                .unwrap_or_default(),
+            fixed_value: element.fixed,
+            pattern_value: element.pattern,
+            path: element.path,
+            id,
+            type_info,
+            types,
+            cardinality,
+            slices,
+        }))
+    }
+
+    async fn get_slices(
+        &self,
+        profile_url: &str,
+        element_path: &str,
+    ) -> Result<Vec<SliceDefinition>> {
+        let mut slices = Vec::new();
+        if let Some(profile) = self.profile(profile_url) {
+            self.profile_slices(profile, element_path, &mut slices);
+        }
+        Ok(slices)
+    }
+
+    async fn get_extension_definition(
+        &self,
+        extension_url: &str,
+    ) -> Result<Option<ExtensionDefinition>> {
+        let url = Canonical::parse(extension_url).url;
+        Ok(self
+            .profile(extension_url)
+            .filter(|profile| profile.type_name == "Extension")
+            .map(|profile| {
+                self.extension_definition(
+                    profile,
+                    "Extension",
+                    &url,
+                    profile.contexts.clone(),
+                    &mut vec![profile.url.clone()],
+                )
+            }))
+    }
+
+    fn as_sync(&self) -> Option<&dyn SyncModelProvider> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl SearchParameterProvider for PackageModelProvider {
+    async fn get_search_parameters(
+        &self,
+        resource_type: &str,
+    ) -> Result<Vec<SearchParameterDefinition>> {
+        Ok(self
+            .search_parameters
+            .iter()
+            .filter(|parameter| {
+                parameter
+                    .base
+                    .iter()
+                    .any(|base| self.is_type_derived_from(resource_type, base))
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+impl SyncModelProvider for PackageModelProvider {
+    fn get_type_sync(&self, type_name: &str) -> Option<TypeInfo> {
+        let (namespace, name) = self.split_qualified(type_name);
+        if let Some(model) = self.types.get(name)
+            && namespace.is_none_or(|namespace| model.namespace == namespace)
+        {
+            return Some(self.type_info_for_code(name, true));
+        }
+        if name.contains('.')
+            && self.children.contains_key(name)
+            && namespace.is_none_or(|namespace| self.namespace_of(name) == namespace)
+        {
+            return Some(self.backbone_type_info(name, true));
+        }
+        if namespace != Some("FHIR") && SYSTEM_TYPES.contains(&name) {
+            return Some(TypeInfo::system_type(name.to_string(), true));
+        }
+        None
+    }
+
+    fn get_element_type_sync(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Option<TypeInfo> {
+        let parent = parent_type
+            .name
+            .as_deref()
+            .unwrap_or(&parent_type.type_name);
+
+        let (element, choice_code) = self.find_element(parent, property_name)?;
+        let singleton = !element.is_collection();
+
+        if let Some(code) = choice_code {
+            return Some(self.type_info_for_code(code, singleton));
+        }
+        if element.is_choice() {
+            return Some(self.choice_type_info(element));
+        }
+        if let Some(backbone) = self.backbone_path(element) {
+            return Some(self.backbone_type_info(backbone, singleton));
+        }
+
+        element
+            .types
+            .first()
+            .map(|t| self.type_info_for_code(&t.code, singleton))
+    }
+}
+
+/// Profile with the highest business version
+fn latest_profile<'a>(
+    profiles: impl Iterator<Item = &'a ProfileModel>,
+) -> Option<&'a ProfileModel> {
+    profiles.max_by(|a, b| {
+        compare_versions(
+            a.version.as_deref().unwrap_or(""),
+            b.version.as_deref().unwrap_or(""),
+        )
+    })
+}
+
+/// Element path of an element id (`Patient.identifier:mrn.system` -> `Patient.identifier.system`)
+fn path_of_id(id: &str) -> String {
+    id.split('.')
+        .map(|segment| segment.split_once(':').map_or(segment, |(name, _)| name))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{el, fixture_definitions, sd};
+    use serde_json::json;
+
+    fn fixture_provider() -> PackageModelProvider {
+        PackageModelProvider::from_structure_definitions(&fixture_definitions()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_type_navigation() {
+        let provider = fixture_provider();
+
+        let patient = provider.get_type("Patient").await.unwrap().unwrap();
+        assert_eq!(patient.name.as_deref(), Some("Patient"));
+
+        let name = provider
+            .get_element_type(&patient, "name")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(name.name.as_deref(), Some("HumanName"));
+        assert_eq!(name.singleton, Some(false));
+
+        let family = provider
+            .get_element_type(&name, "family")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(family.type_name, "String");
+        assert_eq!(family.name.as_deref(), Some("string"));
+        assert_eq!(family.namespace.as_deref(), Some("FHIR"));
+        assert_eq!(family.singleton, Some(true));
+
+        assert!(
+            provider
+                .get_element_type(&patient, "unknown")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_choice_elements() {
+        let provider = fixture_provider();
+        let patient = provider.get_type("Patient").await.unwrap().unwrap();
+
+        let deceased = provider
+            .get_element_type(&patient, "deceased")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(provider.is_union_type(&deceased));
+        let union = provider.get_union_types(&deceased).await.unwrap().unwrap();
+        assert_eq!(union.len(), 2);
+
+        let as_date_time = provider.of_type(&deceased, "dateTime").unwrap();
+        assert_eq!(as_date_time.name.as_deref(), Some("dateTime"));
+
+        let typed = provider
+            .get_element_type(&patient, "deceasedBoolean")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(typed.name.as_deref(), Some("boolean"));
+
+        let choices = provider
+            .get_choice_types("Patient", "deceased")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(choices[1].suffix, "DateTime");
+        assert_eq!(choices[1].type_name, "dateTime");
+    }
+
+    #[tokio::test]
+    async fn test_hierarchy_and_listings() {
+        let provider = fixture_provider();
+
+        assert!(provider.is_type_derived_from("Patient", "Resource"));
+        assert!(provider.is_type_derived_from("string", "Element"));
+        assert!(!provider.is_type_derived_from("Resource", "Patient"));
+
+        assert_eq!(
+            provider.get_resource_types().await.unwrap(),
+            vec!["DomainResource", "Patient", "Resource"]
+        );
+        assert_eq!(
+            provider.get_primitive_types().await.unwrap(),
+            vec!["string"]
+        );
+        assert_eq!(provider.get_fhir_version().await.unwrap(), FhirVersion::R4);
+
+        let elements = provider.get_elements("Patient").await.unwrap();
+        assert_eq!(elements[0].name, "name");
+        assert_eq!(elements[0].path, "Patient.name");
+        assert_eq!(elements[0].element_type, "HumanName");
+        assert!(elements[0].is_collection());
+    }
+
+    #[test]
+    fn test_cyclic_base_definitions() {
+        let mut definitions = fixture_definitions();
+        definitions.extend([
+            sd(
+                "Ping",
+                "resource",
+                Some("Pong"),
+                json!([el("Ping", "*", &[])]),
+            ),
+            sd(
+                "Pong",
+                "resource",
+                Some("Ping"),
+                json!([el("Pong", "*", &[])]),
+            ),
+        ]);
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+
+        assert!(provider.is_type_derived_from("Ping", "Pong"));
+        assert!(!provider.is_type_derived_from("Ping", "Resource"));
+        assert_eq!(provider.get_type_ancestors("Ping"), vec!["Pong"]);
+        assert_eq!(provider.get_concrete_subtypes("Ping"), vec!["Pong"]);
+    }
+
+    #[tokio::test]
+    async fn test_profile_navigation() {
+        let mut definitions = fixture_definitions();
+        definitions.push(json!({
+            "resourceType": "StructureDefinition",
+            "url": "http://example.org/StructureDefinition/named-patient",
+            "version": "1.0.0",
+            "name": "NamedPatient",
+            "kind": "resource",
+            "abstract": false,
+            "type": "Patient",
+            "derivation": "constraint",
+            "baseDefinition": format!("{CORE_STRUCTURE_DEFINITION_PREFIX}Patient"),
+            "differential": {"element": [
+                {"id": "Patient.name", "path": "Patient.name", "min": 1,
+                 "slicing": {"discriminator": [{"type": "value", "path": "family"}],
+                             "rules": "open"}},
+                {"id": "Patient.name:official", "path": "Patient.name",
+                 "sliceName": "official", "min": 1, "max": "1"},
+                {"id": "Patient.name:official.family", "path": "Patient.name.family",
+                 "fixedString": "Smith"},
+                {"id": "Patient.deceased[x]", "path": "Patient.deceased[x]",
+                 "type": [{"code": "boolean"}]}
+            ]}
+        }));
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+        let profile = "http://example.org/StructureDefinition/named-patient|1.0.0";
+
+        assert_eq!(provider.type_count(), 6);
+        assert_eq!(provider.profile_count(), 1);
+        let patient = provider.get_profile_type(profile).await.unwrap().unwrap();
+        assert_eq!(patient.name.as_deref(), Some("Patient"));
+
+        let name = provider
+            .get_element_type_in_profile(profile, "Patient", "name")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(name.cardinality.to_string(), "1..*");
+        assert_eq!(name.type_info.name.as_deref(), Some("HumanName"));
+        assert_eq!(name.discriminators[0].path, "family");
+        assert_eq!(name.slices.len(), 1);
+        assert_eq!(name.slices[0].name, "official");
+        assert_eq!(name.slices[0].cardinality.to_string(), "1..1");
+        assert_eq!(name.slices[0].types, vec!["HumanName"]);
+
+        let family = provider
+            .get_element_type_in_profile(profile, "Patient.name:official", "family")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(family.path, "Patient.name.family");
+        assert_eq!(family.fixed_value, Some(json!("Smith")));
+        assert_eq!(family.cardinality.to_string(), "0..1");
+        assert_eq!(family.type_info.type_name, "String");
+
+        let deceased = provider
+            .get_element_type_in_profile(profile, "Patient", "deceased")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(deceased.types, vec!["boolean"]);
+        assert_eq!(deceased.type_info.name.as_deref(), Some("boolean"));
+
+        let typed = provider
+            .get_element_type_in_profile(profile, "Patient", "deceasedBoolean")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(typed.id, "Patient.deceased[x]");
+        assert!(
+            provider
+                .get_element_type_in_profile(profile, "Patient", "deceasedDateTime")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let slices = provider.get_slices(profile, "Patient.name").await.unwrap();
+        assert_eq!(slices, name.slices);
+    }
+
+    fn profile(url: &str, version: &str, base: &str, elements: JsonValue) -> JsonValue {
+        json!({
+            "resourceType": "StructureDefinition",
+            "url": url,
+            "version": version,
+            "name": "PatientProfile",
+            "kind": "resource",
+            "abstract": false,
+            "type": "Patient",
+            "derivation": "constraint",
+            "baseDefinition": base,
+            "differential": {"element": elements}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_profile_versions_and_cycles() {
+        let url = "http://example.org/StructureDefinition/versioned";
+        let base = format!("{CORE_STRUCTURE_DEFINITION_PREFIX}Patient");
+        let mut definitions = fixture_definitions();
+        definitions.extend([
+            profile(
+                url,
+                "1.0.0",
+                &base,
+                json!([{"id": "Patient.name", "path": "Patient.name", "max": "1"}]),
+            ),
+            profile(
+                url,
+                "2.0.0",
+                &base,
+                json!([{"id": "Patient.name", "path": "Patient.name", "max": "2"}]),
+            ),
+            profile(
+                "http://example.org/StructureDefinition/a",
+                "1.0.0",
+                "http://example.org/StructureDefinition/b",
+                json!([{"id": "Patient.name", "path": "Patient.name", "min": 1}]),
+            ),
+            profile(
+                "http://example.org/StructureDefinition/b",
+                "1.0.0",
+                "http://example.org/StructureDefinition/a|1.0.0",
+                json!([{"id": "Patient.name:official", "path": "Patient.name",
+                        "sliceName": "official"}]),
+            ),
+        ]);
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+        assert_eq!(provider.profile_count(), 4);
+
+        let max_of = |profile: &'static str| {
+            let provider = &provider;
+            async move {
+                provider
+                    .get_element_type_in_profile(profile, "Patient", "name")
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .cardinality
+                    .max
+            }
+        };
+        assert_eq!(
+            max_of("http://example.org/StructureDefinition/versioned|1.0.0").await,
+            Some(1)
+        );
+        assert_eq!(
+            max_of("http://example.org/StructureDefinition/versioned|2").await,
+            Some(2)
+        );
+        assert_eq!(
+            max_of("http://example.org/StructureDefinition/versioned").await,
+            Some(2)
+        );
+
+        let name = provider
+            .get_element_type_in_profile(
+                "http://example.org/StructureDefinition/a",
+                "Patient",
+                "name",
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(name.cardinality.min, 1);
+        assert_eq!(name.slices[0].name, "official");
+    }
+
+    fn logical(name: &str, elements: JsonValue) -> JsonValue {
+        json!({
+            "resourceType": "StructureDefinition",
+            "url": format!("http://acme.org/StructureDefinition/{name}"),
+            "name": name,
+            "kind": "logical",
+            "abstract": false,
+            "type": format!("http://acme.org/StructureDefinition/{name}"),
+            "derivation": "specialization",
+            "baseDefinition": format!("{CORE_STRUCTURE_DEFINITION_PREFIX}Element"),
+            "snapshot": {"element": elements}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_logical_models_in_namespace() {
+        let mut definitions = fixture_definitions();
+        definitions.extend([
+            logical(
+                "Shipment",
+                json!([
+                    el("Shipment", "*", &[]),
+                    el("Shipment.label", "1", &["string"]),
+                    el(
+                        "Shipment.address",
+                        "1",
+                        &["http://acme.org/StructureDefinition/Address"]
+                    ),
+                    el("Shipment.parcel", "*", &["Element"]),
+                    el("Shipment.parcel.weight", "1", &["string"])
+                ]),
+            ),
+            logical(
+                "Address",
+                json!([
+                    el("Address", "*", &[]),
+                    el("Address.city", "1", &["string"])
+                ]),
+            ),
+        ]);
+        let mut provider = PackageModelProvider::new().with_namespace("Acme", "http://acme.org/");
+        for definition in &definitions {
+            provider.add_structure_definition(definition).unwrap();
+        }
+
+        assert_eq!(
+            provider.get_logical_types().await.unwrap(),
+            vec!["Address", "Shipment"]
+        );
+        assert!(!provider.resource_type_exists("Shipment").await.unwrap());
+        let shipment = provider.get_type("Acme.Shipment").await.unwrap().unwrap();
+        assert_eq!(shipment.namespace.as_deref(), Some("Acme"));
+        assert_eq!(shipment.name.as_deref(), Some("Shipment"));
+        assert_eq!(
+            provider.get_type("Shipment").await.unwrap(),
+            Some(shipment.clone())
+        );
+        assert!(provider.get_type("FHIR.Shipment").await.unwrap().is_none());
+        let patient = provider.get_type("Patient").await.unwrap().unwrap();
+        assert_eq!(patient.namespace.as_deref(), Some("FHIR"));
+
+        let address = provider
+            .get_element_type(&shipment, "address")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(address.name.as_deref(), Some("Address"));
+        assert_eq!(address.namespace.as_deref(), Some("Acme"));
+        let city = provider
+            .get_element_type(&address, "city")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(city.name.as_deref(), Some("string"));
+        assert_eq!(city.namespace.as_deref(), Some("FHIR"));
+
+        let parcel = provider
+            .get_element_type(&shipment, "parcel")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(parcel.name.as_deref(), Some("Shipment.parcel"));
+        assert_eq!(parcel.namespace.as_deref(), Some("Acme"));
+        assert!(parcel.is_backbone_element());
+
+        assert!(provider.of_type(&address, "Acme.Address").is_some());
+        assert!(provider.of_type(&address, "Address").is_some());
+        assert!(provider.of_type(&address, "Element").is_some());
+        assert!(provider.of_type(&patient, "Acme.Address").is_none());
+        assert!(provider.is_type_derived_from("Shipment.address", "Address"));
+    }
+
+    #[tokio::test]
+    async fn test_extension_definitions() {
+        let mut definitions = fixture_definitions();
+        definitions.push(sd(
+            "Extension",
+            "complex-type",
+            Some("Element"),
+            json!([
+                el("Extension", "*", &[]),
+                el("Extension.extension", "*", &["Extension"]),
+                el("Extension.url", "1", &["uri"]),
+                el(
+                    "Extension.value[x]",
+                    "1",
+                    &["string", "boolean", "HumanName"]
+                )
+            ]),
+        ));
+        let extension = |name: &str, context: &str, elements: JsonValue| {
+            json!({
+                "resourceType": "StructureDefinition",
+                "url": format!("http://example.org/StructureDefinition/{name}"),
+                "name": name,
+                "kind": "complex-type",
+                "abstract": false,
+                "type": "Extension",
+                "derivation": "constraint",
+                "baseDefinition": format!("{CORE_STRUCTURE_DEFINITION_PREFIX}Extension"),
+                "context": [{"type": "element", "expression": context}],
+                "differential": {"element": elements}
+            })
+        };
+        definitions.push(extension(
+            "nickname",
+            "HumanName",
+            json!([
+                {"id": "Extension", "path": "Extension", "max": "1"},
+                {"id": "Extension.value[x]", "path": "Extension.value[x]",
+                 "type": [{"code": "string"}]}
+            ]),
+        ));
+        definitions.push(extension(
+            "deceased-details",
+            "Patient",
+            json!([
+                {"id": "Extension.extension", "path": "Extension.extension",
+                 "slicing": {"discriminator": [{"type": "value", "path": "url"}],
+                             "rules": "open"}},
+                {"id": "Extension.extension:confirmed", "path": "Extension.extension",
+                 "sliceName": "confirmed", "min": 1, "max": "1"},
+                {"id": "Extension.extension:confirmed.url", "path": "Extension.extension.url",
+                 "fixedUri": "confirmed"},
+                {"id": "Extension.extension:confirmed.value[x]",
+                 "path": "Extension.extension.value[x]", "type": [{"code": "boolean"}]},
+                {"id": "Extension.extension:alias", "path": "Extension.extension",
+                 "sliceName": "alias", "max": "*",
+                 "type": [{"code": "Extension",
+                           "profile": ["http://example.org/StructureDefinition/nickname"]}]},
+                {"id": "Extension.value[x]", "path": "Extension.value[x]", "max": "0"}
+            ]),
+        ));
+        // Complex extensions that reference each other
+        for (name, other) in [("ping", "pong|1.0"), ("pong", "ping")] {
+            definitions.push(extension(
+                name,
+                "Patient",
+                json!([
+                    {"id": "Extension.extension:next", "path": "Extension.extension",
+                     "sliceName": "next", "max": "1",
+                     "type": [{"code": "Extension",
+                               "profile": [format!("http://example.org/StructureDefinition/{other}")]}]},
+                    {"id": "Extension.value[x]", "path": "Extension.value[x]", "max": "0"}
+                ]),
+            ));
+        }
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+
+        let nickname = provider
+            .get_extension_definition("http://example.org/StructureDefinition/nickname")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!nickname.is_complex());
+        assert_eq!(nickname.cardinality.to_string(), "0..1");
+        assert_eq!(
+            nickname
+                .value_type()
+                .and_then(|t| t.name.clone())
+                .as_deref(),
+            Some("string")
+        );
+        assert_eq!(nickname.contexts[0].expression, "HumanName");
+
+        let details = provider
+            .get_extension_definition("http://example.org/StructureDefinition/deceased-details|1.0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(details.is_complex());
+        assert_eq!(details.extensions.len(), 2);
+        let confirmed = details.extension("confirmed").unwrap();
+        assert_eq!(confirmed.cardinality.to_string(), "1..1");
+        assert_eq!(confirmed.value_types[0].type_name, "Boolean");
+        assert_eq!(
+            confirmed.contexts[0].expression,
+            "http://example.org/StructureDefinition/deceased-details"
+        );
+        let alias = details
+            .extension("http://example.org/StructureDefinition/nickname")
+            .unwrap();
+        assert_eq!(alias.cardinality.to_string(), "0..*");
+        assert_eq!(alias.value_types, nickname.value_types);
+
+        let nickname_url = "http://example.org/StructureDefinition/nickname";
+        assert!(
+            provider
+                .is_extension_allowed(nickname_url, "Patient.name")
+                .await
+                .unwrap()
+        );
+        assert!(
+            provider
+                .is_extension_allowed(nickname_url, "HumanName")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !provider
+                .is_extension_allowed(nickname_url, "Patient")
+                .await
+                .unwrap()
+        );
+        assert!(
+            provider
+                .is_extension_allowed(
+                    "http://example.org/StructureDefinition/deceased-details",
+                    "Patient"
+                )
+                .await
+                .unwrap()
+        );
+        let ping = provider
+            .get_extension_definition("http://example.org/StructureDefinition/ping")
+            .await
+            .unwrap()
+            .unwrap();
+        let pong = &ping.extensions[0];
+        assert_eq!(pong.url, "http://example.org/StructureDefinition/pong|1.0");
+        assert_eq!(
+            pong.extensions[0].url,
+            "http://example.org/StructureDefinition/ping"
+        );
+        assert!(pong.extensions[0].extensions.is_empty());
+        assert!(
+            provider
+                .get_extension_definition("http://example.org/StructureDefinition/unknown")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_queries() {
+        let mut definitions = fixture_definitions();
+        for definition in &mut definitions {
+            if matches!(
+                definition["name"].as_str(),
+                Some("Resource" | "DomainResource")
+            ) {
+                definition["abstract"] = json!(true);
+            }
+        }
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+
+        assert_eq!(
+            provider.get_type_ancestors("Patient"),
+            vec!["DomainResource", "Resource"]
+        );
+        assert!(provider.get_type_ancestors("Resource").is_empty());
+        assert_eq!(
+            provider.get_direct_subtypes("Element"),
+            vec!["HumanName", "string"]
+        );
+        assert_eq!(provider.get_concrete_subtypes("Resource"), vec!["Patient"]);
+        assert!(provider.get_concrete_subtypes("Patient").is_empty());
+
+        assert_eq!(
+            provider.get_common_supertype(&["Patient", "DomainResource"]),
+            Some("DomainResource".to_string())
+        );
+        assert_eq!(
+            provider.get_common_supertype(&["HumanName", "string"]),
+            Some("Element".to_string())
+        );
+        assert_eq!(
+            provider.get_common_supertype(&["Patient", "HumanName"]),
+            None
+        );
+        assert_eq!(provider.get_common_supertype(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_backbone_elements() {
+        let mut definitions = fixture_definitions();
+        let mut nested = el("Questionnaire.item.item", "*", &[]);
+        nested["contentReference"] = json!("#Questionnaire.item");
+        definitions.extend([
+            sd(
+                "BackboneElement",
+                "complex-type",
+                Some("Element"),
+                json!([el("BackboneElement", "*", &[])]),
+            ),
+            sd(
+                "Questionnaire",
+                "resource",
+                Some("DomainResource"),
+                json!([
+                    el("Questionnaire", "*", &[]),
+                    el("Questionnaire.item", "*", &["BackboneElement"]),
+                    el("Questionnaire.item.linkId", "1", &["string"]),
+                    nested
+                ]),
+            ),
+        ]);
+        let provider = PackageModelProvider::from_structure_definitions(&definitions).unwrap();
+
+        let questionnaire = provider.get_type("Questionnaire").await.unwrap().unwrap();
+        let item = provider
+            .get_element_type(&questionnaire, "item")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(item.is_backbone_element());
+        assert_eq!(item.name.as_deref(), Some("Questionnaire.item"));
+        assert_eq!(item.singleton, Some(false));
+
+        let nested = provider
+            .get_element_type(&item, "item")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(nested.name.as_deref(), Some("Questionnaire.item"));
+        let deeper = provider
+            .get_element_type(&nested, "item")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(deeper, nested);
+
+        let link_id = provider
+            .get_element_type(&deeper, "linkId")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(link_id.name.as_deref(), Some("string"));
+
+        let by_name = provider
+            .get_type("Questionnaire.item")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_name.name, item.name);
+        assert!(provider.is_type_derived_from("Questionnaire.item", "BackboneElement"));
+        assert!(provider.is_type_derived_from("Questionnaire.item", "Element"));
+        assert!(provider.of_type(&item, "BackboneElement").is_some());
+        assert!(
+            !provider
+                .get_resource_types()
+                .await
+                .unwrap()
+                .contains(&"Questionnaire.item".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_directory() {
+        let dir = std::env::temp_dir().join(format!("fhir-model-pkg-{}", std::process::id()));
+        let package_dir = dir.join("package");
+        std::fs::create_dir_all(&package_dir).unwrap();
+        for definition in fixture_definitions() {
+            let file = package_dir.join(format!(
+                "StructureDefinition-{}.json",
+                definition["name"].as_str().unwrap()
+            ));
+            std::fs::write(file, definition.to_string()).unwrap();
+        }
+        std::fs::write(
+            package_dir.join("package.json"),
+            r#"{"name":"test","version":"1.0.0"}"#,
+        )
+        .unwrap();
+
+        let provider = PackageModelProvider::from_directory(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(provider.type_count(), 6);
+        assert!(provider.resource_type_exists("Patient").await.unwrap());
+    }
+}
@@ -10,6 +10,8 @@ use std::sync::{Arc, LazyLock};
 
 use crate::error::Result;
 use crate::evaluation::{EvaluationResult, IntoEvaluationResult, TypeInfoResult};
+use crate::interner::Symbol;
+use crate::structure_definition::{ExtensionContext, SlicingDiscriminator};
 
 /// Core trait for accessing FHIR model information
 ///
@@ -47,6 +49,11 @@ pub trait ModelProvider: Send + Sync + std::fmt::Debug {
     /// Get list of all primitive types
     async fn get_primitive_types(&self) -> Result<Vec<String>>;
 
+    /// Get list of all logical models
+    async fn get_logical_types(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     /// Check if a resource type exists
     async fn resource_type_exists(&self, resource_type: &str) -> Result<bool> {
         let resource_types = self.get_resource_types().await?;
@@ -66,6 +73,40 @@ pub trait ModelProvider: Send + Sync + std::fmt::Debug {
         derived_type == base_type
     }
 
+    /// Ancestors of a type, nearest first (e.g. `DomainResource`, `Resource` for `Patient`)
+    fn get_type_ancestors(&self, type_name: &str) -> Vec<String> {
+        let _ = type_name;
+        Vec::new()
+    }
+
+    /// Types deriving directly from a type, sorted
+    fn get_direct_subtypes(&self, type_name: &str) -> Vec<String> {
+        let _ = type_name;
+        Vec::new()
+    }
+
+    /// Non-abstract types deriving from a type directly or indirectly, sorted
+    ///
+    /// For example every concrete resource for `DomainResource`.
+    fn get_concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        let _ = type_name;
+        Vec::new()
+    }
+
+    /// Most specific type all given types derive from
+    ///
+    /// Returns `DomainResource` for `Patient` and `Observation`, and `None`
+    /// when the types share no ancestor (or no types are given).
+    fn get_common_supertype(&self, type_names: &[&str]) -> Option<String> {
+        let (first, rest) = type_names.split_first()?;
+        std::iter::once(first.to_string())
+            .chain(self.get_type_ancestors(first))
+            .find(|candidate| {
+                rest.iter()
+                    .all(|type_name| self.is_type_derived_from(type_name, candidate))
+            })
+    }
+
     /// Get choice type metadata for a property (valueX patterns)
     async fn get_choice_types(
         &self,
@@ -87,50 +128,205 @@ pub trait ModelProvider: Send + Sync + std::fmt::Debug {
         let _ = type_info;
         false
     }
+
+    /// Get the type constrained by a profile (e.g. `Patient` for US Core Patient)
+    async fn get_profile_type(&self, profile_url: &str) -> Result<Option<TypeInfo>> {
+        let _ = profile_url;
+        Ok(None)
+    }
+
+    /// Get an element's type as constrained by a profile
+    ///
+    /// `parent_path` is an element id within the profile (e.g. `Patient` or
+    /// `Patient.identifier:mrn`), so slices can be navigated into.
+    async fn get_element_type_in_profile(
+        &self,
+        profile_url: &str,
+        parent_path: &str,
+        property_name: &str,
+    ) -> Result<Option<ProfileElementInfo>> {
+        let _ = (profile_url, parent_path, property_name);
+        Ok(None)
+    }
+
+    /// Get the slices a profile defines on an element (e.g. `Patient.identifier`)
+    async fn get_slices(
+        &self,
+        profile_url: &str,
+        element_path: &str,
+    ) -> Result<Vec<SliceDefinition>> {
+        let _ = (profile_url, element_path);
+        Ok(Vec::new())
+    }
+
+    /// Resolve an extension definition by canonical URL
+    ///
+    /// Complex extensions list their sub-extensions instead of value types.
+    async fn get_extension_definition(
+        &self,
+        extension_url: &str,
+    ) -> Result<Option<ExtensionDefinition>> {
+        let _ = extension_url;
+        Ok(None)
+    }
+
+    /// Check whether an extension may appear at an element path (e.g. `Patient.name`)
+    ///
+    /// `element` contexts match the path itself or the type of the element at
+    /// the path, including base types. For `extension` contexts, pass the URL of
+    /// the enclosing extension as the path. `fhirpath` contexts cannot be checked
+    /// statically and are treated as allowed.
+    async fn is_extension_allowed(&self, extension_url: &str, context_path: &str) -> Result<bool> {
+        let Some(definition) = self.get_extension_definition(extension_url).await? else {
+            return Ok(false);
+        };
+
+        let mut segments = context_path.split('.');
+        let mut element_type = match segments.next() {
+            Some(root) => self.get_type(root).await?,
+            None => None,
+        };
+        for segment in segments {
+            element_type = match element_type {
+                Some(parent) => self.get_element_type(&parent, segment).await?,
+                None => None,
+            };
+        }
+        let type_name = element_type.and_then(|t| t.name);
+
+        Ok(definition
+            .contexts
+            .iter()
+            .any(|context| match context.context_type.as_str() {
+                "element" => {
+                    context.expression == context_path
+                        || type_name.as_ref().is_some_and(|name| {
+                            self.is_type_derived_from(name, &context.expression)
+                        })
+                }
+                "extension" => context.expression == context_path,
+                _ => true,
+            }))
+    }
+
+    /// Synchronous access to type lookups, for providers that keep their model in memory
+    ///
+    /// Returns `None` by default; callers then fall back to the async methods.
+    fn as_sync(&self) -> Option<&dyn SyncModelProvider> {
+        None
+    }
+}
+
+/// Synchronous fast path for type lookups
+///
+/// In-memory providers implement this next to `ModelProvider` and return it
+/// from `ModelProvider::as_sync`, so hot evaluation loops can navigate
+/// properties without allocating a boxed future per lookup.
+pub trait SyncModelProvider: Send + Sync {
+    /// Core type lookup
+    fn get_type_sync(&self, type_name: &str) -> Option<TypeInfo>;
+
+    /// Get element type from complex type
+    fn get_element_type_sync(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Option<TypeInfo>;
+
+    /// Returns the type of the items of a collection, or `None` for a singleton
+    fn get_children_type_sync(&self, parent_type: &TypeInfo) -> Option<TypeInfo> {
+        if parent_type.singleton.unwrap_or(true) {
+            None
+        } else {
+            Some(TypeInfo {
+                singleton: Some(true),
+                is_empty: Some(false),
+                ..parent_type.clone()
+            })
+        }
+    }
 }
 
 /// Type information structure for FHIR elements
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeInfo {
     /// FHIRPath type name ('Any', 'Boolean', 'String', 'Integer', etc.)
-    pub type_name: String,
+    pub type_name: Symbol,
     /// Single value vs collection (optional, defaults to true)
     pub singleton: Option<bool>,
     /// Indicates this is definitely an empty collection
     pub is_empty: Option<bool>,
     /// Model type namespace ('FHIR', 'System', etc.)
-    pub namespace: Option<String>,
+    pub namespace: Option<Symbol>,
     /// Model type name (Patient, string, etc.)
-    pub name: Option<String>,
+    pub name: Option<Symbol>,
 }
 
 impl TypeInfo {
     /// Create a system type
-    pub fn system_type(type_name: String, singleton: bool) -> Self {
+    pub fn system_type(type_name: impl Into<Symbol>, singleton: bool) -> Self {
+        let type_name = type_name.into();
         Self {
             type_name: type_name.clone(),
             singleton: Some(singleton),
             is_empty: Some(false),
-            namespace: Some("System".to_string()),
+            namespace: Some(Symbol::intern("System")),
             name: Some(type_name),
         }
     }
 
+    /// Create a FHIR primitive type backed by its System type (e.g. `code` -> `String`)
+    ///
+    /// Returns `None` if the name is not a FHIR primitive.
+    pub fn fhir_primitive(name: &str, singleton: bool) -> Option<Self> {
+        let primitive = type_constants::primitive_type(name)?;
+        Some(Self {
+            type_name: Symbol::intern(primitive.system_type),
+            singleton: Some(singleton),
+            is_empty: Some(false),
+            namespace: Some(Symbol::intern("FHIR")),
+            name: Some(Symbol::intern(primitive.name)),
+        })
+    }
+
+    /// Create an anonymous backbone element type, named by its element path
+    ///
+    /// Backbone elements (e.g. `Patient.contact`) have no type of their own, so
+    /// providers expose them under their defining path as a stable synthetic name.
+    pub fn backbone_element(path: &str, singleton: bool) -> Self {
+        Self {
+            type_name: Symbol::intern("Any"),
+            singleton: Some(singleton),
+            is_empty: Some(false),
+            namespace: Some(Symbol::intern("FHIR")),
+            name: Some(Symbol::intern(path)),
+        }
+    }
+
+    /// Whether this is a backbone element type with a synthetic path name
+    ///
+    /// Backbones of custom-namespace models are reported under their model's
+    /// namespace, so any namespace qualifies.
+    pub fn is_backbone_element(&self) -> bool {
+        self.type_name == "Any" && self.name.as_ref().is_some_and(|name| name.contains('.'))
+    }
+
     /// Create a FHIR complex type (Element, BackboneElement, Resource, etc.)
     pub fn new_complex(type_name: &str) -> Self {
+        let type_name = Symbol::lookup_or_owned(type_name);
         Self {
-            type_name: type_name.to_string(),
+            type_name: type_name.clone(),
             singleton: Some(true),
             is_empty: Some(false),
-            namespace: Some("FHIR".to_string()),
-            name: Some(type_name.to_string()),
+            namespace: Some(Symbol::intern("FHIR")),
+            name: Some(type_name),
         }
     }
 }
 
 /// Pre-allocated `Arc<TypeInfo>` constants for common system types.
-/// Using these avoids repeated String allocations when creating FhirPathValues.
+/// Using these avoids re-interning type names when creating FhirPathValues.
 pub mod type_constants {
     use super::*;
 
@@ -161,18 +357,311 @@ pub mod type_constants {
     /// System Quantity type info
     pub static QUANTITY_TYPE: LazyLock<Arc<TypeInfo>> =
         LazyLock::new(|| Arc::new(TypeInfo::system_type("Quantity".to_string(), true)));
+
+    /// Definition of a FHIR primitive type and its FHIRPath System type mapping
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PrimitiveTypeDef {
+        /// FHIR type name (e.g. `positiveInt`)
+        pub name: &'static str,
+        /// FHIRPath System type the primitive maps to (e.g. `Integer`)
+        pub system_type: &'static str,
+        /// FHIR primitive the type is derived from (e.g. `integer` for `positiveInt`)
+        pub base: Option<&'static str>,
+        /// Regular expression the whole value must match, as published by the specification
+        pub regex: Option<&'static str>,
+        /// Smallest allowed value for integer types
+        pub min_value: Option<i64>,
+        /// Largest allowed value for integer types
+        pub max_value: Option<i64>,
+        /// Maximum length of the value in characters
+        pub max_length: Option<usize>,
+    }
+
+    impl PrimitiveTypeDef {
+        const fn new(name: &'static str, system_type: &'static str) -> Self {
+            Self {
+                name,
+                system_type,
+                base: None,
+                regex: None,
+                min_value: None,
+                max_value: None,
+                max_length: None,
+            }
+        }
+
+        const fn derived_from(mut self, base: &'static str) -> Self {
+            self.base = Some(base);
+            self
+        }
+
+        const fn regex(mut self, regex: &'static str) -> Self {
+            self.regex = Some(regex);
+            self
+        }
+
+        const fn range(mut self, min: i64, max: i64) -> Self {
+            self.min_value = Some(min);
+            self.max_value = Some(max);
+            self
+        }
+
+        const fn max_length(mut self, max_length: usize) -> Self {
+            self.max_length = Some(max_length);
+            self
+        }
+
+        /// Whether an integer value lies within the type's value range
+        pub fn is_in_range(&self, value: i64) -> bool {
+            self.min_value.is_none_or(|min| value >= min)
+                && self.max_value.is_none_or(|max| value <= max)
+        }
+
+        /// Whether a value is within the type's maximum length
+        pub fn is_within_length(&self, value: &str) -> bool {
+            self.max_length
+                .is_none_or(|max| value.chars().count() <= max)
+        }
+    }
+
+    const DATE_REGEX: &str = r"([0-9]([0-9]([0-9][1-9]|[1-9]0)|[1-9]00)|[1-9]000)(-(0[1-9]|1[0-2])(-(0[1-9]|[1-2][0-9]|3[0-1]))?)?";
+    const DATE_TIME_REGEX: &str = r"([0-9]([0-9]([0-9][1-9]|[1-9]0)|[1-9]00)|[1-9]000)(-(0[1-9]|1[0-2])(-(0[1-9]|[1-2][0-9]|3[0-1])(T([01][0-9]|2[0-3]):[0-5][0-9]:([0-5][0-9]|60)(\.[0-9]+)?(Z|(\+|-)((0[0-9]|1[0-3]):[0-5][0-9]|14:00)))?)?)?";
+    const INSTANT_REGEX: &str = r"([0-9]([0-9]([0-9][1-9]|[1-9]0)|[1-9]00)|[1-9]000)-(0[1-9]|1[0-2])-(0[1-9]|[1-2][0-9]|3[0-1])T([01][0-9]|2[0-3]):[0-5][0-9]:([0-5][0-9]|60)(\.[0-9]+)?(Z|(\+|-)((0[0-9]|1[0-3]):[0-5][0-9]|14:00))";
+
+    /// All FHIR primitive types (R4 through R5)
+    pub const FHIR_PRIMITIVES: &[PrimitiveTypeDef] = &[
+        PrimitiveTypeDef::new("boolean", "Boolean").regex("true|false"),
+        PrimitiveTypeDef::new("integer", "Integer")
+            .regex(r"[0]|[-+]?[1-9][0-9]*")
+            .range(i32::MIN as i64, i32::MAX as i64),
+        PrimitiveTypeDef::new("integer64", "Long")
+            .regex(r"[0]|[-+]?[1-9][0-9]*")
+            .range(i64::MIN, i64::MAX),
+        PrimitiveTypeDef::new("unsignedInt", "Integer")
+            .derived_from("integer")
+            .regex(r"[0]|([1-9][0-9]*)")
+            .range(0, i32::MAX as i64),
+        PrimitiveTypeDef::new("positiveInt", "Integer")
+            .derived_from("integer")
+            .regex(r"\+?[1-9][0-9]*")
+            .range(1, i32::MAX as i64),
+        PrimitiveTypeDef::new("decimal", "Decimal")
+            .regex(r"-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?"),
+        PrimitiveTypeDef::new("string", "String")
+            .regex(r"[ \r\n\t\S]+")
+            .max_length(1024 * 1024),
+        PrimitiveTypeDef::new("code", "String")
+            .derived_from("string")
+            .regex(r"[^\s]+( [^\s]+)*"),
+        PrimitiveTypeDef::new("id", "String")
+            .derived_from("string")
+            .regex(r"[A-Za-z0-9\-\.]{1,64}")
+            .max_length(64),
+        PrimitiveTypeDef::new("markdown", "String")
+            .derived_from("string")
+            .regex(r"\s*(\S|\s)*"),
+        PrimitiveTypeDef::new("uri", "String").regex(r"\S*"),
+        PrimitiveTypeDef::new("url", "String")
+            .derived_from("uri")
+            .regex(r"\S*"),
+        PrimitiveTypeDef::new("canonical", "String")
+            .derived_from("uri")
+            .regex(r"\S*"),
+        PrimitiveTypeDef::new("oid", "String")
+            .derived_from("uri")
+            .regex(r"urn:oid:[0-2](\.(0|[1-9][0-9]*))+"),
+        PrimitiveTypeDef::new("uuid", "String")
+            .derived_from("uri")
+            .regex(r"urn:uuid:[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}"),
+        PrimitiveTypeDef::new("base64Binary", "String").regex(r"(\s*([0-9a-zA-Z\+/=]){4}\s*)+"),
+        PrimitiveTypeDef::new("xhtml", "String"),
+        PrimitiveTypeDef::new("date", "Date").regex(DATE_REGEX),
+        PrimitiveTypeDef::new("dateTime", "DateTime").regex(DATE_TIME_REGEX),
+        PrimitiveTypeDef::new("instant", "DateTime").regex(INSTANT_REGEX),
+        PrimitiveTypeDef::new("time", "Time")
+            .regex(r"([01][0-9]|2[0-3]):[0-5][0-9]:([0-5][0-9]|60)(\.[0-9]+)?"),
+    ];
+
+    /// Look up a FHIR primitive type by name (optionally qualified as `FHIR.code`)
+    pub fn primitive_type(name: &str) -> Option<&'static PrimitiveTypeDef> {
+        let name = name.strip_prefix("FHIR.").unwrap_or(name);
+        FHIR_PRIMITIVES.iter().find(|p| p.name == name)
+    }
+
+    /// FHIRPath System type a FHIR primitive maps to (`code` -> `String`)
+    pub fn to_system_type(fhir_primitive: &str) -> Option<&'static str> {
+        primitive_type(fhir_primitive).map(|p| p.system_type)
+    }
+
+    /// Whether a name is a FHIR primitive type
+    pub fn is_fhir_primitive(name: &str) -> bool {
+        primitive_type(name).is_some()
+    }
 }
 
-/// Element information for completion suggestions
+/// Element information for completion suggestions, code generation and form building
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ElementInfo {
     /// Element name
     pub name: String,
-    /// Element type
+    /// Element path (e.g. `Patient.name`)
+    pub path: String,
+    /// Element type; choice elements list their types separated by ` | `
     pub element_type: String,
     /// Documentation/description
     pub documentation: Option<String>,
+    /// Element cardinality
+    pub cardinality: Cardinality,
+    /// Whether the element can change the meaning of its parent (`isModifier`)
+    pub is_modifier: bool,
+    /// Whether the element is part of the summary view (`isSummary`)
+    pub is_summary: bool,
+    /// Whether implementations must support the element (`mustSupport`)
+    pub must_support: bool,
+    /// Terminology binding for coded elements
+    pub binding: Option<ElementBinding>,
+    /// Fixed value (`fixed[x]`)
+    pub fixed_value: Option<serde_json::Value>,
+    /// Pattern value (`pattern[x]`)
+    pub pattern_value: Option<serde_json::Value>,
+    /// Keys of the constraints that apply to the element (e.g. `ele-1`)
+    pub constraints: Vec<String>,
+}
+
+impl ElementInfo {
+    /// Create element information with an optional singleton cardinality (`0..1`)
+    ///
+    /// The path defaults to the element name; use `with_path` to set the full path.
+    pub fn new(name: impl Into<String>, element_type: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            path: name.clone(),
+            name,
+            element_type: element_type.into(),
+            documentation: None,
+            cardinality: Cardinality::OPTIONAL,
+            is_modifier: false,
+            is_summary: false,
+            must_support: false,
+            binding: None,
+            fixed_value: None,
+            pattern_value: None,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Set the element path
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Set the documentation
+    pub fn with_documentation(mut self, documentation: impl Into<String>) -> Self {
+        self.documentation = Some(documentation.into());
+        self
+    }
+
+    /// Set the cardinality
+    pub fn with_cardinality(mut self, cardinality: Cardinality) -> Self {
+        self.cardinality = cardinality;
+        self
+    }
+
+    /// Mark the element as a modifier
+    pub fn with_modifier(mut self, is_modifier: bool) -> Self {
+        self.is_modifier = is_modifier;
+        self
+    }
+
+    /// Mark the element as part of the summary view
+    pub fn with_summary(mut self, is_summary: bool) -> Self {
+        self.is_summary = is_summary;
+        self
+    }
+
+    /// Mark the element as must-support
+    pub fn with_must_support(mut self, must_support: bool) -> Self {
+        self.must_support = must_support;
+        self
+    }
+
+    /// Set the terminology binding
+    pub fn with_binding(mut self, binding: ElementBinding) -> Self {
+        self.binding = Some(binding);
+        self
+    }
+
+    /// Set the fixed value
+    pub fn with_fixed_value(mut self, value: serde_json::Value) -> Self {
+        self.fixed_value = Some(value);
+        self
+    }
+
+    /// Set the pattern value
+    pub fn with_pattern_value(mut self, value: serde_json::Value) -> Self {
+        self.pattern_value = Some(value);
+        self
+    }
+
+    /// Set the keys of the constraints that apply
+    pub fn with_constraints(mut self, constraints: Vec<String>) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Whether the element may repeat
+    pub fn is_collection(&self) -> bool {
+        self.cardinality.is_collection()
+    }
+}
+
+/// Strength of a terminology binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BindingStrength {
+    /// Codes must come from the value set
+    Required,
+    /// Codes must come from the value set if a suitable code exists
+    Extensible,
+    /// Codes are encouraged to come from the value set
+    Preferred,
+    /// The value set is only an example
+    Example,
+}
+
+impl BindingStrength {
+    /// Parse a binding strength code (`required`, `extensible`, ...)
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "required" => Some(Self::Required),
+            "extensible" => Some(Self::Extensible),
+            "preferred" => Some(Self::Preferred),
+            "example" => Some(Self::Example),
+            _ => None,
+        }
+    }
+
+    /// FHIR code of the binding strength
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Required => "required",
+            Self::Extensible => "extensible",
+            Self::Preferred => "preferred",
+            Self::Example => "example",
+        }
+    }
+}
+
+/// Terminology binding of a coded element
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ElementBinding {
+    /// Binding strength
+    pub strength: BindingStrength,
+    /// Canonical URL of the bound ValueSet
+    pub value_set: Option<String>,
 }
 
 /// Choice type information for valueX properties
@@ -185,8 +674,138 @@ pub struct ChoiceTypeInfo {
     pub type_name: String,
 }
 
+/// Element cardinality (`min..max`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cardinality {
+    /// Minimum number of occurrences
+    pub min: u32,
+    /// Maximum number of occurrences (`None` for unbounded)
+    pub max: Option<u32>,
+}
+
+impl Cardinality {
+    /// Optional singleton (`0..1`)
+    pub const OPTIONAL: Self = Self {
+        min: 0,
+        max: Some(1),
+    };
+
+    /// Create a cardinality from a FHIR `min` and `max` (`"*"` for unbounded)
+    pub fn from_bounds(min: u32, max: &str) -> Self {
+        Self {
+            min,
+            max: max.parse().ok(),
+        }
+    }
+
+    /// Whether the element may repeat
+    pub fn is_collection(&self) -> bool {
+        self.max.is_none_or(|max| max > 1)
+    }
+
+    /// Whether the element must be present
+    pub fn is_required(&self) -> bool {
+        self.min > 0
+    }
+
+    /// Whether the element has been constrained out (`max = 0`)
+    pub fn is_prohibited(&self) -> bool {
+        self.max == Some(0)
+    }
+}
+
+impl std::fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "{}..{max}", self.min),
+            None => write!(f, "{}..*", self.min),
+        }
+    }
+}
+
+/// Element information in the context of a profile
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProfileElementInfo {
+    /// Element id within the profile (e.g. `Patient.identifier:mrn.system`)
+    pub id: String,
+    /// Element path (e.g. `Patient.identifier.system`)
+    pub path: String,
+    /// Type to use for further navigation, narrowed by the profile
+    pub type_info: TypeInfo,
+    /// Allowed type codes after profile constraints
+    pub types: Vec<String>,
+    /// Profiles the element's type must conform to
+    pub type_profiles: Vec<String>,
+    /// Cardinality after profile constraints
+    pub cardinality: Cardinality,
+    /// Fixed value (`fixed[x]`)
+    pub fixed_value: Option<serde_json::Value>,
+    /// Pattern value (`pattern[x]`)
+    pub pattern_value: Option<serde_json::Value>,
+    /// Slicing discriminators if the element is sliced
+    pub discriminators: Vec<SlicingDiscriminator>,
+    /// Slices defined on the element
+    pub slices: Vec<SliceDefinition>,
+}
+
+/// A slice defined by a profile on a repeating element
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SliceDefinition {
+    /// Slice name (e.g. `mrn`)
+    pub name: String,
+    /// Element id of the slice (e.g. `Patient.identifier:mrn`)
+    pub id: String,
+    /// Cardinality of the slice
+    pub cardinality: Cardinality,
+    /// Allowed type codes
+    pub types: Vec<String>,
+    /// Profiles the slice must conform to (e.g. extension definitions)
+    pub type_profiles: Vec<String>,
+}
+
+/// Extension definition resolved from its canonical URL
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExtensionDefinition {
+    /// Canonical URL, or the relative URL (e.g. `ombCategory`) of a sub-extension
+    pub url: String,
+    /// Where the extension may be used
+    pub contexts: Vec<ExtensionContext>,
+    /// Cardinality of the extension where it is used
+    pub cardinality: Cardinality,
+    /// Allowed `value[x]` types (empty for complex extensions)
+    pub value_types: Vec<TypeInfo>,
+    /// Sub-extensions of a complex extension
+    pub extensions: Vec<ExtensionDefinition>,
+}
+
+impl ExtensionDefinition {
+    /// Whether the extension is made of sub-extensions instead of a value
+    pub fn is_complex(&self) -> bool {
+        self.value_types.is_empty()
+    }
+
+    /// Type of `value` when the extension allows exactly one value type
+    pub fn value_type(&self) -> Option<&TypeInfo> {
+        match self.value_types.as_slice() {
+            [value_type] => Some(value_type),
+            _ => None,
+        }
+    }
+
+    /// Sub-extension by URL
+    pub fn extension(&self, url: &str) -> Option<&ExtensionDefinition> {
+        self.extensions
+            .iter()
+            .find(|extension| extension.url == url)
+    }
+}
+
 /// FHIR version enumeration
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FhirVersion {
     /// FHIR R4 (4.0.x)
@@ -204,6 +823,18 @@ pub enum FhirVersion {
     },
 }
 
+/// Minimal type hierarchy of `EmptyModelProvider` as (derived, base) pairs
+const EMPTY_MODEL_BASE_TYPES: &[(&str, &str)] = &[
+    ("code", "string"),
+    ("id", "string"),
+    ("uri", "string"),
+    ("Patient", "DomainResource"),
+    ("Observation", "DomainResource"),
+    ("Practitioner", "DomainResource"),
+    ("Organization", "DomainResource"),
+    ("DomainResource", "Resource"),
+];
+
 /// Empty implementation of ModelProvider for testing and default behavior
 #[derive(Debug, Clone, Default)]
 pub struct EmptyModelProvider;
@@ -211,44 +842,7 @@ pub struct EmptyModelProvider;
 #[async_trait]
 impl ModelProvider for EmptyModelProvider {
     async fn get_type(&self, type_name: &str) -> Result<Option<TypeInfo>> {
-        match type_name {
-            "Patient" | "Observation" | "Practitioner" | "Organization" => Ok(Some(TypeInfo {
-                type_name: "Any".to_string(),
-                singleton: Some(true),
-                is_empty: Some(false),
-                namespace: Some("FHIR".to_string()),
-                name: Some(type_name.to_string()),
-            })),
-            "Boolean" => Ok(Some(TypeInfo {
-                type_name: "Boolean".to_string(),
-                singleton: Some(true),
-                is_empty: Some(false),
-                namespace: Some("System".to_string()),
-                name: Some("Boolean".to_string()),
-            })),
-            "String" => Ok(Some(TypeInfo {
-                type_name: "String".to_string(),
-                singleton: Some(true),
-                is_empty: Some(false),
-                namespace: Some("System".to_string()),
-                name: Some("String".to_string()),
-            })),
-            "Integer" => Ok(Some(TypeInfo {
-                type_name: "Integer".to_string(),
-                singleton: Some(true),
-                is_empty: Some(false),
-                namespace: Some("System".to_string()),
-                name: Some("Integer".to_string()),
-            })),
-            "Decimal" => Ok(Some(TypeInfo {
-                type_name: "Decimal".to_string(),
-                singleton: Some(true),
-                is_empty: Some(false),
-                namespace: Some("System".to_string()),
-                name: Some("Decimal".to_string()),
-            })),
-            _ => Ok(None),
-        }
+        Ok(self.get_type_sync(type_name))
     }
 
     async fn get_element_type(
@@ -256,29 +850,7 @@ impl ModelProvider for EmptyModelProvider {
         parent_type: &TypeInfo,
         property_name: &str,
     ) -> Result<Option<TypeInfo>> {
-        match (
-            parent_type
-                .name
-                .as_deref()
-                .unwrap_or(&parent_type.type_name),
-            property_name,
-        ) {
-            ("Patient", "name") => Ok(Some(TypeInfo {
-                type_name: "Any".to_string(),
-                singleton: Some(false),
-                is_empty: Some(false),
-                namespace: Some("FHIR".to_string()),
-                name: Some("HumanName".to_string()),
-            })),
-            ("HumanName", "given") => Ok(Some(TypeInfo {
-                type_name: "String".to_string(),
-                singleton: Some(false),
-                is_empty: Some(false),
-                namespace: Some("System".to_string()),
-                name: Some("String".to_string()),
-            })),
-            _ => Ok(None),
-        }
+        Ok(self.get_element_type_sync(parent_type, property_name))
     }
 
     fn of_type(&self, type_info: &TypeInfo, target_type: &str) -> Option<TypeInfo> {
@@ -307,17 +879,56 @@ impl ModelProvider for EmptyModelProvider {
     }
 
     fn is_type_derived_from(&self, derived_type: &str, base_type: &str) -> bool {
-        if derived_type == base_type {
-            return true;
+        let mut current = derived_type;
+        loop {
+            if current == base_type {
+                return true;
+            }
+            match EMPTY_MODEL_BASE_TYPES
+                .iter()
+                .find(|(derived, _)| *derived == current)
+            {
+                Some((_, base)) => current = base,
+                None => return false,
+            }
+        }
+    }
+
+    fn get_type_ancestors(&self, type_name: &str) -> Vec<String> {
+        let mut ancestors = Vec::new();
+        let mut current = type_name;
+        while let Some((_, base)) = EMPTY_MODEL_BASE_TYPES
+            .iter()
+            .find(|(derived, _)| *derived == current)
+        {
+            ancestors.push(base.to_string());
+            current = base;
         }
+        ancestors
+    }
 
-        // Minimal type hierarchy for testing - in real implementation this comes from schemas
-        matches!(
-            (derived_type, base_type),
-            ("code" | "id" | "uri", "string")
-                | ("Patient", "DomainResource")
-                | ("DomainResource", "Resource")
-        )
+    fn get_direct_subtypes(&self, type_name: &str) -> Vec<String> {
+        let mut subtypes: Vec<String> = EMPTY_MODEL_BASE_TYPES
+            .iter()
+            .filter(|(_, base)| *base == type_name)
+            .map(|(derived, _)| derived.to_string())
+            .collect();
+        subtypes.sort();
+        subtypes
+    }
+
+    fn get_concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        let mut subtypes: Vec<String> = EMPTY_MODEL_BASE_TYPES
+            .iter()
+            .map(|(derived, _)| *derived)
+            .filter(|derived| !matches!(*derived, "Resource" | "DomainResource"))
+            .filter(|derived| {
+                *derived != type_name && self.is_type_derived_from(derived, type_name)
+            })
+            .map(String::from)
+            .collect();
+        subtypes.sort();
+        subtypes
     }
 
     fn get_element_names(&self, parent_type: &TypeInfo) -> Vec<String> {
@@ -345,32 +956,21 @@ impl ModelProvider for EmptyModelProvider {
     }
 
     async fn get_children_type(&self, parent_type: &TypeInfo) -> Result<Option<TypeInfo>> {
-        if parent_type.singleton.unwrap_or(true) {
-            Ok(None)
-        } else {
-            Ok(Some(TypeInfo {
-                type_name: parent_type.type_name.clone(),
-                singleton: Some(true),
-                is_empty: Some(false),
-                namespace: parent_type.namespace.clone(),
-                name: parent_type.name.clone(),
-            }))
-        }
+        Ok(self.get_children_type_sync(parent_type))
     }
 
     async fn get_elements(&self, type_name: &str) -> Result<Vec<ElementInfo>> {
         match type_name {
             "Patient" => Ok(vec![
-                ElementInfo {
-                    name: "id".to_string(),
-                    element_type: "id".to_string(),
-                    documentation: Some("Logical id of this artifact".to_string()),
-                },
-                ElementInfo {
-                    name: "name".to_string(),
-                    element_type: "HumanName[]".to_string(),
-                    documentation: Some("A name associated with the patient".to_string()),
-                },
+                ElementInfo::new("id", "id")
+                    .with_path("Patient.id")
+                    .with_documentation("Logical id of this artifact")
+                    .with_summary(true),
+                ElementInfo::new("name", "HumanName")
+                    .with_path("Patient.name")
+                    .with_documentation("A name associated with the patient")
+                    .with_cardinality(Cardinality { min: 0, max: None })
+                    .with_summary(true),
             ]),
             _ => Ok(Vec::new()),
         }
@@ -443,18 +1043,18 @@ impl ModelProvider for EmptyModelProvider {
         match type_info.type_name.as_str() {
             "Union" | "Choice" => Ok(Some(vec![
                 TypeInfo {
-                    type_name: "String".to_string(),
+                    type_name: Symbol::intern("String"),
                     singleton: Some(true),
                     is_empty: Some(false),
-                    namespace: Some("System".to_string()),
-                    name: Some("String".to_string()),
+                    namespace: Some(Symbol::intern("System")),
+                    name: Some(Symbol::intern("String")),
                 },
                 TypeInfo {
-                    type_name: "Integer".to_string(),
+                    type_name: Symbol::intern("Integer"),
                     singleton: Some(true),
                     is_empty: Some(false),
-                    namespace: Some("System".to_string()),
-                    name: Some("Integer".to_string()),
+                    namespace: Some(Symbol::intern("System")),
+                    name: Some(Symbol::intern("Integer")),
                 },
             ])),
             _ => Ok(None),
@@ -464,6 +1064,116 @@ impl ModelProvider for EmptyModelProvider {
     fn is_union_type(&self, type_info: &TypeInfo) -> bool {
         matches!(type_info.type_name.as_str(), "Union" | "Choice")
     }
+
+    fn as_sync(&self) -> Option<&dyn SyncModelProvider> {
+        Some(self)
+    }
+}
+
+impl SyncModelProvider for EmptyModelProvider {
+    fn get_type_sync(&self, type_name: &str) -> Option<TypeInfo> {
+        match type_name {
+            "Patient" | "Observation" | "Practitioner" | "Organization" => Some(TypeInfo {
+                type_name: Symbol::intern("Any"),
+                singleton: Some(true),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("FHIR")),
+                name: Some(Symbol::intern(type_name)),
+            }),
+            "Boolean" => Some(TypeInfo {
+                type_name: Symbol::intern("Boolean"),
+                singleton: Some(true),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("System")),
+                name: Some(Symbol::intern("Boolean")),
+            }),
+            "String" => Some(TypeInfo {
+                type_name: Symbol::intern("String"),
+                singleton: Some(true),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("System")),
+                name: Some(Symbol::intern("String")),
+            }),
+            "Integer" => Some(TypeInfo {
+                type_name: Symbol::intern("Integer"),
+                singleton: Some(true),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("System")),
+                name: Some(Symbol::intern("Integer")),
+            }),
+            "Decimal" => Some(TypeInfo {
+                type_name: Symbol::intern("Decimal"),
+                singleton: Some(true),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("System")),
+                name: Some(Symbol::intern("Decimal")),
+            }),
+            _ => TypeInfo::fhir_primitive(type_name, true),
+        }
+    }
+
+    fn get_element_type_sync(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Option<TypeInfo> {
+        match (
+            parent_type
+                .name
+                .as_deref()
+                .unwrap_or(&parent_type.type_name),
+            property_name,
+        ) {
+            ("Patient", "name") => Some(TypeInfo {
+                type_name: Symbol::intern("Any"),
+                singleton: Some(false),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("FHIR")),
+                name: Some(Symbol::intern("HumanName")),
+            }),
+            ("HumanName", "given") => Some(TypeInfo {
+                type_name: Symbol::intern("String"),
+                singleton: Some(false),
+                is_empty: Some(false),
+                namespace: Some(Symbol::intern("System")),
+                name: Some(Symbol::intern("String")),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl FhirVersion {
+    /// Parse a FHIR version string such as "4.0.1", "5.0.0" or "R4B"
+    ///
+    /// Unrecognized versions are returned as `FhirVersion::Custom`.
+    pub fn from_version_str(version: &str) -> Self {
+        match version {
+            "R4" | "r4" => FhirVersion::R4,
+            "R4B" | "r4b" => FhirVersion::R4B,
+            "R5" | "r5" => FhirVersion::R5,
+            "R6" | "r6" => FhirVersion::R6,
+            v if v.starts_with("4.0") => FhirVersion::R4,
+            v if v.starts_with("4.3") => FhirVersion::R4B,
+            v if v.starts_with("5.0") => FhirVersion::R5,
+            v if v.starts_with("6.0") => FhirVersion::R6,
+            v => FhirVersion::Custom {
+                version: v.to_string(),
+            },
+        }
+    }
+
+    /// FHIR version of a core package (`hl7.fhir.r4.core`, `hl7.fhir.r5.expansions`, ...)
+    pub fn from_core_package(package_name: &str) -> Option<Self> {
+        let release = package_name.strip_prefix("hl7.fhir.")?.split('.').next()?;
+        match release {
+            "r4" => Some(FhirVersion::R4),
+            "r4b" => Some(FhirVersion::R4B),
+            "r5" => Some(FhirVersion::R5),
+            "r6" => Some(FhirVersion::R6),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for FhirVersion {
@@ -483,7 +1193,7 @@ impl std::fmt::Display for FhirVersion {
 impl IntoEvaluationResult for TypeInfo {
     fn to_evaluation_result(&self) -> EvaluationResult {
         // Convert TypeInfo to an object representation
-        let mut map = std::collections::HashMap::new();
+        let mut map = indexmap::IndexMap::new();
 
         map.insert(
             "type_name".to_string(),
@@ -506,11 +1216,13 @@ impl IntoEvaluationResult for TypeInfo {
             map.insert("name".to_string(), name.to_evaluation_result());
         }
 
-        let type_info = if let Some(ref namespace) = self.namespace {
-            Some(TypeInfoResult::new(namespace, &self.type_name))
-        } else {
-            Some(TypeInfoResult::system(&self.type_name))
-        };
+        let type_info = Some(TypeInfoResult {
+            namespace: self
+                .namespace
+                .clone()
+                .unwrap_or_else(|| Symbol::intern("System")),
+            name: self.type_name.clone(),
+        });
 
         EvaluationResult::Object { map, type_info }
     }
@@ -518,9 +1230,10 @@ impl IntoEvaluationResult for TypeInfo {
 
 impl IntoEvaluationResult for ElementInfo {
     fn to_evaluation_result(&self) -> EvaluationResult {
-        let mut map = std::collections::HashMap::new();
+        let mut map = indexmap::IndexMap::new();
 
         map.insert("name".to_string(), self.name.to_evaluation_result());
+        map.insert("path".to_string(), self.path.to_evaluation_result());
         map.insert(
             "element_type".to_string(),
             self.element_type.to_evaluation_result(),
@@ -533,13 +1246,52 @@ impl IntoEvaluationResult for ElementInfo {
             );
         }
 
+        map.insert(
+            "min".to_string(),
+            (self.cardinality.min as i64).to_evaluation_result(),
+        );
+        let max = match self.cardinality.max {
+            Some(max) => max.to_string(),
+            None => "*".to_string(),
+        };
+        map.insert("max".to_string(), max.to_evaluation_result());
+        map.insert(
+            "is_modifier".to_string(),
+            self.is_modifier.to_evaluation_result(),
+        );
+        map.insert(
+            "is_summary".to_string(),
+            self.is_summary.to_evaluation_result(),
+        );
+        map.insert(
+            "must_support".to_string(),
+            self.must_support.to_evaluation_result(),
+        );
+
+        if let Some(ref binding) = self.binding {
+            map.insert(
+                "binding_strength".to_string(),
+                binding.strength.as_str().to_evaluation_result(),
+            );
+            if let Some(ref value_set) = binding.value_set {
+                map.insert("value_set".to_string(), value_set.to_evaluation_result());
+            }
+        }
+
+        if !self.constraints.is_empty() {
+            map.insert(
+                "constraints".to_string(),
+                self.constraints.to_evaluation_result(),
+            );
+        }
+
         EvaluationResult::typed_object(map, "FHIR", "ElementInfo")
     }
 }
 
 impl IntoEvaluationResult for ChoiceTypeInfo {
     fn to_evaluation_result(&self) -> EvaluationResult {
-        let mut map = std::collections::HashMap::new();
+        let mut map = indexmap::IndexMap::new();
 
         map.insert("suffix".to_string(), self.suffix.to_evaluation_result());
         map.insert(
@@ -595,6 +1347,9 @@ impl LiteModelProvider {
 #[async_trait]
 impl ModelProvider for LiteModelProvider {
     async fn get_type(&self, type_name: &str) -> Result<Option<TypeInfo>> {
+        if let Some(sync) = self.inner.as_sync() {
+            return Ok(sync.get_type_sync(type_name));
+        }
         self.inner.get_type(type_name).await
     }
 
@@ -603,6 +1358,9 @@ impl ModelProvider for LiteModelProvider {
         parent_type: &TypeInfo,
         property_name: &str,
     ) -> Result<Option<TypeInfo>> {
+        if let Some(sync) = self.inner.as_sync() {
+            return Ok(sync.get_element_type_sync(parent_type, property_name));
+        }
         self.inner
             .get_element_type(parent_type, property_name)
             .await
@@ -617,6 +1375,9 @@ impl ModelProvider for LiteModelProvider {
     }
 
     async fn get_children_type(&self, parent_type: &TypeInfo) -> Result<Option<TypeInfo>> {
+        if let Some(sync) = self.inner.as_sync() {
+            return Ok(sync.get_children_type_sync(parent_type));
+        }
         self.inner.get_children_type(parent_type).await
     }
 
@@ -636,6 +1397,10 @@ impl ModelProvider for LiteModelProvider {
         self.inner.get_primitive_types().await
     }
 
+    async fn get_logical_types(&self) -> Result<Vec<String>> {
+        self.inner.get_logical_types().await
+    }
+
     async fn resource_type_exists(&self, resource_type: &str) -> Result<bool> {
         self.inner.resource_type_exists(resource_type).await
     }
@@ -648,6 +1413,22 @@ impl ModelProvider for LiteModelProvider {
         self.inner.is_type_derived_from(derived_type, base_type)
     }
 
+    fn get_type_ancestors(&self, type_name: &str) -> Vec<String> {
+        self.inner.get_type_ancestors(type_name)
+    }
+
+    fn get_direct_subtypes(&self, type_name: &str) -> Vec<String> {
+        self.inner.get_direct_subtypes(type_name)
+    }
+
+    fn get_concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        self.inner.get_concrete_subtypes(type_name)
+    }
+
+    fn get_common_supertype(&self, type_names: &[&str]) -> Option<String> {
+        self.inner.get_common_supertype(type_names)
+    }
+
     async fn get_choice_types(
         &self,
         parent_type: &str,
@@ -665,4 +1446,85 @@ impl ModelProvider for LiteModelProvider {
     fn is_union_type(&self, type_info: &TypeInfo) -> bool {
         self.inner.is_union_type(type_info)
     }
+
+    async fn get_profile_type(&self, profile_url: &str) -> Result<Option<TypeInfo>> {
+        self.inner.get_profile_type(profile_url).await
+    }
+
+    async fn get_element_type_in_profile(
+        &self,
+        profile_url: &str,
+        parent_path: &str,
+        property_name: &str,
+    ) -> Result<Option<ProfileElementInfo>> {
+        self.inner
+            .get_element_type_in_profile(profile_url, parent_path, property_name)
+            .await
+    }
+
+    async fn get_slices(
+        &self,
+        profile_url: &str,
+        element_path: &str,
+    ) -> Result<Vec<SliceDefinition>> {
+        self.inner.get_slices(profile_url, element_path).await
+    }
+
+    async fn get_extension_definition(
+        &self,
+        extension_url: &str,
+    ) -> Result<Option<ExtensionDefinition>> {
+        self.inner.get_extension_definition(extension_url).await
+    }
+
+    fn as_sync(&self) -> Option<&dyn SyncModelProvider> {
+        self.inner.as_sync()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fhir_primitive_catalogue() {
+        assert_eq!(type_constants::to_system_type("code"), Some("String"));
+        assert_eq!(
+            type_constants::to_system_type("FHIR.instant"),
+            Some("DateTime")
+        );
+        assert_eq!(type_constants::to_system_type("Patient"), None);
+
+        let positive_int = type_constants::primitive_type("positiveInt").unwrap();
+        assert_eq!(positive_int.base, Some("integer"));
+        assert!(positive_int.is_in_range(1) && !positive_int.is_in_range(0));
+        assert!(
+            !type_constants::primitive_type("id")
+                .unwrap()
+                .is_within_length(&"a".repeat(65))
+        );
+
+        let uri = EmptyModelProvider.get_type("uri").await.unwrap().unwrap();
+        assert_eq!(uri.type_name, "String");
+        assert_eq!(uri.name.as_deref(), Some("uri"));
+        assert_eq!(TypeInfo::fhir_primitive("Patient", true), None);
+    }
+
+    #[tokio::test]
+    async fn test_sync_fast_path() {
+        let lite = LiteModelProvider::new(Arc::new(EmptyModelProvider));
+        let sync = lite.as_sync().expect("EmptyModelProvider is in memory");
+
+        let patient = sync.get_type_sync("Patient").unwrap();
+        let name = sync.get_element_type_sync(&patient, "name").unwrap();
+        assert_eq!(name.name.as_deref(), Some("HumanName"));
+        assert_eq!(
+            lite.get_element_type(&patient, "name").await.unwrap(),
+            Some(name.clone())
+        );
+
+        let item = sync.get_children_type_sync(&name).unwrap();
+        assert_eq!(item.singleton, Some(true));
+        assert_eq!(sync.get_children_type_sync(&item), None);
+    }
 }
@@ -0,0 +1,441 @@
+//! Reflection of compiled Rust model types
+//!
+//! This module lets generated Rust structs describe their FHIR structure
+//! (elements, cardinalities, choice variants and backbone elements) through the
+//! `FhirType` trait. `PackageModelProvider::register_type` turns these
+//! descriptions into type definitions, so a `ModelProvider` can be assembled
+//! from the compiled types and FHIRPath typing cannot drift from the structs.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! impl FhirType for Observation {
+//!     fn type_reflection() -> TypeReflection {
+//!         TypeReflection::new("Observation", StructureDefinitionKind::Resource)
+//!             .with_base("DomainResource")
+//!             .with_element(ElementReflection::new("status", "code").with_cardinality(1, Some(1)))
+//!             .with_element(ElementReflection::from_choice::<ObservationValue>())
+//!     }
+//! }
+//!
+//! let provider = PackageModelProvider::new().with_type::<Observation>();
+//! ```
+
+use crate::error::{ModelError, Result};
+use crate::fhir_traits::{ChoiceElement, FhirResourceMetadata};
+use crate::package_provider::CORE_STRUCTURE_DEFINITION_PREFIX;
+use crate::provider::type_constants;
+use crate::structure_definition::{
+    ElementDefinition, ElementType, StructureDefinition, StructureDefinitionKind, TypeDerivation,
+};
+
+/// Rust type that describes the FHIR structure it models
+pub trait FhirType {
+    /// Structure of the type
+    fn type_reflection() -> TypeReflection;
+}
+
+/// FHIR structure of a compiled type
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeReflection {
+    /// Type name (e.g. `Patient`)
+    pub name: &'static str,
+    /// Canonical URL, for types outside the core specification
+    pub url: Option<&'static str>,
+    /// Kind of the type
+    pub kind: StructureDefinitionKind,
+    /// Base type name (e.g. `DomainResource`) or canonical URL of a non-core base
+    pub base: Option<&'static str>,
+    /// Whether the type is abstract
+    pub is_abstract: bool,
+    /// Elements in definition order
+    pub elements: Vec<ElementReflection>,
+}
+
+impl TypeReflection {
+    /// Describe a type without elements
+    pub fn new(name: &'static str, kind: StructureDefinitionKind) -> Self {
+        Self {
+            name,
+            url: None,
+            kind,
+            base: None,
+            is_abstract: false,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Set the canonical URL (defaults to the core `StructureDefinition/{name}` URL)
+    ///
+    /// Custom and logical types need their own URL, so they do not collide with
+    /// core definitions and can be matched by `PackageModelProvider::with_namespace`.
+    pub fn with_url(mut self, url: &'static str) -> Self {
+        self.url = Some(url);
+        self
+    }
+
+    /// Set the base type, by name for core types or by canonical URL
+    pub fn with_base(mut self, base: &'static str) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Mark the type as abstract
+    pub fn with_abstract(mut self, is_abstract: bool) -> Self {
+        self.is_abstract = is_abstract;
+        self
+    }
+
+    /// Add an element
+    pub fn with_element(mut self, element: ElementReflection) -> Self {
+        self.elements.push(element);
+        self
+    }
+
+    /// Paths of the choice elements relative to the type (e.g. `value`, `component.value`)
+    pub fn choice_elements(&self) -> Vec<String> {
+        fn collect(elements: &[ElementReflection], prefix: &str, paths: &mut Vec<String>) {
+            for element in elements {
+                let path = format!("{prefix}{}", element.name);
+                if element.is_choice {
+                    paths.push(path.clone());
+                }
+                collect(&element.children, &format!("{path}."), paths);
+            }
+        }
+
+        let mut paths = Vec::new();
+        collect(&self.elements, "", &mut paths);
+        paths
+    }
+
+    /// Check that `FhirResourceMetadata` agrees with this structure
+    ///
+    /// The resource type must match the type name and the declared choice
+    /// elements must be exactly the reflected ones.
+    pub fn check_metadata<T: FhirResourceMetadata>(&self) -> Result<()> {
+        if T::resource_type() != self.name {
+            return Err(ModelError::validation_error(format!(
+                "Metadata describes '{}' but the reflected type is '{}'",
+                T::resource_type(),
+                self.name
+            )));
+        }
+
+        let mut declared: Vec<&str> = T::choice_elements().to_vec();
+        declared.sort_unstable();
+        let mut reflected = self.choice_elements();
+        reflected.sort_unstable();
+        if declared != reflected {
+            return Err(ModelError::validation_error(format!(
+                "Choice elements of '{}' disagree: metadata declares {declared:?}, type has {reflected:?}",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Specialization StructureDefinition with a snapshot of the reflected elements
+    pub fn to_structure_definition(&self) -> StructureDefinition {
+        let mut snapshot = vec![ElementDefinition {
+            id: Some(self.name.to_string()),
+            path: self.name.to_string(),
+            min: Some(0),
+            max: Some("*".to_string()),
+            ..Default::default()
+        }];
+        for element in &self.elements {
+            element.push_definitions(self.name, &mut snapshot);
+        }
+
+        StructureDefinition {
+            url: self.url.map_or_else(
+                || format!("{CORE_STRUCTURE_DEFINITION_PREFIX}{}", self.name),
+                String::from,
+            ),
+            version: None,
+            name: self.name.to_string(),
+            type_name: self.name.to_string(),
+            kind: self.kind,
+            is_abstract: self.is_abstract,
+            derivation: self.base.map(|_| TypeDerivation::Specialization),
+            base_definition: self.base.map(|base| {
+                if base.contains("://") {
+                    base.to_string()
+                } else {
+                    format!("{CORE_STRUCTURE_DEFINITION_PREFIX}{base}")
+                }
+            }),
+            fhir_version: None,
+            contexts: Vec::new(),
+            snapshot,
+            differential: Vec::new(),
+        }
+    }
+}
+
+/// FHIR structure of a field of a compiled type
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementReflection {
+    /// Element name, without `[x]` for choice elements
+    pub name: &'static str,
+    /// Allowed type codes
+    pub types: Vec<&'static str>,
+    /// Minimum cardinality
+    pub min: u32,
+    /// Maximum cardinality (`None` for unbounded)
+    pub max: Option<u32>,
+    /// Whether the element is a choice (`value[x]`)
+    pub is_choice: bool,
+    /// Child elements of a backbone element
+    pub children: Vec<ElementReflection>,
+}
+
+impl ElementReflection {
+    /// Describe an optional single-valued element of one type
+    pub fn new(name: &'static str, type_code: &'static str) -> Self {
+        Self {
+            name,
+            types: vec![type_code],
+            min: 0,
+            max: Some(1),
+            is_choice: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Describe a choice element (`value[x]`) allowing several types
+    pub fn choice(name: &'static str, types: &[&'static str]) -> Self {
+        Self {
+            types: types.to_vec(),
+            is_choice: true,
+            ..Self::new(name, "")
+        }
+    }
+
+    /// Describe a choice element from the generated enum implementing `ChoiceElement`
+    ///
+    /// Type codes are taken from the variant field names, so `valueDateTime`
+    /// yields `dateTime` and `valueQuantity` yields `Quantity`.
+    pub fn from_choice<C: ChoiceElement>() -> Self {
+        let name = C::base_name();
+        let types = C::possible_field_names()
+            .into_iter()
+            .filter_map(|field| field.strip_prefix(name))
+            .map(|suffix| {
+                type_constants::FHIR_PRIMITIVES
+                    .iter()
+                    .map(|primitive| primitive.name)
+                    .find(|primitive| primitive.eq_ignore_ascii_case(suffix))
+                    .unwrap_or(suffix)
+            })
+            .collect();
+        Self {
+            types,
+            is_choice: true,
+            ..Self::new(name, "")
+        }
+    }
+
+    /// Describe a backbone element with its child elements
+    pub fn backbone(name: &'static str, children: Vec<ElementReflection>) -> Self {
+        Self {
+            children,
+            ..Self::new(name, "BackboneElement")
+        }
+    }
+
+    /// Set the cardinality
+    pub fn with_cardinality(mut self, min: u32, max: Option<u32>) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Append the definitions of this element and its children under a parent path
+    fn push_definitions(&self, parent: &str, snapshot: &mut Vec<ElementDefinition>) {
+        let path = if self.is_choice {
+            format!("{parent}.{}[x]", self.name)
+        } else {
+            format!("{parent}.{}", self.name)
+        };
+        snapshot.push(ElementDefinition {
+            id: Some(path.clone()),
+            path: path.clone(),
+            min: Some(self.min),
+            max: Some(
+                self.max
+                    .map_or_else(|| "*".to_string(), |max| max.to_string()),
+            ),
+            types: self
+                .types
+                .iter()
+                .map(|code| ElementType {
+                    code: code.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        });
+        for child in &self.children {
+            child.push_definitions(&path, snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_provider::PackageModelProvider;
+    use crate::provider::ModelProvider;
+
+    struct PatientDeceased;
+
+    impl ChoiceElement for PatientDeceased {
+        fn base_name() -> &'static str {
+            "deceased"
+        }
+
+        fn possible_field_names() -> Vec<&'static str> {
+            vec!["deceasedBoolean", "deceasedDateTime"]
+        }
+    }
+
+    struct Patient;
+
+    impl FhirType for Patient {
+        fn type_reflection() -> TypeReflection {
+            TypeReflection::new("Patient", StructureDefinitionKind::Resource)
+                .with_base("DomainResource")
+                .with_element(ElementReflection::new("name", "HumanName").with_cardinality(0, None))
+                .with_element(ElementReflection::from_choice::<PatientDeceased>())
+                .with_element(
+                    ElementReflection::backbone(
+                        "contact",
+                        vec![ElementReflection::new("name", "HumanName")],
+                    )
+                    .with_cardinality(0, None),
+                )
+        }
+    }
+
+    impl FhirResourceMetadata for Patient {
+        fn choice_elements() -> &'static [&'static str] {
+            &["deceased"]
+        }
+
+        fn resource_type() -> &'static str {
+            "Patient"
+        }
+    }
+
+    struct HumanName;
+
+    impl FhirType for HumanName {
+        fn type_reflection() -> TypeReflection {
+            TypeReflection::new("HumanName", StructureDefinitionKind::ComplexType)
+                .with_base("Element")
+                .with_element(ElementReflection::new("family", "string"))
+        }
+    }
+
+    struct Shipment;
+
+    impl FhirType for Shipment {
+        fn type_reflection() -> TypeReflection {
+            TypeReflection::new("Shipment", StructureDefinitionKind::Logical)
+                .with_url("http://acme.org/fhir/StructureDefinition/Shipment")
+                .with_base("Element")
+                .with_element(ElementReflection::new("label", "string"))
+        }
+    }
+
+    struct Parcel;
+
+    impl FhirType for Parcel {
+        fn type_reflection() -> TypeReflection {
+            TypeReflection::new("Parcel", StructureDefinitionKind::Logical)
+                .with_url("http://acme.org/fhir/StructureDefinition/Parcel")
+                .with_base("http://acme.org/fhir/StructureDefinition/Shipment")
+        }
+    }
+
+    struct StalePatient;
+
+    impl FhirResourceMetadata for StalePatient {
+        fn choice_elements() -> &'static [&'static str] {
+            &["deceased", "value"]
+        }
+
+        fn resource_type() -> &'static str {
+            "Patient"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_from_reflected_types() {
+        let reflection = Patient::type_reflection();
+        assert_eq!(reflection.elements[1].types, vec!["boolean", "dateTime"]);
+        reflection.check_metadata::<Patient>().unwrap();
+        assert!(reflection.check_metadata::<StalePatient>().is_err());
+
+        let provider = PackageModelProvider::new()
+            .with_type::<Patient>()
+            .with_type::<HumanName>();
+        let patient = provider.get_type("Patient").await.unwrap().unwrap();
+        assert!(provider.is_type_derived_from("Patient", "DomainResource"));
+
+        let name = provider
+            .get_element_type(&patient, "name")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(name.name.as_deref(), Some("HumanName"));
+        assert_eq!(name.singleton, Some(false));
+        let family = provider
+            .get_element_type(&name, "family")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(family.type_name, "String");
+
+        let deceased = provider
+            .get_element_type(&patient, "deceasedDateTime")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(deceased.name.as_deref(), Some("dateTime"));
+
+        let contact = provider
+            .get_element_type(&patient, "contact")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(contact.name.as_deref(), Some("Patient.contact"));
+        assert!(
+            provider
+                .get_element_type(&contact, "name")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reflected_types_in_namespace() {
+        let definition = Shipment::type_reflection().to_structure_definition();
+        assert_eq!(
+            definition.url,
+            "http://acme.org/fhir/StructureDefinition/Shipment"
+        );
+
+        let provider = PackageModelProvider::new()
+            .with_namespace("Acme", "http://acme.org/fhir/")
+            .with_type::<Shipment>()
+            .with_type::<Parcel>();
+        let shipment = provider.get_type("Acme.Shipment").await.unwrap().unwrap();
+        assert_eq!(shipment.namespace.as_deref(), Some("Acme"));
+        assert!(provider.get_type("FHIR.Shipment").await.unwrap().is_none());
+        assert!(provider.is_type_derived_from("Parcel", "Shipment"));
+    }
+}
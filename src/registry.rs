@@ -0,0 +1,460 @@
+//! Registry of model providers for multiple FHIR versions
+//!
+//! This module provides `ModelProviderRegistry`, which holds one `ModelProvider`
+//! per `FhirVersion` so that a single process can serve, for example, R4 and R5
+//! traffic side by side. The registry can detect the version of a resource from
+//! its `meta.profile` and build evaluators per request without global state.
+
+use indexmap::IndexMap;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::canonical::Canonical;
+use crate::error::{ModelError, Result};
+use crate::evaluator::{FhirPathEvaluator, FhirPathEvaluatorFactory};
+use crate::package::{FhirPackage, PackageSet};
+use crate::provider::{FhirVersion, ModelProvider};
+
+/// Canonical URL prefix of the core FHIR StructureDefinitions
+const CORE_STRUCTURE_DEFINITION_PREFIX: &str = "http://hl7.org/fhir/StructureDefinition/";
+
+/// Model providers keyed by FHIR version
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use octofhir_fhir_model::ModelProviderRegistry;
+///
+/// let registry = ModelProviderRegistry::new()
+///     .with_provider(FhirVersion::R4, r4_provider)
+///     .with_provider(FhirVersion::R5, r5_provider)
+///     .with_default_version(FhirVersion::R4);
+///
+/// let provider = registry.provider_for_resource(&resource)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModelProviderRegistry {
+    /// Registered providers in registration order
+    providers: IndexMap<FhirVersion, Arc<dyn ModelProvider>>,
+    /// Version used when a resource does not identify one
+    default_version: Option<FhirVersion>,
+    /// Profile canonical URL -> profile version -> FHIR version the profile
+    /// was published for
+    profile_versions: HashMap<String, HashMap<Option<String>, FhirVersion>>,
+}
+
+impl ModelProviderRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a provider for a FHIR version
+    pub fn with_provider(mut self, version: FhirVersion, provider: Arc<dyn ModelProvider>) -> Self {
+        self.register(version, provider);
+        self
+    }
+
+    /// Set the version used when a resource does not identify one
+    pub fn with_default_version(mut self, version: FhirVersion) -> Self {
+        self.default_version = Some(version);
+        self
+    }
+
+    /// Register a provider for a FHIR version, returning the one it replaces
+    pub fn register(
+        &mut self,
+        version: FhirVersion,
+        provider: Arc<dyn ModelProvider>,
+    ) -> Option<Arc<dyn ModelProvider>> {
+        self.providers.insert(version, provider)
+    }
+
+    /// Register a provider under the version it reports via `get_fhir_version`
+    pub async fn register_provider(
+        &mut self,
+        provider: Arc<dyn ModelProvider>,
+    ) -> Result<FhirVersion> {
+        let version = provider.get_fhir_version().await?;
+        self.register(version.clone(), provider);
+        Ok(version)
+    }
+
+    /// Associate a profile canonical, with or without `|version`, with a FHIR version
+    ///
+    /// Fails if the same profile version is already registered for another
+    /// FHIR version.
+    pub fn register_profile(&mut self, profile: &str, version: FhirVersion) -> Result<()> {
+        self.insert_profile(Canonical::parse(profile), version)
+    }
+
+    fn insert_profile(&mut self, profile: Canonical, version: FhirVersion) -> Result<()> {
+        let versions = self
+            .profile_versions
+            .entry(profile.url.clone())
+            .or_default();
+        match versions.get(&profile.version) {
+            Some(registered) if *registered != version => {
+                Err(ModelError::invalid_configuration(format!(
+                    "Profile {profile} is already registered for FHIR version {registered}, \
+                     not {version}"
+                )))
+            }
+            _ => {
+                versions.insert(profile.version, version);
+                Ok(())
+            }
+        }
+    }
+
+    /// Associate every StructureDefinition of a package with the package's FHIR version
+    ///
+    /// Core definitions (`http://hl7.org/fhir/StructureDefinition/...`) share their
+    /// URLs across FHIR versions, so they are skipped; resources identify a core
+    /// version with an explicit `|version` instead. Profiles are registered
+    /// under their own `version`. Returns the number of profiles registered, or
+    /// an error if a profile version is already registered for another FHIR
+    /// version.
+    pub fn register_package(&mut self, package: &FhirPackage) -> Result<usize> {
+        let Some(version) = package.manifest().fhir_version() else {
+            return Ok(0);
+        };
+
+        let mut registered = 0;
+        for definition in package.structure_definitions() {
+            let Some(url) = definition.get("url").and_then(|u| u.as_str()) else {
+                continue;
+            };
+            if url.starts_with(CORE_STRUCTURE_DEFINITION_PREFIX) {
+                continue;
+            }
+            let profile = Canonical {
+                url: url.to_string(),
+                version: definition
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            };
+            self.insert_profile(profile, version.clone())?;
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
+    /// Register the StructureDefinitions of every package in a set
+    ///
+    /// Returns the number of profiles registered.
+    pub fn register_package_set(&mut self, packages: &PackageSet) -> Result<usize> {
+        packages
+            .packages()
+            .iter()
+            .map(|package| self.register_package(package))
+            .sum()
+    }
+
+    /// Provider registered for a version, if any
+    pub fn get(&self, version: &FhirVersion) -> Option<Arc<dyn ModelProvider>> {
+        self.providers.get(version).cloned()
+    }
+
+    /// Provider registered for a version
+    pub fn provider(&self, version: &FhirVersion) -> Result<Arc<dyn ModelProvider>> {
+        self.get(version).ok_or_else(|| {
+            ModelError::invalid_configuration(format!(
+                "No model provider registered for FHIR version {version}"
+            ))
+        })
+    }
+
+    /// Registered versions in registration order
+    pub fn versions(&self) -> Vec<FhirVersion> {
+        self.providers.keys().cloned().collect()
+    }
+
+    /// Whether a provider is registered for a version
+    pub fn contains(&self, version: &FhirVersion) -> bool {
+        self.providers.contains_key(version)
+    }
+
+    /// Version used when a resource does not identify one
+    ///
+    /// Falls back to the only registered version when no default is configured.
+    pub fn default_version(&self) -> Option<FhirVersion> {
+        if self.default_version.is_some() {
+            return self.default_version.clone();
+        }
+        match self.providers.len() {
+            1 => self.providers.keys().next().cloned(),
+            _ => None,
+        }
+    }
+
+    /// Detect the FHIR version of a resource
+    ///
+    /// Checks `meta.profile` against versioned core profiles
+    /// (`http://hl7.org/fhir/StructureDefinition/Patient|4.0.1`) and registered
+    /// profiles, then the `fhirVersion` element of conformance resources.
+    /// A registered profile is matched by its `|version` when the reference
+    /// names one that is registered, and otherwise by its URL only when all of
+    /// its registered versions share a FHIR version.
+    pub fn detect_version(&self, resource: &JsonValue) -> Option<FhirVersion> {
+        let profiles = resource
+            .pointer("/meta/profile")
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.as_str());
+
+        for profile in profiles {
+            let canonical = Canonical::parse(profile);
+            if canonical.url.starts_with(CORE_STRUCTURE_DEFINITION_PREFIX)
+                && let Some(version) = canonical.version.as_deref()
+            {
+                return Some(FhirVersion::from_version_str(version));
+            }
+            if let Some(version) = self.profile_version(&canonical) {
+                return Some(version);
+            }
+        }
+
+        resource
+            .get("fhirVersion")
+            .and_then(|v| v.as_str())
+            .map(FhirVersion::from_version_str)
+    }
+
+    /// FHIR version of a registered profile, if the reference is unambiguous
+    fn profile_version(&self, canonical: &Canonical) -> Option<FhirVersion> {
+        let registered = self.profile_versions.get(&canonical.url)?;
+        let matching: Vec<&FhirVersion> = registered
+            .iter()
+            .filter(|(profile_version, _)| canonical.matches_version(profile_version.as_deref()))
+            .map(|(_, version)| version)
+            .collect();
+        let candidates = if matching.is_empty() {
+            registered.values().collect()
+        } else {
+            matching
+        };
+        let (first, rest) = candidates.split_first()?;
+        rest.iter()
+            .all(|version| version == first)
+            .then(|| (*first).clone())
+    }
+
+    /// Provider for a resource, using its detected version or the default version
+    pub fn provider_for_resource(&self, resource: &JsonValue) -> Result<Arc<dyn ModelProvider>> {
+        let version = self
+            .detect_version(resource)
+            .or_else(|| self.default_version())
+            .ok_or_else(|| {
+                ModelError::invalid_configuration(
+                    "Cannot determine the FHIR version of the resource and no default is configured",
+                )
+            })?;
+        self.provider(&version)
+    }
+
+    /// Create an evaluator backed by the provider for a version
+    pub async fn create_evaluator<F>(
+        &self,
+        factory: &F,
+        version: &FhirVersion,
+    ) -> Result<Arc<dyn FhirPathEvaluator>>
+    where
+        F: FhirPathEvaluatorFactory + ?Sized,
+    {
+        factory.create_evaluator(self.provider(version)?).await
+    }
+
+    /// Create an evaluator backed by the provider for a resource's FHIR version
+    pub async fn create_evaluator_for_resource<F>(
+        &self,
+        factory: &F,
+        resource: &JsonValue,
+    ) -> Result<Arc<dyn FhirPathEvaluator>>
+    where
+        F: FhirPathEvaluatorFactory + ?Sized,
+    {
+        factory
+            .create_evaluator(self.provider_for_resource(resource)?)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_provider::PackageModelProvider;
+    use serde_json::json;
+
+    fn provider(version: FhirVersion) -> Arc<dyn ModelProvider> {
+        Arc::new(PackageModelProvider::new().with_fhir_version(version))
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_version() {
+        let mut registry =
+            ModelProviderRegistry::new().with_provider(FhirVersion::R4, provider(FhirVersion::R4));
+        let version = registry
+            .register_provider(provider(FhirVersion::R5))
+            .await
+            .unwrap();
+        assert_eq!(version, FhirVersion::R5);
+        assert_eq!(registry.versions(), vec![FhirVersion::R4, FhirVersion::R5]);
+
+        let r5 = registry.provider(&FhirVersion::R5).unwrap();
+        assert_eq!(r5.get_fhir_version().await.unwrap(), FhirVersion::R5);
+        assert!(matches!(
+            registry.provider(&FhirVersion::R6),
+            Err(ModelError::InvalidConfiguration { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_detects_version_from_resource() {
+        let mut registry = ModelProviderRegistry::new()
+            .with_provider(FhirVersion::R4, provider(FhirVersion::R4))
+            .with_provider(FhirVersion::R5, provider(FhirVersion::R5));
+        registry
+            .register_profile(
+                "http://hl7.org/fhir/us/core/StructureDefinition/us-core-patient",
+                FhirVersion::R4,
+            )
+            .unwrap();
+
+        let us_core = json!({
+            "resourceType": "Patient",
+            "meta": {"profile": ["http://hl7.org/fhir/us/core/StructureDefinition/us-core-patient|6.1.0"]}
+        });
+        assert_eq!(registry.detect_version(&us_core), Some(FhirVersion::R4));
+
+        let core_r5 = json!({
+            "resourceType": "Patient",
+            "meta": {"profile": ["http://hl7.org/fhir/StructureDefinition/Patient|5.0.0"]}
+        });
+        let provider = registry.provider_for_resource(&core_r5).unwrap();
+        assert_eq!(provider.get_fhir_version().await.unwrap(), FhirVersion::R5);
+
+        let unknown = json!({"resourceType": "Patient"});
+        assert!(registry.provider_for_resource(&unknown).is_err());
+        let registry = registry.with_default_version(FhirVersion::R4);
+        assert!(registry.provider_for_resource(&unknown).is_ok());
+    }
+
+    #[test]
+    fn test_detects_version_of_versioned_profile() {
+        const VITALS: &str = "http://example.org/StructureDefinition/vitals";
+        let mut registry = ModelProviderRegistry::new();
+        registry
+            .register_profile(&format!("{VITALS}|1.0.0"), FhirVersion::R4)
+            .unwrap();
+        registry
+            .register_profile(&format!("{VITALS}|2.0.0"), FhirVersion::R5)
+            .unwrap();
+        registry
+            .register_profile(&format!("{VITALS}|2.0.0"), FhirVersion::R5)
+            .unwrap();
+        assert!(matches!(
+            registry.register_profile(&format!("{VITALS}|1.0.0"), FhirVersion::R5),
+            Err(ModelError::InvalidConfiguration { .. })
+        ));
+
+        let detect = |registry: &ModelProviderRegistry, profile: &str| {
+            registry.detect_version(&json!({
+                "resourceType": "Observation",
+                "meta": {"profile": [profile]}
+            }))
+        };
+        assert_eq!(
+            detect(&registry, &format!("{VITALS}|1.0.0")),
+            Some(FhirVersion::R4)
+        );
+        assert_eq!(
+            detect(&registry, &format!("{VITALS}|2.0.0")),
+            Some(FhirVersion::R5)
+        );
+        assert_eq!(
+            detect(&registry, &format!("{VITALS}|2.0")),
+            Some(FhirVersion::R5)
+        );
+        // The bare URL and unregistered versions are ambiguous
+        assert_eq!(detect(&registry, VITALS), None);
+        assert_eq!(detect(&registry, &format!("{VITALS}|3.0.0")), None);
+
+        // A URL registered for one FHIR version matches any of its versions
+        registry
+            .register_profile(&format!("{VITALS}-panel|1.0.0"), FhirVersion::R4)
+            .unwrap();
+        registry
+            .register_profile(&format!("{VITALS}-panel|1.1.0"), FhirVersion::R4)
+            .unwrap();
+        assert_eq!(
+            detect(&registry, &format!("{VITALS}-panel")),
+            Some(FhirVersion::R4)
+        );
+        assert_eq!(
+            detect(&registry, &format!("{VITALS}-panel|2.0.0")),
+            Some(FhirVersion::R4)
+        );
+    }
+
+    #[test]
+    fn test_core_version_not_overridden_by_packages() {
+        let root = std::env::temp_dir().join(format!("fhir-model-registry-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let mut registry = ModelProviderRegistry::new();
+        for (name, version) in [("hl7.fhir.r4.core", "4.0.1"), ("hl7.fhir.r5.core", "5.0.0")] {
+            let dir = root.join(name).join("package");
+            std::fs::create_dir_all(&dir).unwrap();
+            let manifest = json!({"name": name, "version": version, "fhirVersions": [version]});
+            std::fs::write(dir.join("package.json"), manifest.to_string()).unwrap();
+            let patient = json!({
+                "resourceType": "StructureDefinition",
+                "url": "http://hl7.org/fhir/StructureDefinition/Patient",
+                "version": version
+            });
+            std::fs::write(
+                dir.join("StructureDefinition-Patient.json"),
+                patient.to_string(),
+            )
+            .unwrap();
+            let package = FhirPackage::from_directory(root.join(name)).unwrap();
+            assert_eq!(registry.register_package(&package).unwrap(), 0);
+        }
+        registry
+            .register_profile(
+                "http://hl7.org/fhir/StructureDefinition/Observation",
+                FhirVersion::R5,
+            )
+            .unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let core_r4 = json!({
+            "resourceType": "Observation",
+            "meta": {"profile": ["http://hl7.org/fhir/StructureDefinition/Observation|4.0.1"]}
+        });
+        assert_eq!(registry.detect_version(&core_r4), Some(FhirVersion::R4));
+        let unversioned = json!({
+            "resourceType": "Patient",
+            "meta": {"profile": ["http://hl7.org/fhir/StructureDefinition/Patient"]}
+        });
+        assert_eq!(registry.detect_version(&unversioned), None);
+    }
+
+    #[test]
+    fn test_package_fhir_version_from_dependency() {
+        let manifest = crate::package::PackageManifest::from_json(&json!({
+            "name": "hl7.fhir.us.core",
+            "version": "6.1.0",
+            "dependencies": {"hl7.terminology.r4": "5.0.0", "hl7.fhir.r4.core": "4.0.1"}
+        }))
+        .unwrap();
+        assert_eq!(manifest.fhir_version(), Some(FhirVersion::R4));
+        assert_eq!(
+            FhirVersion::from_core_package("hl7.fhir.r4b.core"),
+            Some(FhirVersion::R4B)
+        );
+    }
+}
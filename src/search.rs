@@ -0,0 +1,568 @@
+//! Search parameter definitions
+//!
+//! This module provides `SearchParameterProvider`, a sibling of `ModelProvider`
+//! that lists the SearchParameters defined for each resource type, and a static
+//! checker that verifies search expressions against a `ModelProvider`.
+//! `PackageModelProvider` implements the trait from the SearchParameter
+//! resources of the packages it loads.
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ModelError, Result};
+use crate::provider::{ModelProvider, TypeInfo};
+
+/// Type of a search parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SearchParamType {
+    /// Number search (`number`)
+    Number,
+    /// Date/time search (`date`)
+    Date,
+    /// String search (`string`)
+    String,
+    /// Token search (`token`)
+    Token,
+    /// Reference search (`reference`)
+    Reference,
+    /// Composite search (`composite`)
+    Composite,
+    /// Quantity search (`quantity`)
+    Quantity,
+    /// URI search (`uri`)
+    Uri,
+    /// Special search (`special`)
+    Special,
+}
+
+impl SearchParamType {
+    /// Parse a search parameter type code
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "number" => Some(Self::Number),
+            "date" => Some(Self::Date),
+            "string" => Some(Self::String),
+            "token" => Some(Self::Token),
+            "reference" => Some(Self::Reference),
+            "composite" => Some(Self::Composite),
+            "quantity" => Some(Self::Quantity),
+            "uri" => Some(Self::Uri),
+            "special" => Some(Self::Special),
+            _ => None,
+        }
+    }
+
+    /// Search parameter type code
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Number => "number",
+            Self::Date => "date",
+            Self::String => "string",
+            Self::Token => "token",
+            Self::Reference => "reference",
+            Self::Composite => "composite",
+            Self::Quantity => "quantity",
+            Self::Uri => "uri",
+            Self::Special => "special",
+        }
+    }
+
+    /// FHIR types a search expression of this type may select
+    ///
+    /// Types derived from these are accepted as well. Composite and special
+    /// parameters are not restricted.
+    pub fn allowed_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::Number => &["integer", "decimal", "integer64"],
+            Self::Date => &["date", "dateTime", "instant", "Period", "Timing"],
+            Self::String => &["string", "HumanName", "Address"],
+            Self::Token => &[
+                "code",
+                "Coding",
+                "CodeableConcept",
+                "Identifier",
+                "ContactPoint",
+                "boolean",
+                "string",
+                "uri",
+            ],
+            Self::Reference => &["Reference", "canonical", "uri", "Resource"],
+            Self::Quantity => &["Quantity", "Money", "Range", "SampledData"],
+            Self::Uri => &["uri"],
+            Self::Composite | Self::Special => &[],
+        }
+    }
+}
+
+impl std::fmt::Display for SearchParamType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// SearchParameter definition
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SearchParameterDefinition {
+    /// Canonical URL
+    pub url: String,
+    /// Computer friendly name
+    pub name: String,
+    /// Code used in search URLs (e.g. `birthdate`)
+    pub code: String,
+    /// Resource types the parameter applies to
+    pub base: Vec<String>,
+    /// Type of the parameter
+    pub param_type: SearchParamType,
+    /// FHIRPath expression selecting the searched values
+    pub expression: Option<String>,
+    /// Resource types a reference parameter may point to
+    pub targets: Vec<String>,
+    /// Supported modifiers (e.g. `missing`, `exact`)
+    pub modifiers: Vec<String>,
+    /// Supported comparators (e.g. `eq`, `gt`)
+    pub comparators: Vec<String>,
+}
+
+impl SearchParameterDefinition {
+    /// Parse a SearchParameter resource
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        if value.get("resourceType").and_then(|v| v.as_str()) != Some("SearchParameter") {
+            return Err(ModelError::schema_load_error(
+                "Expected a SearchParameter resource",
+            ));
+        }
+
+        let url = required_str(value, "url")?;
+        let type_code = required_str(value, "type")?;
+        let param_type = SearchParamType::from_code(&type_code).ok_or_else(|| {
+            ModelError::schema_load_error(format!(
+                "Unknown search parameter type '{type_code}' in {url}"
+            ))
+        })?;
+        let code = required_str(value, "code")?;
+
+        Ok(Self {
+            name: value
+                .get("name")
+                .and_then(|v| v.as_str())
+                .map_or_else(|| code.clone(), String::from),
+            base: string_array(value.get("base")),
+            expression: value
+                .get("expression")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            targets: string_array(value.get("target")),
+            modifiers: string_array(value.get("modifier")),
+            comparators: string_array(value.get("comparator")),
+            url,
+            code,
+            param_type,
+        })
+    }
+}
+
+/// Problem found while checking a search expression against the model
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SearchParameterIssue {
+    /// Canonical URL of the search parameter
+    pub url: String,
+    /// Code of the search parameter
+    pub code: String,
+    /// Branch of the expression the problem was found in
+    pub expression: String,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl std::fmt::Display for SearchParameterIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.code, self.expression, self.message)
+    }
+}
+
+/// Provider of the search parameters defined for resource types
+#[async_trait]
+pub trait SearchParameterProvider: Send + Sync + std::fmt::Debug {
+    /// Search parameters applicable to a resource type, including inherited ones
+    async fn get_search_parameters(
+        &self,
+        resource_type: &str,
+    ) -> Result<Vec<SearchParameterDefinition>>;
+
+    /// Search parameter of a resource type by code
+    async fn get_search_parameter(
+        &self,
+        resource_type: &str,
+        code: &str,
+    ) -> Result<Option<SearchParameterDefinition>> {
+        Ok(self
+            .get_search_parameters(resource_type)
+            .await?
+            .into_iter()
+            .find(|parameter| parameter.code == code))
+    }
+}
+
+/// Check that a search parameter's expression type-checks against a model
+///
+/// Every `|` branch must navigate existing elements from a known type, and
+/// select a type the parameter type can search on. Branches using constructs
+/// beyond paths, `as`/`ofType` casts and filtering functions such as `where`
+/// cannot be checked statically and are skipped.
+pub async fn check_search_parameter(
+    model: &dyn ModelProvider,
+    parameter: &SearchParameterDefinition,
+) -> Result<Vec<SearchParameterIssue>> {
+    let Some(expression) = parameter.expression.as_deref() else {
+        return Ok(Vec::new());
+    };
+
+    let mut issues = Vec::new();
+    for branch in split_top_level(expression, "|") {
+        let branch = strip_parens(branch.trim());
+        if let Err(message) = check_branch(model, parameter.param_type, branch).await? {
+            issues.push(SearchParameterIssue {
+                url: parameter.url.clone(),
+                code: parameter.code.clone(),
+                expression: branch.to_string(),
+                message,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+/// Check one branch of a search expression, returning the problem found if any
+async fn check_branch(
+    model: &dyn ModelProvider,
+    param_type: SearchParamType,
+    branch: &str,
+) -> Result<std::result::Result<(), String>> {
+    let (path, cast) = match split_top_level(branch, " as ").as_slice() {
+        [path] => (path.trim(), None),
+        [path, cast] => (path.trim(), Some(cast.trim())),
+        _ => return Ok(Ok(())),
+    };
+    if has_top_level_whitespace(path) {
+        // Operators and other expressions beyond simple paths
+        return Ok(Ok(()));
+    }
+
+    let segments = split_top_level(strip_parens(path), ".");
+    let Some((root, properties)) = segments.split_first() else {
+        return Ok(Ok(()));
+    };
+    let Some(mut current) = model.get_type(root).await? else {
+        return Ok(Err(format!("Unknown type '{root}'")));
+    };
+
+    for segment in properties {
+        match segment.split_once('(') {
+            Some(("ofType" | "as", argument)) => {
+                let target = argument.trim_end_matches(')').trim();
+                match cast_to(model, &current, target).await? {
+                    Some(cast) => current = cast,
+                    None => return Ok(Err(format!("'{target}' is not a possible type here"))),
+                }
+            }
+            Some(("where" | "first" | "last" | "single", _)) => {}
+            Some(("extension", _)) => match model.get_type("Extension").await? {
+                Some(extension) => current = extension,
+                None => return Ok(Ok(())),
+            },
+            // `resolve()` and other functions leave the static model
+            Some(_) => return Ok(Ok(())),
+            None => {
+                // Indexers (`entry[0]`) select items without changing the type
+                let name = segment.split_once('[').map_or(*segment, |(name, _)| name);
+                match model.get_element_type(&current, name).await? {
+                    Some(element) => current = element,
+                    None => {
+                        return Ok(Err(format!(
+                            "Unknown element '{name}' on '{}'",
+                            type_label(&current)
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(target) = cast {
+        match cast_to(model, &current, target).await? {
+            Some(cast) => current = cast,
+            None => return Ok(Err(format!("'{target}' is not a possible type here"))),
+        }
+    }
+
+    let allowed = param_type.allowed_types();
+    if allowed.is_empty() {
+        return Ok(Ok(()));
+    }
+    let candidates = match model.get_union_types(&current).await? {
+        Some(union) if model.is_union_type(&current) => union,
+        _ => vec![current.clone()],
+    };
+    let compatible = candidates.iter().any(|candidate| {
+        let name = type_label(candidate);
+        allowed
+            .iter()
+            .any(|allowed| model.is_type_derived_from(name, allowed))
+    });
+    if compatible {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(format!(
+            "{param_type} parameter selects '{}'",
+            type_label(&current)
+        )))
+    }
+}
+
+/// Narrow a type (possibly a choice) to a target type
+async fn cast_to(
+    model: &dyn ModelProvider,
+    type_info: &TypeInfo,
+    target: &str,
+) -> Result<Option<TypeInfo>> {
+    if let Some(cast) = model.of_type(type_info, target) {
+        return Ok(Some(cast));
+    }
+    // Casting down the hierarchy (e.g. `Resource` to `Patient`) is allowed too
+    let name = type_label(type_info);
+    if model.is_type_derived_from(target, name) {
+        return model.get_type(target).await;
+    }
+    Ok(None)
+}
+
+/// Model type name of a type, falling back to its FHIRPath type
+fn type_label(type_info: &TypeInfo) -> &str {
+    type_info.name.as_deref().unwrap_or(&type_info.type_name)
+}
+
+/// Split an expression on a separator outside parentheses and string literals
+fn split_top_level<'a>(expression: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut start = 0;
+    let mut chars = expression.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\'' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            }
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth = depth.saturating_sub(1),
+            _ if !in_string && depth == 0 && expression[index..].starts_with(separator) => {
+                parts.push(&expression[start..index]);
+                start = index + separator.len();
+                // Skip the rest of a multi-character separator
+                for _ in 1..separator.chars().count() {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    parts.push(&expression[start..]);
+    parts
+}
+
+/// Whether an expression contains whitespace outside parentheses and string literals
+fn has_top_level_whitespace(expression: &str) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut chars = expression.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            }
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth = depth.saturating_sub(1),
+            c if !in_string && depth == 0 && c.is_whitespace() => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Remove parentheses wrapping a whole expression
+fn strip_parens(expression: &str) -> &str {
+    let mut expression = expression.trim();
+    while let Some(inner) = expression
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        // `(a).b(c)` starts and ends with parentheses without being wrapped
+        if !is_balanced(inner) {
+            break;
+        }
+        expression = inner.trim();
+    }
+    expression
+}
+
+/// Whether parentheses outside string literals never close more than they opened
+fn is_balanced(expression: &str) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    for c in expression.chars() {
+        match c {
+            '\'' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => match depth.checked_sub(1) {
+                Some(remaining) => depth = remaining,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+fn required_str(value: &JsonValue, field: &str) -> Result<String> {
+    value
+        .get(field)
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .ok_or_else(|| {
+            ModelError::schema_load_error(format!("SearchParameter is missing '{field}'"))
+        })
+}
+
+fn string_array(value: Option<&JsonValue>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_provider::PackageModelProvider;
+    use crate::test_support::fixture_definitions;
+    use serde_json::json;
+
+    fn search_parameter(code: &str, base: &str, param_type: &str, expression: &str) -> JsonValue {
+        json!({
+            "resourceType": "SearchParameter",
+            "url": format!("http://example.org/SearchParameter/{base}-{code}"),
+            "name": code,
+            "code": code,
+            "base": [base],
+            "type": param_type,
+            "expression": expression,
+            "modifier": ["missing"],
+            "comparator": ["eq", "lt"]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_search_parameters_type_check() {
+        let mut provider =
+            PackageModelProvider::from_structure_definitions(&fixture_definitions()).unwrap();
+        for parameter in [
+            search_parameter("name", "Patient", "string", "Patient.name"),
+            search_parameter("family", "Patient", "string", "Patient.name.family"),
+            search_parameter(
+                "deceased",
+                "Patient",
+                "date",
+                "(Patient.deceased as dateTime) | Patient.deceased.ofType(boolean).where(true)",
+            ),
+            search_parameter("nickname", "Patient", "string", "Patient.nickname"),
+            search_parameter("name-count", "Patient", "number", "Patient.name"),
+            search_parameter(
+                "deceased-amount",
+                "Patient",
+                "quantity",
+                "Patient.deceased as Quantity",
+            ),
+            search_parameter(
+                "content",
+                "Resource",
+                "special",
+                "Resource.exists() and true",
+            ),
+            search_parameter(
+                "official",
+                "Patient",
+                "string",
+                "Patient.name.where(family = 'Smith').given | Patient.name.where(given = 'a b').nickname",
+            ),
+            search_parameter(
+                "first-family",
+                "Patient",
+                "string",
+                "Patient.name[0].family",
+            ),
+        ] {
+            provider.add_search_parameter(&parameter).unwrap();
+        }
+        assert_eq!(provider.search_parameter_count(), 9);
+
+        let parameters = provider.get_search_parameters("Patient").await.unwrap();
+        assert_eq!(parameters.len(), 9);
+        assert!(
+            provider
+                .get_search_parameters("HumanName")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let name = provider
+            .get_search_parameter("Patient", "name")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(name.param_type, SearchParamType::String);
+        assert_eq!(name.modifiers, vec!["missing"]);
+        assert_eq!(name.comparators, vec!["eq", "lt"]);
+
+        let issues = provider.validate_search_parameters().await.unwrap();
+        let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "deceased (Patient.deceased.ofType(boolean).where(true)): date parameter selects 'boolean'",
+                "nickname (Patient.nickname): Unknown element 'nickname' on 'Patient'",
+                "name-count (Patient.name): number parameter selects 'HumanName'",
+                "deceased-amount (Patient.deceased as Quantity): 'Quantity' is not a possible type here",
+                "official (Patient.name.where(given = 'a b').nickname): Unknown element 'nickname' on 'HumanName'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expression_splitting() {
+        assert_eq!(
+            split_top_level("a.where(b | c) | d.e('x|y')", "|"),
+            vec!["a.where(b | c) ", " d.e('x|y')"]
+        );
+        assert_eq!(
+            strip_parens("((Observation.value as Quantity))"),
+            "Observation.value as Quantity"
+        );
+        assert_eq!(strip_parens("(a).b(c)"), "(a).b(c)");
+        assert!(!has_top_level_whitespace("a.where(b = 'c d').e"));
+        assert!(has_top_level_whitespace("a.exists() and b"));
+    }
+}
@@ -0,0 +1,716 @@
+//! ModelProvider backed by compile-time generated tables
+//!
+//! This module provides `StaticModelProvider`, which answers type queries from a
+//! `StaticModel`: a set of perfect-hash tables generated from StructureDefinitions
+//! ahead of time (see the `codegen` feature). Nothing is parsed or allocated at
+//! startup, so the provider can replace `EmptyModelProvider` at zero cost.
+
+use async_trait::async_trait;
+
+use crate::error::Result;
+use crate::interner::Symbol;
+pub use crate::provider::BindingStrength;
+use crate::provider::{
+    Cardinality, ChoiceTypeInfo, ElementBinding, ElementInfo, FhirVersion, ModelProvider,
+    SyncModelProvider, TypeInfo, type_constants,
+};
+pub use crate::structure_definition::StructureDefinitionKind;
+use crate::structure_definition::{SYSTEM_TYPE_PREFIX, choice_suffix};
+
+/// FHIRPath System types that can be looked up by name
+const SYSTEM_TYPES: &[&str] = &[
+    "Boolean", "String", "Integer", "Long", "Decimal", "Date", "DateTime", "Time", "Quantity",
+];
+
+/// Read-only map with a precomputed perfect hash (CHD) over string keys
+#[derive(Debug)]
+pub struct StaticMap<V: 'static> {
+    /// Hash seed the displacements were computed for
+    pub seed: u64,
+    /// Displacement pair per bucket
+    pub displacements: &'static [(u32, u32)],
+    /// Entries in slot order
+    pub entries: &'static [(&'static str, V)],
+}
+
+impl<V> StaticMap<V> {
+    /// Look up a key
+    pub fn get(&self, key: &str) -> Option<&V> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let hashes = perfect_hash(key, self.seed);
+        let (d1, d2) = self.displacements[(hashes.0 % self.displacements.len() as u32) as usize];
+        let (entry_key, value) = &self.entries[slot(hashes, d1, d2, self.entries.len())];
+        (*entry_key == key).then_some(value)
+    }
+
+    /// Whether a key is present
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all entries in slot order
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &V)> {
+        self.entries.iter().map(|(key, value)| (*key, value))
+    }
+}
+
+/// Type definition in a static model
+#[derive(Debug)]
+pub struct StaticTypeDef {
+    /// Kind of the type
+    pub kind: StructureDefinitionKind,
+    /// Whether the type is abstract
+    pub is_abstract: bool,
+    /// Ancestor types, nearest first (e.g. `DomainResource`, `Resource` for `Patient`)
+    pub ancestors: &'static [&'static str],
+    /// System type backing a primitive
+    pub system_type: Option<&'static str>,
+    /// Namespace the type is reported under (`None` for `FHIR`)
+    pub namespace: Option<&'static str>,
+}
+
+/// Element definition in a static model
+#[derive(Debug)]
+pub struct StaticElementDef {
+    /// Element name without `[x]`
+    pub name: &'static str,
+    /// Element path (e.g. `Patient.deceased[x]`)
+    pub path: &'static str,
+    /// Allowed type codes
+    pub types: &'static [&'static str],
+    /// Minimum cardinality
+    pub min: u32,
+    /// Maximum cardinality (`None` for unbounded)
+    pub max: Option<u32>,
+    /// Whether the element is a modifier
+    pub is_modifier: bool,
+    /// Whether the element is part of the summary
+    pub is_summary: bool,
+    /// Whether implementations must support the element
+    pub must_support: bool,
+    /// Binding strength for coded elements
+    pub binding_strength: Option<BindingStrength>,
+    /// Canonical URL of the bound ValueSet
+    pub value_set: Option<&'static str>,
+    /// Keys of the constraints defined on the element
+    pub constraints: &'static [&'static str],
+    /// Short description
+    pub documentation: Option<&'static str>,
+    /// Path of the element whose definition this element reuses
+    pub content_reference: Option<&'static str>,
+    /// Fixed value (`fixed[x]`) as JSON text
+    pub fixed_value: Option<&'static str>,
+    /// Pattern value (`pattern[x]`) as JSON text
+    pub pattern_value: Option<&'static str>,
+}
+
+impl StaticElementDef {
+    fn is_choice(&self) -> bool {
+        self.path.ends_with("[x]")
+    }
+
+    fn cardinality(&self) -> Cardinality {
+        Cardinality {
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+/// Typed name of a choice element (e.g. `Observation.valueQuantity`)
+#[derive(Debug)]
+pub struct StaticChoiceDef {
+    /// Key of the choice element in `StaticModel::elements` (e.g. `Observation.value`)
+    pub element: &'static str,
+    /// Type code selected by the typed name (e.g. `Quantity`)
+    pub type_code: &'static str,
+}
+
+/// Complete static model of a FHIR release
+#[derive(Debug)]
+pub struct StaticModel {
+    /// FHIR version the model was generated from (e.g. `R4` or `4.0.1`)
+    pub fhir_version: &'static str,
+    /// Type name -> type definition
+    pub types: StaticMap<StaticTypeDef>,
+    /// `Parent.name` -> element definition
+    pub elements: StaticMap<StaticElementDef>,
+    /// Parent path -> element names in definition order
+    pub children: StaticMap<&'static [&'static str]>,
+    /// Typed choice name (`Observation.valueQuantity`) -> choice element and type
+    pub choice_elements: StaticMap<StaticChoiceDef>,
+    /// Concrete resource types, sorted
+    pub resource_types: &'static [&'static str],
+    /// Complex types, sorted
+    pub complex_types: &'static [&'static str],
+    /// Primitive types, sorted
+    pub primitive_types: &'static [&'static str],
+    /// Logical models, sorted
+    pub logical_types: &'static [&'static str],
+    /// Namespaces other than `FHIR` and `System` that hold types, sorted
+    pub namespaces: &'static [&'static str],
+}
+
+/// ModelProvider implementation backed by a generated `StaticModel`
+///
+/// # Example
+///
+/// ```rust,ignore
+/// // build.rs generated `FHIR_R4` with `StaticModelGenerator`
+/// include!(concat!(env!("OUT_DIR"), "/fhir_r4.rs"));
+///
+/// let provider = StaticModelProvider::new(&FHIR_R4);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StaticModelProvider {
+    model: &'static StaticModel,
+}
+
+impl StaticModelProvider {
+    /// Create a provider over a static model
+    pub const fn new(model: &'static StaticModel) -> Self {
+        Self { model }
+    }
+
+    /// The underlying static model
+    pub fn model(&self) -> &'static StaticModel {
+        self.model
+    }
+
+    fn type_info_for_code(&self, code: &str, singleton: bool) -> TypeInfo {
+        if let Some(system) = code.strip_prefix(SYSTEM_TYPE_PREFIX) {
+            return TypeInfo::system_type(system, singleton);
+        }
+
+        let type_def = self.model.types.get(code);
+        let type_name = type_def
+            .and_then(|t| t.system_type)
+            .or_else(|| type_constants::to_system_type(code))
+            .unwrap_or("Any");
+
+        TypeInfo {
+            type_name: Symbol::intern(type_name),
+            singleton: Some(singleton),
+            is_empty: Some(false),
+            namespace: Some(Symbol::intern(
+                type_def.and_then(|t| t.namespace).unwrap_or("FHIR"),
+            )),
+            name: Some(Symbol::intern(code)),
+        }
+    }
+
+    /// Namespace of the type an element path is rooted at
+    fn namespace_of(&self, path: &str) -> &'static str {
+        let root = path.split('.').next().unwrap_or(path);
+        self.model
+            .types
+            .get(root)
+            .and_then(|t| t.namespace)
+            .unwrap_or("FHIR")
+    }
+
+    /// Split an optionally namespace-qualified type name (`FHIR.Patient`, `Acme.Model`)
+    fn split_qualified<'a>(&self, type_name: &'a str) -> (Option<&'a str>, &'a str) {
+        match type_name.split_once('.') {
+            Some((namespace @ ("FHIR" | "System"), name)) => (Some(namespace), name),
+            Some((namespace, name)) if self.model.namespaces.contains(&namespace) => {
+                (Some(namespace), name)
+            }
+            _ => (None, type_name),
+        }
+    }
+
+    fn backbone_type_info(&self, path: &str, singleton: bool) -> TypeInfo {
+        TypeInfo {
+            namespace: Some(Symbol::intern(self.namespace_of(path))),
+            ..TypeInfo::backbone_element(path, singleton)
+        }
+    }
+
+    /// Resolve a property on a parent path, including typed choice names (`valueQuantity`)
+    fn find_element(
+        &self,
+        parent: &str,
+        property: &str,
+    ) -> Option<(&'static StaticElementDef, Option<&'static str>)> {
+        let key = format!("{parent}.{property}");
+        if let Some(element) = self.model.elements.get(&key) {
+            return Some((element, None));
+        }
+        let choice = self.model.choice_elements.get(&key)?;
+        let element = self.model.elements.get(choice.element)?;
+        Some((element, Some(choice.type_code)))
+    }
+
+    /// Synthetic type name of a backbone element: its own path, or the path its
+    /// `contentReference` points to
+    fn backbone_path(&self, element: &'static StaticElementDef) -> Option<&'static str> {
+        if let Some(target) = element.content_reference {
+            return self.model.children.contains_key(target).then_some(target);
+        }
+        (!element.is_choice() && self.model.children.contains_key(element.path))
+            .then_some(element.path)
+    }
+
+    fn choice_type_info(&self, element: &StaticElementDef) -> TypeInfo {
+        TypeInfo {
+            type_name: Symbol::intern("Choice"),
+            singleton: Some(!element.cardinality().is_collection()),
+            is_empty: Some(false),
+            namespace: Some(Symbol::intern(self.namespace_of(element.path))),
+            name: Some(Symbol::intern(element.path)),
+        }
+    }
+
+    fn element_info(element: &StaticElementDef) -> ElementInfo {
+        ElementInfo {
+            name: element.name.to_string(),
+            path: element.path.to_string(),
+            element_type: element.types.join(" | "),
+            documentation: element.documentation.map(String::from),
+            cardinality: element.cardinality(),
+            is_modifier: element.is_modifier,
+            is_summary: element.is_summary,
+            must_support: element.must_support,
+            binding: element.binding_strength.map(|strength| ElementBinding {
+                strength,
+                value_set: element.value_set.map(String::from),
+            }),
+            fixed_value: element
+                .fixed_value
+                .and_then(|v| serde_json::from_str(v).ok()),
+            pattern_value: element
+                .pattern_value
+                .and_then(|v| serde_json::from_str(v).ok()),
+            constraints: element.constraints.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl ModelProvider for StaticModelProvider {
+    async fn get_type(&self, type_name: &str) -> Result<Option<TypeInfo>> {
+        Ok(self.get_type_sync(type_name))
+    }
+
+    async fn get_element_type(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Result<Option<TypeInfo>> {
+        Ok(self.get_element_type_sync(parent_type, property_name))
+    }
+
+    fn of_type(&self, type_info: &TypeInfo, target_type: &str) -> Option<TypeInfo> {
+        let (namespace, target) = self.split_qualified(target_type);
+        // Types in registered namespaces only match when qualified with their own namespace
+        if let Some(namespace) = namespace
+            && self.model.namespaces.contains(&namespace)
+            && type_info
+                .namespace
+                .as_ref()
+                .is_none_or(|ns| ns != namespace)
+        {
+            return None;
+        }
+
+        if type_info.type_name == "Choice" {
+            let path = type_info.name.as_deref()?;
+            let element = self.model.elements.get(path.trim_end_matches("[x]"))?;
+            let singleton = type_info.singleton.unwrap_or(true);
+            return element
+                .types
+                .iter()
+                .find(|code| **code == target || self.is_type_derived_from(code, target))
+                .map(|code| self.type_info_for_code(code, singleton));
+        }
+
+        if type_info.type_name == target
+            || type_info
+                .name
+                .as_deref()
+                .is_some_and(|name| self.is_type_derived_from(name, target))
+            || self.is_type_derived_from(&type_info.type_name, target)
+        {
+            return Some(type_info.clone());
+        }
+
+        None
+    }
+
+    fn get_element_names(&self, parent_type: &TypeInfo) -> Vec<String> {
+        let parent = parent_type
+            .name
+            .as_deref()
+            .unwrap_or(&parent_type.type_name);
+        self.model
+            .children
+            .get(parent)
+            .map(|names| names.iter().map(|name| name.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    async fn get_children_type(&self, parent_type: &TypeInfo) -> Result<Option<TypeInfo>> {
+        Ok(self.get_children_type_sync(parent_type))
+    }
+
+    async fn get_elements(&self, type_name: &str) -> Result<Vec<ElementInfo>> {
+        let Some(names) = self.model.children.get(type_name) else {
+            return Ok(Vec::new());
+        };
+        Ok(names
+            .iter()
+            .filter_map(|name| self.model.elements.get(&format!("{type_name}.{name}")))
+            .map(Self::element_info)
+            .collect())
+    }
+
+    async fn get_resource_types(&self) -> Result<Vec<String>> {
+        Ok(self
+            .model
+            .resource_types
+            .iter()
+            .map(|t| t.to_string())
+            .collect())
+    }
+
+    async fn get_complex_types(&self) -> Result<Vec<String>> {
+        Ok(self
+            .model
+            .complex_types
+            .iter()
+            .map(|t| t.to_string())
+            .collect())
+    }
+
+    async fn get_primitive_types(&self) -> Result<Vec<String>> {
+        Ok(self
+            .model
+            .primitive_types
+            .iter()
+            .map(|t| t.to_string())
+            .collect())
+    }
+
+    async fn get_logical_types(&self) -> Result<Vec<String>> {
+        Ok(self
+            .model
+            .logical_types
+            .iter()
+            .map(|t| t.to_string())
+            .collect())
+    }
+
+    async fn resource_type_exists(&self, resource_type: &str) -> Result<bool> {
+        Ok(self
+            .model
+            .types
+            .get(resource_type)
+            .is_some_and(|t| t.kind == StructureDefinitionKind::Resource && !t.is_abstract))
+    }
+
+    async fn get_fhir_version(&self) -> Result<FhirVersion> {
+        Ok(FhirVersion::from_version_str(self.model.fhir_version))
+    }
+
+    fn is_type_derived_from(&self, derived_type: &str, base_type: &str) -> bool {
+        if derived_type == base_type {
+            return true;
+        }
+        if let Some(type_def) = self.model.types.get(derived_type) {
+            return type_def.ancestors.contains(&base_type);
+        }
+        // Backbone elements derive from their declared type (`BackboneElement`)
+        self.model
+            .elements
+            .get(derived_type)
+            .and_then(|element| element.types.first())
+            .is_some_and(|code| self.is_type_derived_from(code, base_type))
+    }
+
+    fn get_type_ancestors(&self, type_name: &str) -> Vec<String> {
+        if let Some(type_def) = self.model.types.get(type_name) {
+            return type_def.ancestors.iter().map(|a| a.to_string()).collect();
+        }
+        // Backbone elements derive from their declared type (`BackboneElement`)
+        let Some(code) = self
+            .model
+            .elements
+            .get(type_name)
+            .and_then(|element| element.types.first())
+        else {
+            return Vec::new();
+        };
+        let mut ancestors = vec![code.to_string()];
+        ancestors.extend(self.get_type_ancestors(code));
+        ancestors
+    }
+
+    fn get_direct_subtypes(&self, type_name: &str) -> Vec<String> {
+        let mut subtypes: Vec<String> = self
+            .model
+            .types
+            .iter()
+            .filter(|(_, type_def)| type_def.ancestors.first() == Some(&type_name))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        subtypes.sort();
+        subtypes
+    }
+
+    fn get_concrete_subtypes(&self, type_name: &str) -> Vec<String> {
+        let mut subtypes: Vec<String> = self
+            .model
+            .types
+            .iter()
+            .filter(|(_, type_def)| {
+                !type_def.is_abstract && type_def.ancestors.contains(&type_name)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect();
+        subtypes.sort();
+        subtypes
+    }
+
+    async fn get_choice_types(
+        &self,
+        parent_type: &str,
+        property_name: &str,
+    ) -> Result<Option<Vec<ChoiceTypeInfo>>> {
+        Ok(self
+            .model
+            .elements
+            .get(&format!("{parent_type}.{property_name}"))
+            .filter(|element| element.is_choice())
+            .map(|element| {
+                element
+                    .types
+                    .iter()
+                    .map(|code| ChoiceTypeInfo {
+                        suffix: choice_suffix(code),
+                        type_name: code.to_string(),
+                    })
+                    .collect()
+            }))
+    }
+
+    async fn get_union_types(&self, type_info: &TypeInfo) -> Result<Option<Vec<TypeInfo>>> {
+        if type_info.type_name != "Choice" {
+            return Ok(None);
+        }
+        let Some(path) = type_info.name.as_deref() else {
+            return Ok(None);
+        };
+        let singleton = type_info.singleton.unwrap_or(true);
+
+        Ok(self
+            .model
+            .elements
+            .get(path.trim_end_matches("[x]"))
+            .map(|element| {
+                element
+                    .types
+                    .iter()
+                    .map(|code| self.type_info_for_code(code, singleton))
+                    .collect()
+            }))
+    }
+
+    fn is_union_type(&self, type_info: &TypeInfo) -> bool {
+        type_info.type_name == "Choice"
+    }
+
+    fn as_sync(&self) -> Option<&dyn SyncModelProvider> {
+        Some(self)
+    }
+}
+
+impl SyncModelProvider for StaticModelProvider {
+    fn get_type_sync(&self, type_name: &str) -> Option<TypeInfo> {
+        let (namespace, name) = self.split_qualified(type_name);
+        if let Some(type_def) = self.model.types.get(name)
+            && namespace.is_none_or(|namespace| type_def.namespace.unwrap_or("FHIR") == namespace)
+        {
+            return Some(self.type_info_for_code(name, true));
+        }
+        if name.contains('.')
+            && self.model.children.contains_key(name)
+            && namespace.is_none_or(|namespace| self.namespace_of(name) == namespace)
+        {
+            return Some(self.backbone_type_info(name, true));
+        }
+        if namespace != Some("FHIR") && SYSTEM_TYPES.contains(&name) {
+            return Some(TypeInfo::system_type(name.to_string(), true));
+        }
+        None
+    }
+
+    fn get_element_type_sync(
+        &self,
+        parent_type: &TypeInfo,
+        property_name: &str,
+    ) -> Option<TypeInfo> {
+        let parent = parent_type
+            .name
+            .as_deref()
+            .unwrap_or(&parent_type.type_name);
+
+        let (element, choice_code) = self.find_element(parent, property_name)?;
+        let singleton = !element.cardinality().is_collection();
+
+        if let Some(code) = choice_code {
+            return Some(self.type_info_for_code(code, singleton));
+        }
+        if element.is_choice() {
+            return Some(self.choice_type_info(element));
+        }
+        if let Some(backbone) = self.backbone_path(element) {
+            return Some(self.backbone_type_info(backbone, singleton));
+        }
+
+        element
+            .types
+            .first()
+            .map(|code| self.type_info_for_code(code, singleton))
+    }
+}
+
+/// Seeded hash of a key split into the bucket hash and two slot hashes
+pub(crate) fn perfect_hash(key: &str, seed: u64) -> (u32, u32, u32) {
+    // FNV-1a followed by splitmix64 finalisation for better bit dispersion
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for byte in key.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    let first = splitmix64(hash);
+    let second = splitmix64(first);
+    ((first >> 32) as u32, first as u32, second as u32)
+}
+
+/// Slot of a key for a bucket's displacement pair
+pub(crate) fn slot(hashes: (u32, u32, u32), d1: u32, d2: u32, len: usize) -> usize {
+    let (_, f1, f2) = hashes;
+    (d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2) as usize) % len
+}
+
+fn splitmix64(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Single-entry maps need no generator: every key hashes to slot 0
+    static TEST_MODEL: StaticModel = StaticModel {
+        fhir_version: "4.0.1",
+        types: StaticMap {
+            seed: 0,
+            displacements: &[(0, 0)],
+            entries: &[(
+                "Patient",
+                StaticTypeDef {
+                    kind: StructureDefinitionKind::Resource,
+                    is_abstract: false,
+                    ancestors: &["DomainResource", "Resource"],
+                    system_type: None,
+                    namespace: None,
+                },
+            )],
+        },
+        elements: StaticMap {
+            seed: 0,
+            displacements: &[(0, 0)],
+            entries: &[(
+                "Patient.deceased",
+                StaticElementDef {
+                    name: "deceased",
+                    path: "Patient.deceased[x]",
+                    types: &["boolean", "dateTime"],
+                    min: 0,
+                    max: Some(1),
+                    is_modifier: true,
+                    is_summary: true,
+                    must_support: false,
+                    binding_strength: None,
+                    value_set: None,
+                    constraints: &["ele-1"],
+                    documentation: Some("Indicates if the individual is deceased or not"),
+                    content_reference: None,
+                    fixed_value: None,
+                    pattern_value: None,
+                },
+            )],
+        },
+        children: StaticMap {
+            seed: 0,
+            displacements: &[(0, 0)],
+            entries: &[("Patient", &["deceased"])],
+        },
+        choice_elements: StaticMap {
+            seed: 0,
+            displacements: &[(0, 0)],
+            entries: &[(
+                "Patient.deceasedBoolean",
+                StaticChoiceDef {
+                    element: "Patient.deceased",
+                    type_code: "boolean",
+                },
+            )],
+        },
+        resource_types: &["Patient"],
+        complex_types: &[],
+        primitive_types: &[],
+        logical_types: &[],
+        namespaces: &[],
+    };
+
+    #[tokio::test]
+    async fn test_static_provider_navigation() {
+        let provider = StaticModelProvider::new(&TEST_MODEL);
+
+        let patient = provider.get_type("FHIR.Patient").await.unwrap().unwrap();
+        assert_eq!(patient.name.as_deref(), Some("Patient"));
+        assert!(provider.get_type("Observation").await.unwrap().is_none());
+        assert!(provider.is_type_derived_from("Patient", "Resource"));
+        assert_eq!(provider.get_fhir_version().await.unwrap(), FhirVersion::R4);
+
+        let deceased = provider
+            .get_element_type(&patient, "deceased")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(provider.is_union_type(&deceased));
+        let as_date_time = provider.of_type(&deceased, "dateTime").unwrap();
+        assert_eq!(as_date_time.name.as_deref(), Some("dateTime"));
+
+        let typed = provider
+            .get_element_type(&patient, "deceasedBoolean")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(typed.name.as_deref(), Some("boolean"));
+
+        let elements = provider.get_elements("Patient").await.unwrap();
+        assert_eq!(elements[0].path, "Patient.deceased[x]");
+        assert_eq!(elements[0].element_type, "boolean | dateTime");
+        assert!(elements[0].is_modifier);
+        assert!(provider.resource_type_exists("Patient").await.unwrap());
+    }
+}
@@ -0,0 +1,582 @@
+//! StructureDefinition model used by package-backed providers
+//!
+//! This module provides a lightweight, read-only view over FHIR
+//! StructureDefinition resources. Only the parts needed for FHIRPath type
+//! navigation are parsed; everything else in the JSON is ignored.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::error::{ModelError, Result};
+use crate::provider::{BindingStrength, Cardinality, ElementBinding, ElementInfo};
+
+/// Prefix used by FHIR snapshots for FHIRPath System types (e.g. `Element.id`)
+pub const SYSTEM_TYPE_PREFIX: &str = "http://hl7.org/fhirpath/System.";
+
+/// Kind of a StructureDefinition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StructureDefinitionKind {
+    /// A primitive data type (`primitive-type`)
+    PrimitiveType,
+    /// A complex data type (`complex-type`)
+    ComplexType,
+    /// A resource (`resource`)
+    Resource,
+    /// A logical model (`logical`)
+    Logical,
+}
+
+impl StructureDefinitionKind {
+    /// Parse the `kind` code of a StructureDefinition
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "primitive-type" => Some(Self::PrimitiveType),
+            "complex-type" => Some(Self::ComplexType),
+            "resource" => Some(Self::Resource),
+            "logical" => Some(Self::Logical),
+            _ => None,
+        }
+    }
+}
+
+/// How a StructureDefinition relates to its base definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeDerivation {
+    /// Defines a new type (`specialization`)
+    Specialization,
+    /// Constrains an existing type, i.e. a profile (`constraint`)
+    Constraint,
+}
+
+/// Parsed FHIR StructureDefinition
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureDefinition {
+    /// Canonical URL
+    pub url: String,
+    /// Business version
+    pub version: Option<String>,
+    /// Computer friendly name
+    pub name: String,
+    /// Type defined or constrained by this structure
+    pub type_name: String,
+    /// Kind of structure
+    pub kind: StructureDefinitionKind,
+    /// Whether the type is abstract
+    pub is_abstract: bool,
+    /// Derivation from the base definition, if any
+    pub derivation: Option<TypeDerivation>,
+    /// Canonical URL of the base definition
+    pub base_definition: Option<String>,
+    /// FHIR version the definition was written for (e.g. "4.0.1")
+    pub fhir_version: Option<String>,
+    /// Where an extension defined by this structure may be used
+    pub contexts: Vec<ExtensionContext>,
+    /// Snapshot elements in document order
+    pub snapshot: Vec<ElementDefinition>,
+    /// Differential elements in document order
+    pub differential: Vec<ElementDefinition>,
+}
+
+impl StructureDefinition {
+    /// Parse a StructureDefinition from its JSON representation
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        if value.get("resourceType").and_then(|v| v.as_str()) != Some("StructureDefinition") {
+            return Err(ModelError::schema_load_error(
+                "Expected a StructureDefinition resource",
+            ));
+        }
+
+        let url = required_str(value, "url")?;
+        let kind_code = required_str(value, "kind")?;
+        let kind = StructureDefinitionKind::from_code(&kind_code).ok_or_else(|| {
+            ModelError::schema_load_error(format!(
+                "Unknown StructureDefinition kind '{kind_code}' in {url}"
+            ))
+        })?;
+        let type_name = required_str(value, "type")?;
+
+        let derivation = match value.get("derivation").and_then(|v| v.as_str()) {
+            Some("specialization") => Some(TypeDerivation::Specialization),
+            Some("constraint") => Some(TypeDerivation::Constraint),
+            _ => None,
+        };
+
+        Ok(Self {
+            name: optional_str(value, "name").unwrap_or_else(|| type_name.clone()),
+            version: optional_str(value, "version"),
+            is_abstract: value
+                .get("abstract")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            base_definition: optional_str(value, "baseDefinition"),
+            fhir_version: optional_str(value, "fhirVersion"),
+            contexts: value
+                .get("context")
+                .and_then(|v| v.as_array())
+                .map(|contexts| {
+                    contexts
+                        .iter()
+                        .filter_map(ExtensionContext::from_json)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            snapshot: parse_elements(value.get("snapshot"))?,
+            differential: parse_elements(value.get("differential"))?,
+            url,
+            kind,
+            type_name,
+            derivation,
+        })
+    }
+
+    /// Whether this definition is a profile on another type
+    pub fn is_profile(&self) -> bool {
+        self.derivation == Some(TypeDerivation::Constraint)
+    }
+
+    /// Canonical URL with the version appended (`url|version`) when known
+    pub fn versioned_url(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{}|{version}", self.url),
+            None => self.url.clone(),
+        }
+    }
+}
+
+/// Parsed ElementDefinition from a snapshot or differential
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementDefinition {
+    /// Element id (e.g. `Patient.identifier:mrn.system`)
+    pub id: Option<String>,
+    /// Element path (e.g. `Patient.identifier.system`)
+    pub path: String,
+    /// Slice name if this element defines a slice
+    pub slice_name: Option<String>,
+    /// Minimum cardinality
+    pub min: Option<u32>,
+    /// Maximum cardinality (`"*"` for unbounded)
+    pub max: Option<String>,
+    /// Allowed types
+    pub types: Vec<ElementType>,
+    /// Reference to another element's definition (e.g. `#Questionnaire.item`)
+    pub content_reference: Option<String>,
+    /// Slicing rules if this element is sliced
+    pub slicing: Option<ElementSlicing>,
+    /// Fixed value (`fixed[x]`)
+    pub fixed: Option<JsonValue>,
+    /// Pattern value (`pattern[x]`)
+    pub pattern: Option<JsonValue>,
+    /// Whether the element is a modifier (`isModifier`)
+    pub is_modifier: Option<bool>,
+    /// Whether the element is part of the summary (`isSummary`)
+    pub is_summary: Option<bool>,
+    /// Whether the element must be supported (`mustSupport`)
+    pub must_support: Option<bool>,
+    /// Terminology binding
+    pub binding: Option<ElementBinding>,
+    /// Keys of the constraints defined on the element
+    pub constraint_keys: Vec<String>,
+    /// Short description
+    pub short: Option<String>,
+    /// Full definition
+    pub definition: Option<String>,
+}
+
+impl ElementDefinition {
+    /// Parse an ElementDefinition from its JSON representation
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        let path = required_str(value, "path")?;
+        let types = value
+            .get("type")
+            .and_then(|v| v.as_array())
+            .map(|types| types.iter().filter_map(ElementType::from_json).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            id: optional_str(value, "id"),
+            slice_name: optional_str(value, "sliceName"),
+            min: value
+                .get("min")
+                .and_then(|v| v.as_u64())
+                .map(|min| min as u32),
+            max: optional_str(value, "max"),
+            content_reference: optional_str(value, "contentReference"),
+            slicing: value.get("slicing").map(ElementSlicing::from_json),
+            fixed: prefixed_value(value, "fixed"),
+            pattern: prefixed_value(value, "pattern"),
+            is_modifier: value.get("isModifier").and_then(|v| v.as_bool()),
+            is_summary: value.get("isSummary").and_then(|v| v.as_bool()),
+            must_support: value.get("mustSupport").and_then(|v| v.as_bool()),
+            binding: value.get("binding").and_then(parse_binding),
+            constraint_keys: value
+                .get("constraint")
+                .and_then(|v| v.as_array())
+                .map(|constraints| {
+                    constraints
+                        .iter()
+                        .filter_map(|c| c.get("key")?.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            short: optional_str(value, "short"),
+            definition: optional_str(value, "definition"),
+            path,
+            types,
+        })
+    }
+
+    /// Last path segment without the `[x]` suffix (e.g. `value` for `Observation.value[x]`)
+    pub fn name(&self) -> &str {
+        let last = self.path.rsplit('.').next().unwrap_or(&self.path);
+        last.strip_suffix("[x]").unwrap_or(last)
+    }
+
+    /// Path of the parent element, or `None` for the root element
+    pub fn parent_path(&self) -> Option<&str> {
+        self.path.rsplit_once('.').map(|(parent, _)| parent)
+    }
+
+    /// Whether this is a choice element (`[x]`)
+    pub fn is_choice(&self) -> bool {
+        self.path.ends_with("[x]")
+    }
+
+    /// Whether the element may repeat (max is `*` or greater than one)
+    pub fn is_collection(&self) -> bool {
+        match self.max.as_deref() {
+            Some("*") => true,
+            Some(max) => max.parse::<u32>().is_ok_and(|max| max > 1),
+            None => false,
+        }
+    }
+
+    /// Type codes allowed for this element
+    pub fn type_codes(&self) -> impl Iterator<Item = &str> {
+        self.types.iter().map(|t| t.code.as_str())
+    }
+
+    /// Path of the element whose definition this element reuses
+    ///
+    /// Both `#Questionnaire.item` and the canonical form
+    /// `http://hl7.org/fhir/StructureDefinition/Questionnaire#Questionnaire.item`
+    /// yield `Questionnaire.item`.
+    pub fn content_reference_path(&self) -> Option<&str> {
+        let reference = self.content_reference.as_deref()?;
+        Some(
+            reference
+                .rsplit_once('#')
+                .map_or(reference, |(_, path)| path),
+        )
+    }
+
+    /// Cardinality of the element (`0..*` when not specified)
+    pub fn cardinality(&self) -> Cardinality {
+        Cardinality::from_bounds(self.min.unwrap_or(0), self.max.as_deref().unwrap_or("*"))
+    }
+
+    /// Fill in everything this (differential) element leaves unspecified from its base
+    pub(crate) fn inherit_from(&mut self, base: &ElementDefinition) {
+        self.min = self.min.or(base.min);
+        if self.max.is_none() {
+            self.max.clone_from(&base.max);
+        }
+        if self.types.is_empty() {
+            self.types.clone_from(&base.types);
+        }
+        for (own, inherited) in [
+            (&mut self.content_reference, &base.content_reference),
+            (&mut self.short, &base.short),
+            (&mut self.definition, &base.definition),
+        ] {
+            if own.is_none() {
+                own.clone_from(inherited);
+            }
+        }
+        if self.slicing.is_none() {
+            self.slicing.clone_from(&base.slicing);
+        }
+        if self.fixed.is_none() {
+            self.fixed.clone_from(&base.fixed);
+        }
+        if self.pattern.is_none() {
+            self.pattern.clone_from(&base.pattern);
+        }
+        for (own, inherited) in [
+            (&mut self.is_modifier, base.is_modifier),
+            (&mut self.is_summary, base.is_summary),
+            (&mut self.must_support, base.must_support),
+        ] {
+            *own = own.or(inherited);
+        }
+        if self.binding.is_none() {
+            self.binding.clone_from(&base.binding);
+        }
+        // Constraints are additive: a profile can only add to the base constraints
+        let own_keys = std::mem::take(&mut self.constraint_keys);
+        self.constraint_keys = base.constraint_keys.clone();
+        for key in own_keys {
+            if !self.constraint_keys.contains(&key) {
+                self.constraint_keys.push(key);
+            }
+        }
+    }
+
+    /// Element information as reported by `ModelProvider::get_elements`
+    pub fn to_element_info(&self) -> ElementInfo {
+        ElementInfo {
+            name: self.name().to_string(),
+            path: self.path.clone(),
+            element_type: match self.content_reference_path() {
+                Some(path) if self.types.is_empty() => path.to_string(),
+                _ => self.type_codes().collect::<Vec<_>>().join(" | "),
+            },
+            documentation: self.short.clone(),
+            cardinality: self.cardinality(),
+            is_modifier: self.is_modifier.unwrap_or(false),
+            is_summary: self.is_summary.unwrap_or(false),
+            must_support: self.must_support.unwrap_or(false),
+            binding: self.binding.clone(),
+            fixed_value: self.fixed.clone(),
+            pattern_value: self.pattern.clone(),
+            constraints: self.constraint_keys.clone(),
+        }
+    }
+}
+
+/// Slicing rules of a sliced element
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementSlicing {
+    /// Discriminators used to tell slices apart
+    pub discriminators: Vec<SlicingDiscriminator>,
+    /// Whether slices must appear in the order they are defined
+    pub ordered: bool,
+    /// Whether other slices are allowed (`closed`, `open`, `openAtEnd`)
+    pub rules: Option<String>,
+}
+
+impl ElementSlicing {
+    fn from_json(value: &JsonValue) -> Self {
+        Self {
+            discriminators: value
+                .get("discriminator")
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(SlicingDiscriminator::from_json)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ordered: value
+                .get("ordered")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            rules: optional_str(value, "rules"),
+        }
+    }
+}
+
+/// Discriminator of a slicing (e.g. `value` on `system`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SlicingDiscriminator {
+    /// Discriminator type (`value`, `pattern`, `type`, `profile`, `exists`)
+    pub discriminator_type: String,
+    /// FHIRPath expression identifying the discriminating element
+    pub path: String,
+}
+
+impl SlicingDiscriminator {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            discriminator_type: value.get("type")?.as_str()?.to_string(),
+            path: value.get("path")?.as_str()?.to_string(),
+        })
+    }
+}
+
+/// Context an extension may be used in (e.g. `element` on `Patient.name`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExtensionContext {
+    /// Context type (`element`, `extension`, `fhirpath`)
+    pub context_type: String,
+    /// Element path or type, extension URL, or FHIRPath expression
+    pub expression: String,
+}
+
+impl ExtensionContext {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            context_type: value.get("type")?.as_str()?.to_string(),
+            expression: value.get("expression")?.as_str()?.to_string(),
+        })
+    }
+}
+
+/// A single allowed type of an element
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementType {
+    /// Type code (e.g. `string`, `Reference`, `http://hl7.org/fhirpath/System.String`)
+    pub code: String,
+    /// Profiles the type must conform to
+    pub profiles: Vec<String>,
+    /// Target profiles for Reference/canonical types
+    pub target_profiles: Vec<String>,
+}
+
+impl ElementType {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            code: value.get("code")?.as_str()?.to_string(),
+            profiles: string_array(value.get("profile")),
+            target_profiles: string_array(value.get("targetProfile")),
+        })
+    }
+
+    /// System type name if this is a FHIRPath System type code
+    pub fn system_type(&self) -> Option<&str> {
+        self.code.strip_prefix(SYSTEM_TYPE_PREFIX)
+    }
+}
+
+fn parse_elements(container: Option<&JsonValue>) -> Result<Vec<ElementDefinition>> {
+    container
+        .and_then(|c| c.get("element"))
+        .and_then(|e| e.as_array())
+        .map(|elements| elements.iter().map(ElementDefinition::from_json).collect())
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+/// Suffix used for a type in a choice element name (`dateTime` -> `DateTime`)
+pub(crate) fn choice_suffix(code: &str) -> String {
+    let mut chars = code.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn parse_binding(value: &JsonValue) -> Option<ElementBinding> {
+    let strength = BindingStrength::from_code(value.get("strength")?.as_str()?)?;
+    Some(ElementBinding {
+        strength,
+        value_set: optional_str(value, "valueSet"),
+    })
+}
+
+/// Value of a choice property such as `fixed[x]` (`fixedUri`, `patternCodeableConcept`, ...)
+fn prefixed_value(value: &JsonValue, prefix: &str) -> Option<JsonValue> {
+    value.as_object()?.iter().find_map(|(key, v)| {
+        key.strip_prefix(prefix)
+            .filter(|suffix| suffix.starts_with(|c: char| c.is_ascii_uppercase()))
+            .map(|_| v.clone())
+    })
+}
+
+fn required_str(value: &JsonValue, field: &str) -> Result<String> {
+    optional_str(value, field)
+        .ok_or_else(|| ModelError::schema_load_error(format!("Missing required field '{field}'")))
+}
+
+fn optional_str(value: &JsonValue, field: &str) -> Option<String> {
+    value.get(field).and_then(|v| v.as_str()).map(String::from)
+}
+
+fn string_array(value: Option<&JsonValue>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_structure_definition() {
+        let sd = StructureDefinition::from_json(&json!({
+            "resourceType": "StructureDefinition",
+            "url": "http://hl7.org/fhir/StructureDefinition/Observation",
+            "name": "Observation",
+            "kind": "resource",
+            "abstract": false,
+            "type": "Observation",
+            "derivation": "specialization",
+            "baseDefinition": "http://hl7.org/fhir/StructureDefinition/DomainResource",
+            "fhirVersion": "4.0.1",
+            "snapshot": {"element": [
+                {"id": "Observation", "path": "Observation", "min": 0, "max": "*"},
+                {"id": "Observation.value[x]", "path": "Observation.value[x]", "min": 0,
+                 "max": "1", "type": [{"code": "Quantity"}, {"code": "string"}]}
+            ]}
+        }))
+        .unwrap();
+
+        assert_eq!(sd.kind, StructureDefinitionKind::Resource);
+        assert!(!sd.is_profile());
+        assert_eq!(sd.snapshot.len(), 2);
+
+        let value = &sd.snapshot[1];
+        assert!(value.is_choice());
+        assert_eq!(value.name(), "value");
+        assert_eq!(value.parent_path(), Some("Observation"));
+        assert!(!value.is_collection());
+        assert_eq!(
+            value.type_codes().collect::<Vec<_>>(),
+            vec!["Quantity", "string"]
+        );
+    }
+
+    #[test]
+    fn test_element_info_metadata() {
+        let element = ElementDefinition::from_json(&json!({
+            "id": "Observation.status",
+            "path": "Observation.status",
+            "short": "registered | preliminary | final | amended +",
+            "min": 1,
+            "max": "1",
+            "type": [{"code": "code"}],
+            "fixedCode": "final",
+            "constraint": [{"key": "ele-1", "severity": "error"}],
+            "isModifier": true,
+            "isSummary": true,
+            "binding": {
+                "strength": "required",
+                "valueSet": "http://hl7.org/fhir/ValueSet/observation-status|4.0.1"
+            }
+        }))
+        .unwrap();
+
+        let info = element.to_element_info();
+        assert_eq!(info.name, "status");
+        assert_eq!(info.path, "Observation.status");
+        assert_eq!(info.element_type, "code");
+        assert_eq!(info.cardinality.to_string(), "1..1");
+        assert!(!info.is_collection());
+        assert!(info.is_modifier && info.is_summary && !info.must_support);
+        assert_eq!(info.fixed_value, Some(json!("final")));
+        assert_eq!(info.constraints, vec!["ele-1"]);
+
+        let binding = info.binding.unwrap();
+        assert_eq!(binding.strength, BindingStrength::Required);
+        assert_eq!(
+            binding.value_set.as_deref(),
+            Some("http://hl7.org/fhir/ValueSet/observation-status|4.0.1")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_resources() {
+        let result = StructureDefinition::from_json(&json!({"resourceType": "ValueSet"}));
+        assert!(matches!(result, Err(ModelError::SchemaLoadError { .. })));
+    }
+}
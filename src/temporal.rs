@@ -0,0 +1,845 @@
+//! Temporal values with partial precision
+//!
+//! FHIRPath Date, DateTime and Time values may be partial (`2020-01`, `10:30`)
+//! and DateTime values may carry a timezone offset. This module provides parsed
+//! representations that keep both, so evaluation results compare in time order
+//! rather than as text.
+//!
+//! `fhirpath_compare` follows the FHIRPath comparison rules: values are compared
+//! component by component down to the coarser of the two precisions, and the
+//! result is empty (`None`) when they agree that far but their precisions
+//! differ. Seconds and milliseconds count as a single precision. DateTime values
+//! with offsets are compared in UTC; a value without an offset is taken to be
+//! in UTC, as the offset of the evaluation environment is not known here.
+//!
+//! The `Ord` and `Hash` implementations are total and use the same normalized
+//! components, with the precision as a tie-breaker, so `@2020-01-01T10:00:00+02:00`
+//! and `@2020-01-01T08:00:00Z` are equal.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use crate::error::{ModelError, Result};
+
+/// Precision of a temporal value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TemporalPrecision {
+    /// Year (`2020`)
+    Year,
+    /// Month (`2020-01`)
+    Month,
+    /// Day (`2020-01-15`)
+    Day,
+    /// Hour (`10`)
+    Hour,
+    /// Minute (`10:30`)
+    Minute,
+    /// Second (`10:30:15`)
+    Second,
+    /// Fraction of a second (`10:30:15.250`)
+    Millisecond,
+}
+
+impl TemporalPrecision {
+    /// Index of the last compared component, counted from the year
+    fn level(self) -> usize {
+        match self {
+            Self::Year => 0,
+            Self::Month => 1,
+            Self::Day => 2,
+            Self::Hour => 3,
+            Self::Minute => 4,
+            // Seconds and milliseconds are compared as one decimal value
+            Self::Second | Self::Millisecond => 5,
+        }
+    }
+}
+
+/// Timezone offset of a DateTime value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimezoneOffset {
+    /// UTC written as `Z`
+    Utc,
+    /// Offset in minutes east of UTC, written as `+hh:mm` or `-hh:mm`
+    Offset(i16),
+}
+
+impl TimezoneOffset {
+    /// Offset in minutes east of UTC
+    pub fn minutes(&self) -> i16 {
+        match self {
+            Self::Utc => 0,
+            Self::Offset(minutes) => *minutes,
+        }
+    }
+}
+
+impl fmt::Display for TimezoneOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => f.write_str("Z"),
+            Self::Offset(minutes) => {
+                let sign = if *minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// Date with year, month or day precision
+#[derive(Debug, Clone, Copy)]
+pub struct PrecisionDate {
+    year: u16,
+    month: Option<u8>,
+    day: Option<u8>,
+}
+
+impl PrecisionDate {
+    /// Create a date, checking that the components form a valid calendar date
+    pub fn new(year: u16, month: Option<u8>, day: Option<u8>) -> Result<Self> {
+        if year > 9999 {
+            return Err(ModelError::validation_error(format!(
+                "Year {year} is out of range"
+            )));
+        }
+        if let Some(month) = month
+            && !(1..=12).contains(&month)
+        {
+            return Err(ModelError::validation_error(format!(
+                "Month {month} is out of range"
+            )));
+        }
+        match (month, day) {
+            (None, Some(_)) => Err(ModelError::validation_error(
+                "A date with a day must have a month",
+            )),
+            (Some(month), Some(day)) if day == 0 || day > days_in_month(year, month) => {
+                Err(ModelError::validation_error(format!(
+                    "Day {day} is out of range for {year}-{month:02}"
+                )))
+            }
+            _ => Ok(Self { year, month, day }),
+        }
+    }
+
+    /// Year
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Month (1-12), if the precision includes it
+    pub fn month(&self) -> Option<u8> {
+        self.month
+    }
+
+    /// Day of the month, if the precision includes it
+    pub fn day(&self) -> Option<u8> {
+        self.day
+    }
+
+    /// Precision of the date
+    pub fn precision(&self) -> TemporalPrecision {
+        match (self.month, self.day) {
+            (None, _) => TemporalPrecision::Year,
+            (Some(_), None) => TemporalPrecision::Month,
+            (Some(_), Some(_)) => TemporalPrecision::Day,
+        }
+    }
+
+    /// Compare under FHIRPath rules, `None` when the precisions make it undetermined
+    pub fn fhirpath_compare(&self, other: &Self) -> Option<Ordering> {
+        compare_components(
+            &self.components(),
+            self.precision().level(),
+            &other.components(),
+            other.precision().level(),
+        )
+    }
+
+    fn components(&self) -> [i64; 3] {
+        [
+            i64::from(self.year),
+            self.month.map_or(0, i64::from),
+            self.day.map_or(0, i64::from),
+        ]
+    }
+}
+
+/// Time of day with hour, minute, second or millisecond precision
+#[derive(Debug, Clone, Copy)]
+pub struct PrecisionTime {
+    hour: u8,
+    minute: Option<u8>,
+    second: Option<u8>,
+    /// Fraction of the second in nanoseconds
+    nanosecond: Option<u32>,
+    /// Number of fraction digits written, kept for display
+    fraction_digits: u8,
+}
+
+impl PrecisionTime {
+    /// Create a time, checking the component ranges
+    pub fn new(
+        hour: u8,
+        minute: Option<u8>,
+        second: Option<u8>,
+        millisecond: Option<u16>,
+    ) -> Result<Self> {
+        let nanosecond = millisecond
+            .map(|millisecond| {
+                if millisecond > 999 {
+                    return Err(ModelError::validation_error(format!(
+                        "Millisecond {millisecond} is out of range"
+                    )));
+                }
+                Ok(u32::from(millisecond) * 1_000_000)
+            })
+            .transpose()?;
+        Self::from_parts(hour, minute, second, nanosecond, 3)
+    }
+
+    fn from_parts(
+        hour: u8,
+        minute: Option<u8>,
+        second: Option<u8>,
+        nanosecond: Option<u32>,
+        fraction_digits: u8,
+    ) -> Result<Self> {
+        if hour > 23 {
+            return Err(ModelError::validation_error(format!(
+                "Hour {hour} is out of range"
+            )));
+        }
+        if minute.is_some_and(|minute| minute > 59) {
+            return Err(ModelError::validation_error("Minute is out of range"));
+        }
+        // 60 is allowed for leap seconds
+        if second.is_some_and(|second| second > 60) {
+            return Err(ModelError::validation_error("Second is out of range"));
+        }
+        if (minute.is_none() && second.is_some()) || (second.is_none() && nanosecond.is_some()) {
+            return Err(ModelError::validation_error(
+                "A time component is missing before a finer one",
+            ));
+        }
+        Ok(Self {
+            hour,
+            minute,
+            second,
+            nanosecond,
+            fraction_digits: if nanosecond.is_some() {
+                fraction_digits
+            } else {
+                0
+            },
+        })
+    }
+
+    /// Hour (0-23)
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Minute, if the precision includes it
+    pub fn minute(&self) -> Option<u8> {
+        self.minute
+    }
+
+    /// Second, if the precision includes it
+    pub fn second(&self) -> Option<u8> {
+        self.second
+    }
+
+    /// Millisecond, if the time has a fraction of a second
+    pub fn millisecond(&self) -> Option<u16> {
+        self.nanosecond
+            .map(|nanosecond| (nanosecond / 1_000_000) as u16)
+    }
+
+    /// Precision of the time
+    pub fn precision(&self) -> TemporalPrecision {
+        if self.nanosecond.is_some() {
+            TemporalPrecision::Millisecond
+        } else if self.second.is_some() {
+            TemporalPrecision::Second
+        } else if self.minute.is_some() {
+            TemporalPrecision::Minute
+        } else {
+            TemporalPrecision::Hour
+        }
+    }
+
+    /// Parse a FHIR `time` value, which requires seconds
+    pub fn parse_fhir(value: &str) -> Result<Self> {
+        let time: Self = value.parse()?;
+        if time.second.is_none() {
+            return Err(invalid_format(value, "time", "seconds are required"));
+        }
+        Ok(time)
+    }
+
+    /// Compare under FHIRPath rules, `None` when the precisions make it undetermined
+    pub fn fhirpath_compare(&self, other: &Self) -> Option<Ordering> {
+        compare_components(
+            &self.components(),
+            self.level(),
+            &other.components(),
+            other.level(),
+        )
+    }
+
+    fn level(&self) -> usize {
+        self.precision().level() - TemporalPrecision::Hour.level()
+    }
+
+    fn components(&self) -> [i64; 3] {
+        [
+            i64::from(self.hour),
+            self.minute.map_or(0, i64::from),
+            self.second.map_or(0, i64::from) * 1_000_000_000 + self.nanosecond.map_or(0, i64::from),
+        ]
+    }
+}
+
+/// Date and time with partial precision and an optional timezone offset
+#[derive(Debug, Clone, Copy)]
+pub struct PrecisionDateTime {
+    date: PrecisionDate,
+    time: Option<PrecisionTime>,
+    offset: Option<TimezoneOffset>,
+}
+
+impl PrecisionDateTime {
+    /// Create a DateTime; a time requires a full date and an offset requires a time
+    pub fn new(
+        date: PrecisionDate,
+        time: Option<PrecisionTime>,
+        offset: Option<TimezoneOffset>,
+    ) -> Result<Self> {
+        if time.is_some() && date.precision() != TemporalPrecision::Day {
+            return Err(ModelError::validation_error(
+                "A DateTime with a time must have a full date",
+            ));
+        }
+        if offset.is_some() && time.is_none() {
+            return Err(ModelError::validation_error(
+                "A DateTime with a timezone offset must have a time",
+            ));
+        }
+        if offset.is_some_and(|offset| offset.minutes().abs() >= 24 * 60) {
+            return Err(ModelError::validation_error(
+                "Timezone offset is out of range",
+            ));
+        }
+        Ok(Self { date, time, offset })
+    }
+
+    /// Date part
+    pub fn date(&self) -> &PrecisionDate {
+        &self.date
+    }
+
+    /// Time part, if the precision includes one
+    pub fn time(&self) -> Option<&PrecisionTime> {
+        self.time.as_ref()
+    }
+
+    /// Timezone offset, if one was given
+    pub fn offset(&self) -> Option<TimezoneOffset> {
+        self.offset
+    }
+
+    /// Precision of the DateTime
+    pub fn precision(&self) -> TemporalPrecision {
+        self.time
+            .as_ref()
+            .map_or_else(|| self.date.precision(), PrecisionTime::precision)
+    }
+
+    /// Parse a FHIR `dateTime` value
+    ///
+    /// A time requires seconds and a timezone offset.
+    pub fn parse_fhir(value: &str) -> Result<Self> {
+        let date_time: Self = value.parse()?;
+        if value.ends_with('T') {
+            return Err(invalid_format(
+                value,
+                "dateTime",
+                "time is missing after 'T'",
+            ));
+        }
+        if let Some(time) = &date_time.time {
+            if time.second.is_none() {
+                return Err(invalid_format(value, "dateTime", "seconds are required"));
+            }
+            if date_time.offset.is_none() {
+                return Err(invalid_format(
+                    value,
+                    "dateTime",
+                    "a timezone offset is required",
+                ));
+            }
+        }
+        Ok(date_time)
+    }
+
+    /// Parse a FHIR `instant` value, which must be complete to the second with an offset
+    pub fn parse_fhir_instant(value: &str) -> Result<Self> {
+        let instant = Self::parse_fhir(value)?;
+        if instant.time.is_none() {
+            return Err(invalid_format(value, "instant", "a time is required"));
+        }
+        Ok(instant)
+    }
+
+    /// Compare under FHIRPath rules, `None` when the precisions make it undetermined
+    pub fn fhirpath_compare(&self, other: &Self) -> Option<Ordering> {
+        compare_components(
+            &self.utc_components(),
+            self.precision().level(),
+            &other.utc_components(),
+            other.precision().level(),
+        )
+    }
+
+    /// Components shifted to UTC
+    fn utc_components(&self) -> [i64; 6] {
+        let [year, month, day] = self.date.components();
+        let Some(time) = &self.time else {
+            return [year, month, day, 0, 0, 0];
+        };
+        let [hour, minute, second] = time.components();
+        let offset = self.offset.map_or(0, |offset| i64::from(offset.minutes()));
+        if offset == 0 {
+            return [year, month, day, hour, minute, second];
+        }
+
+        let minutes = days_from_civil(year, month, day) * 1440 + hour * 60 + minute - offset;
+        let (year, month, day) = civil_from_days(minutes.div_euclid(1440));
+        let minute_of_day = minutes.rem_euclid(1440);
+        [
+            year,
+            month,
+            day,
+            minute_of_day / 60,
+            minute_of_day % 60,
+            second,
+        ]
+    }
+}
+
+impl From<PrecisionDate> for PrecisionDateTime {
+    fn from(date: PrecisionDate) -> Self {
+        Self {
+            date,
+            time: None,
+            offset: None,
+        }
+    }
+}
+
+impl FromStr for PrecisionDate {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (date, rest) = parse_date(value)?;
+        if !rest.is_empty() {
+            return Err(invalid_format(
+                value,
+                "Date",
+                "unexpected trailing characters",
+            ));
+        }
+        Ok(date)
+    }
+}
+
+impl FromStr for PrecisionTime {
+    type Err = ModelError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (time, rest) = parse_time(value)?;
+        if !rest.is_empty() {
+            return Err(invalid_format(
+                value,
+                "Time",
+                "unexpected trailing characters",
+            ));
+        }
+        Ok(time)
+    }
+}
+
+impl FromStr for PrecisionDateTime {
+    type Err = ModelError;
+
+    /// Parse a FHIRPath DateTime (`2020`, `2020-01-01T10`, `2020-01-01T10:30:00.5+02:00`)
+    fn from_str(value: &str) -> Result<Self> {
+        let (date, rest) = parse_date(value)?;
+        let Some(rest) = rest.strip_prefix('T') else {
+            if !rest.is_empty() {
+                return Err(invalid_format(value, "DateTime", "expected 'T'"));
+            }
+            return Ok(date.into());
+        };
+        if rest.is_empty() {
+            return Ok(date.into());
+        }
+
+        let (time, rest) = parse_time(rest)?;
+        let offset = if rest.is_empty() {
+            None
+        } else {
+            Some(
+                parse_offset(rest)
+                    .ok_or_else(|| invalid_format(value, "DateTime", "invalid timezone offset"))?,
+            )
+        };
+        Self::new(date, Some(time), offset)
+    }
+}
+
+impl fmt::Display for PrecisionDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if let Some(month) = self.month {
+            write!(f, "-{month:02}")?;
+        }
+        if let Some(day) = self.day {
+            write!(f, "-{day:02}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PrecisionTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}", self.hour)?;
+        if let Some(minute) = self.minute {
+            write!(f, ":{minute:02}")?;
+        }
+        if let Some(second) = self.second {
+            write!(f, ":{second:02}")?;
+        }
+        if let Some(nanosecond) = self.nanosecond {
+            let digits = usize::from(self.fraction_digits);
+            let fraction = nanosecond / 10u32.pow(9 - u32::from(self.fraction_digits));
+            write!(f, ".{fraction:0digits$}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PrecisionDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.date)?;
+        if let Some(time) = &self.time {
+            write!(f, "T{time}")?;
+        }
+        if let Some(offset) = &self.offset {
+            write!(f, "{offset}")?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! impl_total_order {
+    ($type:ty, $components:ident) => {
+        impl PartialEq for $type {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for $type {}
+
+        impl PartialOrd for $type {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $type {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.$components()
+                    .cmp(&other.$components())
+                    .then_with(|| self.precision().cmp(&other.precision()))
+            }
+        }
+
+        impl Hash for $type {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.$components().hash(state);
+                self.precision().hash(state);
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                let value = std::borrow::Cow::<'de, str>::deserialize(deserializer)?;
+                value.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_total_order!(PrecisionDate, components);
+impl_total_order!(PrecisionTime, components);
+impl_total_order!(PrecisionDateTime, utc_components);
+
+/// Compare components down to the coarser level; `None` if equal so far at different levels
+fn compare_components(a: &[i64], a_level: usize, b: &[i64], b_level: usize) -> Option<Ordering> {
+    for (x, y) in a.iter().zip(b).take(a_level.min(b_level) + 1) {
+        if x != y {
+            return Some(x.cmp(y));
+        }
+    }
+    (a_level == b_level).then_some(Ordering::Equal)
+}
+
+fn parse_date(value: &str) -> Result<(PrecisionDate, &str)> {
+    let invalid = || invalid_format(value, "Date", "expected YYYY, YYYY-MM or YYYY-MM-DD");
+    let (year, mut rest) = digits(value, 4).ok_or_else(invalid)?;
+    let mut month = None;
+    let mut day = None;
+    if let Some(after) = rest.strip_prefix('-') {
+        let (parsed, after) = digits(after, 2).ok_or_else(invalid)?;
+        month = Some(parsed as u8);
+        rest = after;
+        if let Some(after) = rest.strip_prefix('-') {
+            let (parsed, after) = digits(after, 2).ok_or_else(invalid)?;
+            day = Some(parsed as u8);
+            rest = after;
+        }
+    }
+    Ok((PrecisionDate::new(year as u16, month, day)?, rest))
+}
+
+fn parse_time(value: &str) -> Result<(PrecisionTime, &str)> {
+    let invalid = || {
+        invalid_format(
+            value,
+            "Time",
+            "expected hh, hh:mm, hh:mm:ss or hh:mm:ss.fff",
+        )
+    };
+    let (hour, mut rest) = digits(value, 2).ok_or_else(invalid)?;
+    let mut minute = None;
+    let mut second = None;
+    let mut nanosecond = None;
+    let mut fraction_digits = 0;
+    if let Some(after) = rest.strip_prefix(':') {
+        let (parsed, after) = digits(after, 2).ok_or_else(invalid)?;
+        minute = Some(parsed as u8);
+        rest = after;
+        if let Some(after) = rest.strip_prefix(':') {
+            let (parsed, after) = digits(after, 2).ok_or_else(invalid)?;
+            second = Some(parsed as u8);
+            rest = after;
+            if let Some(after) = rest.strip_prefix('.') {
+                let count = after.bytes().take_while(u8::is_ascii_digit).count();
+                if !(1..=9).contains(&count) {
+                    return Err(invalid());
+                }
+                let (fraction, after) = digits(after, count).ok_or_else(invalid)?;
+                nanosecond = Some(fraction * 10u32.pow(9 - count as u32));
+                fraction_digits = count as u8;
+                rest = after;
+            }
+        }
+    }
+    let time = PrecisionTime::from_parts(hour as u8, minute, second, nanosecond, fraction_digits)?;
+    Ok((time, rest))
+}
+
+fn parse_offset(value: &str) -> Option<TimezoneOffset> {
+    if value == "Z" {
+        return Some(TimezoneOffset::Utc);
+    }
+    let sign = match value.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let (hours, rest) = digits(&value[1..], 2)?;
+    let (minutes, rest) = digits(rest.strip_prefix(':')?, 2)?;
+    if !rest.is_empty() || hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(TimezoneOffset::Offset(sign * (hours * 60 + minutes) as i16))
+}
+
+/// Parse exactly `count` ASCII digits from the start of a string
+fn digits(value: &str, count: usize) -> Option<(u32, &str)> {
+    let prefix = value.get(..count)?;
+    if !prefix.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((prefix.parse().ok()?, &value[count..]))
+}
+
+fn invalid_format(value: &str, type_name: &str, reason: &str) -> ModelError {
+    ModelError::validation_error(format!("Invalid {type_name} '{value}': {reason}"))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date of a day count since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_time(value: &str) -> PrecisionDateTime {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parsing_and_display() {
+        for value in [
+            "2020",
+            "2020-02",
+            "2020-02-29",
+            "2020-02-29T10",
+            "2020-02-29T10:30",
+            "2020-02-29T10:30:15.25+05:30",
+            "2020-02-29T10:30:15.000Z",
+            "2020-02-29T10:30:15-05:00",
+            "2020-02-29T10:30:15+00:00",
+        ] {
+            assert_eq!(date_time(value).to_string(), value);
+        }
+        assert_eq!(date_time("2020-01-01T").precision(), TemporalPrecision::Day);
+        assert_eq!(
+            date_time("2020-01-01T10:30:15.5").precision(),
+            TemporalPrecision::Millisecond
+        );
+        let time: PrecisionTime = "23:59:60.123456".parse().unwrap();
+        assert_eq!(time.millisecond(), Some(123));
+        assert_eq!(time.to_string(), "23:59:60.123456");
+
+        for invalid in [
+            "2019-02-29",
+            "2020-13",
+            "20",
+            "2020-1-01",
+            "2020-01-01T25",
+            "2020T10",
+        ] {
+            assert!(invalid.parse::<PrecisionDateTime>().is_err(), "{invalid}");
+        }
+        assert!(
+            "2020-01-01T10:00:00+24:00"
+                .parse::<PrecisionDateTime>()
+                .is_err()
+        );
+
+        // FHIR formats are stricter than FHIRPath literals
+        assert!(PrecisionDateTime::parse_fhir("2020-01").is_ok());
+        assert!(PrecisionDateTime::parse_fhir("2020-01-01T10:00:00Z").is_ok());
+        assert!(PrecisionDateTime::parse_fhir("2020-01-01T10:00Z").is_err());
+        assert!(PrecisionDateTime::parse_fhir("2020-01-01T10:00:00").is_err());
+        assert!(PrecisionDateTime::parse_fhir_instant("2020-01-01").is_err());
+        assert!(PrecisionTime::parse_fhir("10:00").is_err());
+    }
+
+    #[test]
+    fn test_fhirpath_comparison() {
+        let compare = |a: &str, b: &str| date_time(a).fhirpath_compare(&date_time(b));
+
+        assert_eq!(
+            compare("2020-01-01T10:00:00+02:00", "2020-01-01T09:00:00Z"),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare("2020-01-01T01:00:00+02:00", "2019-12-31T23:00:00Z"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare("2020-01", "2020-01-15"), None);
+        assert_eq!(compare("2020-01", "2020-02-15"), Some(Ordering::Less));
+        assert_eq!(compare("2020-01-01T10:30", "2020-01-01T10"), None);
+        assert_eq!(
+            compare("2020-01-01T10:30:00", "2020-01-01T10:30:00.000"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare("2020-01-01T10:30:00.5", "2020-01-01T10:30:00.25"),
+            Some(Ordering::Greater)
+        );
+
+        let time = |value: &str| value.parse::<PrecisionTime>().unwrap();
+        assert_eq!(time("10:30").fhirpath_compare(&time("10:30:00")), None);
+        assert_eq!(
+            time("10:30").fhirpath_compare(&time("11:00:00")),
+            Some(Ordering::Less)
+        );
+
+        // The total order agrees with FHIRPath where FHIRPath has an answer
+        assert_eq!(
+            date_time("2020-01-01T10:00:00+02:00"),
+            date_time("2020-01-01T08:00:00Z")
+        );
+        assert!(date_time("2020-01") < date_time("2020-01-15"));
+        assert_ne!(
+            date_time("2020-01-01T10:30:00"),
+            date_time("2020-01-01T10:30:00.000")
+        );
+    }
+}
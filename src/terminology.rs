@@ -5,6 +5,8 @@ use async_trait::async_trait;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "http-client")]
+use crate::canonical::Canonical;
 use crate::error::Result;
 
 /// Simple terminology service provider
@@ -422,12 +424,9 @@ impl TerminologyProvider for HttpTerminologyProvider {
         // Split a `url|version` canonical: `$expand` takes the bare canonical in
         // `url` and the version in `valueSetVersion` (a `|`-versioned `url` 400s on
         // many tx servers).
-        let (vs_url, vs_version) = match valueset_url.split_once('|') {
-            Some((base, version)) => (base, Some(version)),
-            None => (valueset_url, None),
-        };
-        let mut url = format!("{}/ValueSet/$expand?url={}", self.base_url, vs_url);
-        if let Some(version) = vs_version {
+        let canonical = Canonical::parse(valueset_url);
+        let mut url = format!("{}/ValueSet/$expand?url={}", self.base_url, canonical.url);
+        if let Some(version) = &canonical.version {
             url.push_str("&valueSetVersion=");
             url.push_str(version);
         }
@@ -677,18 +676,15 @@ impl TerminologyProvider for HttpTerminologyProvider {
         // `$validate-code` operation the `url` parameter takes the bare canonical
         // and the version is supplied separately via `valueSetVersion`; passing a
         // `|`-versioned value in `url` is rejected by many tx servers with 400.
-        let (vs_url, vs_version) = match valueset.split_once('|') {
-            Some((base, version)) => (base, Some(version)),
-            None => (valueset, None),
-        };
+        let canonical = Canonical::parse(valueset);
 
         let mut params = vec![
-            ("url".to_string(), vs_url.to_string()),
+            ("url".to_string(), canonical.url),
             ("code".to_string(), code.to_string()),
         ];
 
-        if let Some(version) = vs_version {
-            params.push(("valueSetVersion".to_string(), version.to_string()));
+        if let Some(version) = canonical.version {
+            params.push(("valueSetVersion".to_string(), version));
         }
 
         if let Some(sys) = system {
@@ -0,0 +1,93 @@
+//! Shared fixtures for unit tests
+//!
+//! Minimal StructureDefinitions (`Element`, `string`, `HumanName`, `Resource`,
+//! `DomainResource` and `Patient`) used by the provider tests across modules.
+
+use serde_json::{Value as JsonValue, json};
+
+use crate::package_provider::CORE_STRUCTURE_DEFINITION_PREFIX;
+
+pub(crate) fn sd(name: &str, kind: &str, base: Option<&str>, elements: JsonValue) -> JsonValue {
+    let mut sd = json!({
+        "resourceType": "StructureDefinition",
+        "url": format!("{CORE_STRUCTURE_DEFINITION_PREFIX}{name}"),
+        "name": name,
+        "kind": kind,
+        "abstract": false,
+        "type": name,
+        "fhirVersion": "4.0.1",
+        "snapshot": {"element": elements}
+    });
+    if let Some(base) = base {
+        sd["derivation"] = json!("specialization");
+        sd["baseDefinition"] = json!(format!("{CORE_STRUCTURE_DEFINITION_PREFIX}{base}"));
+    }
+    sd
+}
+
+pub(crate) fn el(path: &str, max: &str, types: &[&str]) -> JsonValue {
+    json!({
+        "id": path,
+        "path": path,
+        "min": 0,
+        "max": max,
+        "short": format!("{path} element"),
+        "type": types.iter().map(|t| json!({"code": t})).collect::<Vec<_>>()
+    })
+}
+
+pub(crate) fn fixture_definitions() -> Vec<JsonValue> {
+    vec![
+        sd(
+            "Element",
+            "complex-type",
+            None,
+            json!([el("Element", "*", &[])]),
+        ),
+        sd(
+            "string",
+            "primitive-type",
+            Some("Element"),
+            json!([
+                el("string", "*", &[]),
+                el(
+                    "string.value",
+                    "1",
+                    &["http://hl7.org/fhirpath/System.String"]
+                )
+            ]),
+        ),
+        sd(
+            "HumanName",
+            "complex-type",
+            Some("Element"),
+            json!([
+                el("HumanName", "*", &[]),
+                el("HumanName.family", "1", &["string"]),
+                el("HumanName.given", "*", &["string"])
+            ]),
+        ),
+        sd(
+            "Resource",
+            "resource",
+            None,
+            json!([el("Resource", "*", &[])]),
+        ),
+        sd(
+            "DomainResource",
+            "resource",
+            Some("Resource"),
+            json!([el("DomainResource", "*", &[])]),
+        ),
+        sd(
+            "Patient",
+            "resource",
+            Some("DomainResource"),
+            json!([
+                el("Patient", "*", &[]),
+                el("Patient.name", "*", &["HumanName"]),
+                el("Patient.deceased[x]", "1", &["boolean", "dateTime"])
+            ]),
+        ),
+    ]
+}
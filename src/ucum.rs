@@ -0,0 +1,767 @@
+//! UCUM units for Quantity values
+//!
+//! This module parses UCUM unit expressions (`mg/dL`, `mm[Hg]`, `10*3/uL`,
+//! `kg.m/s2`) without any external service and reduces them to a factor over
+//! the UCUM base units. Quantities whose units reduce to the same base units
+//! are commensurable and can be converted and compared.
+//!
+//! FHIRPath calendar durations (`year`, `month`, `week`, `day`, `hour`,
+//! `minute`) are kept apart from the definite UCUM durations (`a`, `mo`, `wk`,
+//! `d`, `h`, `min`): `1 year` and `1 'a'` are not comparable, but they are
+//! equivalent. `second` and `millisecond` are the same as `s` and `ms`.
+//!
+//! The unit table covers the SI and metric units, clinical units and the
+//! international customary units; other units are rejected as unknown.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock};
+
+use rust_decimal::{Decimal, MathematicalOps};
+
+use crate::error::{ModelError, Result};
+
+/// Canonical forms of the unit expressions seen so far, or why they are invalid
+static CANONICAL_UNITS: LazyLock<papaya::HashMap<String, ParsedUnit>> =
+    LazyLock::new(papaya::HashMap::new);
+
+/// Canonical form of a unit expression, or the message of its parse error
+type ParsedUnit = std::result::Result<CanonicalUnit, Arc<str>>;
+
+/// Base units with their exponents, shared by the clones of a cached unit
+type Dimensions = Arc<BTreeMap<&'static str, i32>>;
+
+/// Number of unit expressions kept in `CANONICAL_UNITS`
+const MAX_CACHED_UNITS: usize = 1024;
+
+/// Largest exponent accepted on a unit symbol (`10*-24`, `m3`)
+const MAX_EXPONENT: u32 = 32;
+
+/// Significant digits kept when comparing canonical values
+const COMPARISON_DIGITS: u32 = 20;
+
+/// Base unit keys of the calendar durations
+const CALENDAR_MONTH: &str = "{calendar month}";
+const CALENDAR_SECOND: &str = "{calendar second}";
+
+/// Unit expression reduced to UCUM base units
+///
+/// A value `v` in the unit is `(v + offset) * numerator / denominator` in the
+/// base units. Only the special temperature units (`Cel`, `[degF]`) have an
+/// offset, and only when they are used on their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalUnit {
+    numerator: Decimal,
+    denominator: Decimal,
+    offset: Decimal,
+    dimensions: Dimensions,
+}
+
+impl CanonicalUnit {
+    fn unity() -> Self {
+        Self {
+            numerator: Decimal::ONE,
+            denominator: Decimal::ONE,
+            offset: Decimal::ZERO,
+            dimensions: Dimensions::default(),
+        }
+    }
+
+    fn base(key: &'static str) -> Self {
+        Self {
+            dimensions: Arc::new(BTreeMap::from([(key, 1)])),
+            ..Self::unity()
+        }
+    }
+
+    fn scalar(value: Decimal) -> Self {
+        Self {
+            numerator: value,
+            ..Self::unity()
+        }
+    }
+
+    /// Base units with their exponents, e.g. `g.m-3`, or `1` when dimensionless
+    pub fn dimension(&self) -> String {
+        if self.dimensions.is_empty() {
+            return "1".to_string();
+        }
+        self.dimensions
+            .iter()
+            .map(|(unit, exponent)| {
+                if *exponent == 1 {
+                    unit.to_string()
+                } else {
+                    format!("{unit}{exponent}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Whether values in both units can be converted into each other
+    pub fn is_commensurable(&self, other: &Self) -> bool {
+        self.dimensions == other.dimensions
+    }
+
+    /// Value expressed in the base units
+    pub fn to_canonical(&self, value: Decimal) -> Option<Decimal> {
+        (value.checked_add(self.offset)?)
+            .checked_mul(self.numerator)?
+            .checked_div(self.denominator)
+    }
+
+    /// Value in this unit of a value expressed in the base units
+    pub fn canonical_to_unit(&self, value: Decimal) -> Option<Decimal> {
+        value
+            .checked_mul(self.denominator)?
+            .checked_div(self.numerator)?
+            .checked_sub(self.offset)
+    }
+
+    fn multiply(&self, other: &Self) -> Result<Self> {
+        let mut dimensions = BTreeMap::clone(&self.dimensions);
+        for (unit, exponent) in other.dimensions.iter() {
+            *dimensions.entry(unit).or_insert(0) += exponent;
+        }
+        dimensions.retain(|_, exponent| *exponent != 0);
+        Ok(Self {
+            numerator: checked(self.numerator.checked_mul(other.numerator))?,
+            denominator: checked(self.denominator.checked_mul(other.denominator))?,
+            offset: Decimal::ZERO,
+            dimensions: Arc::new(dimensions),
+        })
+    }
+
+    fn inverse(&self) -> Self {
+        Self {
+            numerator: self.denominator,
+            denominator: self.numerator,
+            offset: Decimal::ZERO,
+            dimensions: Arc::new(
+                self.dimensions
+                    .iter()
+                    .map(|(unit, exponent)| (*unit, -exponent))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn power(&self, exponent: i32) -> Result<Self> {
+        if exponent == 1 {
+            return Ok(self.clone());
+        }
+        if exponent.unsigned_abs() > MAX_EXPONENT {
+            return Err(ModelError::validation_error(format!(
+                "UCUM unit exponent {exponent} is out of range"
+            )));
+        }
+        let base = if exponent < 0 {
+            self.inverse()
+        } else {
+            self.clone()
+        };
+        let magnitude = i64::from(exponent.unsigned_abs());
+        Ok(Self {
+            numerator: checked(base.numerator.checked_powi(magnitude))?,
+            denominator: checked(base.denominator.checked_powi(magnitude))?,
+            offset: Decimal::ZERO,
+            dimensions: Arc::new(
+                base.dimensions
+                    .iter()
+                    .map(|(unit, power)| (*unit, power * exponent.abs()))
+                    .filter(|(_, power)| *power != 0)
+                    .collect(),
+            ),
+        })
+    }
+}
+
+/// Reduce a UCUM unit expression or FHIRPath calendar duration to base units
+///
+/// An empty unit is the unity `1`. Units and parse errors are cached for the
+/// process, up to `MAX_CACHED_UNITS` expressions.
+pub fn canonicalize(unit: &str) -> Result<CanonicalUnit> {
+    cached_unit(unit).map_err(|message| ModelError::validation_error(message.as_ref()))
+}
+
+fn cached_unit(unit: &str) -> ParsedUnit {
+    let units = CANONICAL_UNITS.pin();
+    if let Some(parsed) = units.get(unit) {
+        return parsed.clone();
+    }
+    let parsed = parse_unit(unit).map_err(|error| match error {
+        ModelError::ValidationError { message } => Arc::from(message),
+        error => Arc::from(error.to_string()),
+    });
+    if units.len() < MAX_CACHED_UNITS {
+        units.insert(unit.to_string(), parsed.clone());
+    }
+    parsed
+}
+
+/// Whether a unit is a valid UCUM expression or calendar duration
+pub fn is_valid_unit(unit: &str) -> bool {
+    canonicalize(unit).is_ok()
+}
+
+/// Convert a value between commensurable units
+pub fn convert(value: Decimal, from: &str, to: &str) -> Result<Decimal> {
+    let source = canonicalize(from)?;
+    let target = canonicalize(to)?;
+    if !source.is_commensurable(&target) {
+        return Err(ModelError::type_incompatibility(
+            format!("a unit commensurable with '{to}'"),
+            from,
+        ));
+    }
+    source
+        .to_canonical(value)
+        .and_then(|canonical| target.canonical_to_unit(canonical))
+        .map(|converted| converted.normalize())
+        .ok_or_else(|| {
+            ModelError::validation_error(format!("Converting {value} '{from}' to '{to}' overflows"))
+        })
+}
+
+/// Definite UCUM unit of a FHIRPath calendar duration keyword (`year` is `a`)
+pub fn calendar_duration_unit(keyword: &str) -> Option<&'static str> {
+    match keyword {
+        "year" | "years" => Some("a"),
+        "month" | "months" => Some("mo"),
+        "week" | "weeks" => Some("wk"),
+        "day" | "days" => Some("d"),
+        "hour" | "hours" => Some("h"),
+        "minute" | "minutes" => Some("min"),
+        "second" | "seconds" => Some("s"),
+        "millisecond" | "milliseconds" => Some("ms"),
+        _ => None,
+    }
+}
+
+/// Compare two quantities under FHIRPath `=`/`<` rules
+///
+/// Units outside the unit table (`[CFU]`, `tablet`) only compare with the
+/// exact same unit. Returns `None` when the units are not commensurable,
+/// including calendar durations above seconds against definite UCUM durations.
+pub fn compare_quantities(
+    value: Decimal,
+    unit: &str,
+    other_value: Decimal,
+    other_unit: &str,
+) -> Option<std::cmp::Ordering> {
+    match (
+        comparable_value(value, unit),
+        comparable_value(other_value, other_unit),
+    ) {
+        (Some((dimensions, canonical)), Some((other_dimensions, other_canonical))) => {
+            (dimensions == other_dimensions).then(|| canonical.cmp(&other_canonical))
+        }
+        _ => (unit == other_unit).then(|| value.cmp(&other_value)),
+    }
+}
+
+/// Whether two quantities are equivalent under FHIRPath `~` rules
+///
+/// Calendar durations are equivalent to their definite UCUM counterparts.
+pub fn quantities_equivalent(
+    value: Decimal,
+    unit: &str,
+    other_value: Decimal,
+    other_unit: &str,
+) -> bool {
+    let unit = calendar_duration_unit(unit).unwrap_or(unit);
+    let other_unit = calendar_duration_unit(other_unit).unwrap_or(other_unit);
+    compare_quantities(value, unit, other_value, other_unit) == Some(std::cmp::Ordering::Equal)
+}
+
+/// Key under which a quantity is equal to, ordered and hashed with others
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum ComparisonKey<'a> {
+    /// Quantity in a known unit, by base unit exponents and canonical value,
+    /// so `1000 'mg'` and `1 'g'` share a key
+    Known(Dimensions, Decimal),
+    /// Quantity in an unknown unit, by the unit text and value
+    Unknown(&'a str, Decimal),
+}
+
+/// Key under which a quantity is equal to, ordered and hashed with others
+pub(crate) fn comparison_key(value: Decimal, unit: &str) -> ComparisonKey<'_> {
+    match comparable_value(value, unit) {
+        Some((dimensions, canonical)) => ComparisonKey::Known(dimensions, canonical),
+        None => ComparisonKey::Unknown(unit, value.normalize()),
+    }
+}
+
+fn comparable_value(value: Decimal, unit: &str) -> Option<(Dimensions, Decimal)> {
+    let canonical = cached_unit(unit).ok()?;
+    let converted = canonical
+        .to_canonical(value)?
+        .round_sf(COMPARISON_DIGITS)?
+        .normalize();
+    Some((canonical.dimensions, converted))
+}
+
+fn parse_unit(unit: &str) -> Result<CanonicalUnit> {
+    if unit.is_empty() {
+        return Ok(CanonicalUnit::unity());
+    }
+    if let Some(calendar) = calendar_duration(unit) {
+        return Ok(calendar);
+    }
+    let mut parser = Parser {
+        input: unit,
+        position: 0,
+    };
+    let result = parser.term()?;
+    if parser.position < unit.len() {
+        return Err(parser.error("unexpected character"));
+    }
+    Ok(result)
+}
+
+/// Calendar durations, kept apart from definite durations except below a minute
+fn calendar_duration(keyword: &str) -> Option<CanonicalUnit> {
+    let (seconds, key) = match keyword {
+        "year" | "years" => (12, CALENDAR_MONTH),
+        "month" | "months" => (1, CALENDAR_MONTH),
+        "week" | "weeks" => (604_800, CALENDAR_SECOND),
+        "day" | "days" => (86_400, CALENDAR_SECOND),
+        "hour" | "hours" => (3_600, CALENDAR_SECOND),
+        "minute" | "minutes" => (60, CALENDAR_SECOND),
+        "second" | "seconds" | "millisecond" | "milliseconds" => {
+            return parse_unit(calendar_duration_unit(keyword)?).ok();
+        }
+        _ => return None,
+    };
+    Some(CanonicalUnit {
+        numerator: Decimal::from(seconds),
+        ..CanonicalUnit::base(key)
+    })
+}
+
+/// Recursive-descent parser for the UCUM grammar
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// `term := '/'? component (('.' | '/') component)*`
+    fn term(&mut self) -> Result<CanonicalUnit> {
+        let mut result = if self.eat('/') {
+            self.component()?.inverse()
+        } else {
+            self.component()?
+        };
+        loop {
+            if self.eat('.') {
+                result = result.multiply(&self.component()?)?;
+            } else if self.eat('/') {
+                result = result.multiply(&self.component()?.inverse())?;
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    /// A parenthesized term, an annotation, a number or a unit symbol with exponent
+    fn component(&mut self) -> Result<CanonicalUnit> {
+        let rest = self.input.get(self.position..).unwrap_or_default();
+        let result = if self.eat('(') {
+            let term = self.term()?;
+            if !self.eat(')') {
+                return Err(self.error("expected ')'"));
+            }
+            term
+        } else if rest.starts_with('{') {
+            CanonicalUnit::unity()
+        } else if rest.starts_with(|c: char| c.is_ascii_digit())
+            && !rest.starts_with("10*")
+            && !rest.starts_with("10^")
+        {
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            self.position += digits;
+            CanonicalUnit::scalar(
+                rest[..digits]
+                    .parse()
+                    .map_err(|_| self.error("invalid number"))?,
+            )
+        } else {
+            let symbol = self.symbol()?;
+            let exponent = self.exponent()?;
+            resolve_symbol(symbol)
+                .ok_or_else(|| {
+                    ModelError::validation_error(format!(
+                        "Unknown UCUM unit '{symbol}' in '{}'",
+                        self.input
+                    ))
+                })??
+                .power(exponent)?
+        };
+        self.annotation()?;
+        Ok(result)
+    }
+
+    fn symbol(&mut self) -> Result<&'a str> {
+        let rest = &self.input[self.position..];
+        if rest.starts_with("10*") || rest.starts_with("10^") {
+            self.position += 3;
+            return Ok(&rest[..3]);
+        }
+
+        let mut end = 0;
+        while let Some(c) = rest[end..].chars().next() {
+            match c {
+                // Bracketed parts may contain any character (`[arb'U]`, `m[H2O]`)
+                '[' => match rest[end..].find(']') {
+                    Some(close) => end += close + 1,
+                    None => return Err(self.error("unclosed '['")),
+                },
+                c if c.is_ascii_digit() => break,
+                '+' | '-' if rest[end + 1..].starts_with(|n: char| n.is_ascii_digit()) => break,
+                '.' | '/' | '(' | ')' | '{' | '}' => break,
+                c => end += c.len_utf8(),
+            }
+        }
+        if end == 0 {
+            return Err(self.error("expected a unit"));
+        }
+        self.position += end;
+        Ok(&rest[..end])
+    }
+
+    fn exponent(&mut self) -> Result<i32> {
+        let rest = &self.input[self.position..];
+        let sign = usize::from(rest.starts_with(['+', '-']));
+        let digits = rest[sign..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Ok(1);
+        }
+        self.position += sign + digits;
+        rest[..sign + digits]
+            .parse()
+            .map_err(|_| self.error("invalid exponent"))
+    }
+
+    fn annotation(&mut self) -> Result<()> {
+        if self.eat('{') {
+            match self.input[self.position..].find('}') {
+                Some(end) => self.position += end + 1,
+                None => return Err(self.error("unclosed '{'")),
+            }
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.input[self.position..].starts_with(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> ModelError {
+        ModelError::validation_error(format!(
+            "Invalid UCUM unit '{}' at position {}: {message}",
+            self.input, self.position
+        ))
+    }
+}
+
+/// Kind of a UCUM atom
+enum Atom {
+    /// Base unit
+    Base,
+    /// Multiple of a unit expression
+    Derived(&'static str, &'static str),
+    /// Arbitrary unit, only commensurable with itself
+    Arbitrary,
+    /// Unit with an offset: value `v` is `(v + offset) * factor` of the unit expression
+    Special(&'static str, &'static str, &'static str),
+}
+
+/// UCUM atoms: code, whether metric prefixes apply, and definition
+const ATOMS: &[(&str, bool, Atom)] = &[
+    // Base units
+    ("m", true, Atom::Base),
+    ("s", true, Atom::Base),
+    ("g", true, Atom::Base),
+    ("rad", true, Atom::Base),
+    ("K", true, Atom::Base),
+    ("C", true, Atom::Base),
+    ("cd", true, Atom::Base),
+    // Dimensionless
+    ("10*", false, Atom::Derived("10", "")),
+    ("10^", false, Atom::Derived("10", "")),
+    (
+        "[pi]",
+        false,
+        Atom::Derived("3.1415926535897932384626433833", ""),
+    ),
+    ("%", false, Atom::Derived("1", "10*-2")),
+    ("[ppth]", false, Atom::Derived("1", "10*-3")),
+    ("[ppm]", false, Atom::Derived("1", "10*-6")),
+    ("[ppb]", false, Atom::Derived("1", "10*-9")),
+    ("mol", true, Atom::Derived("6.0221367", "10*23")),
+    ("sr", true, Atom::Derived("1", "rad2")),
+    // SI derived units
+    ("Hz", true, Atom::Derived("1", "s-1")),
+    ("N", true, Atom::Derived("1", "kg.m/s2")),
+    ("Pa", true, Atom::Derived("1", "N/m2")),
+    ("J", true, Atom::Derived("1", "N.m")),
+    ("W", true, Atom::Derived("1", "J/s")),
+    ("A", true, Atom::Derived("1", "C/s")),
+    ("V", true, Atom::Derived("1", "J/C")),
+    ("F", true, Atom::Derived("1", "C/V")),
+    ("Ohm", true, Atom::Derived("1", "V/A")),
+    ("S", true, Atom::Derived("1", "Ohm-1")),
+    ("Wb", true, Atom::Derived("1", "V.s")),
+    ("T", true, Atom::Derived("1", "Wb/m2")),
+    ("H", true, Atom::Derived("1", "Wb/A")),
+    ("lm", true, Atom::Derived("1", "cd.sr")),
+    ("lx", true, Atom::Derived("1", "lm/m2")),
+    ("Bq", true, Atom::Derived("1", "s-1")),
+    ("Gy", true, Atom::Derived("1", "J/kg")),
+    ("Sv", true, Atom::Derived("1", "J/kg")),
+    ("kat", true, Atom::Derived("1", "mol/s")),
+    ("Cel", true, Atom::Special("273.15", "1", "K")),
+    // Other metric units
+    ("l", true, Atom::Derived("1", "dm3")),
+    ("L", true, Atom::Derived("1", "l")),
+    ("ar", true, Atom::Derived("100", "m2")),
+    ("t", true, Atom::Derived("1000", "kg")),
+    ("bar", true, Atom::Derived("100000", "Pa")),
+    ("u", true, Atom::Derived("1.6605402", "10*-24.g")),
+    ("eV", true, Atom::Derived("1.60217733", "10*-19.J")),
+    ("cal", true, Atom::Derived("4.184", "J")),
+    ("eq", true, Atom::Derived("1", "mol")),
+    ("osm", true, Atom::Derived("1", "mol")),
+    ("U", true, Atom::Derived("1", "umol/min")),
+    ("g%", true, Atom::Derived("1", "g/dl")),
+    ("m[Hg]", true, Atom::Derived("133.3220", "kPa")),
+    ("m[H2O]", true, Atom::Derived("9.80665", "kPa")),
+    ("[g]", false, Atom::Derived("9.80665", "m/s2")),
+    // Time
+    ("min", false, Atom::Derived("60", "s")),
+    ("h", false, Atom::Derived("60", "min")),
+    ("d", false, Atom::Derived("24", "h")),
+    ("wk", false, Atom::Derived("7", "d")),
+    ("a_t", false, Atom::Derived("365.24219", "d")),
+    ("a_j", false, Atom::Derived("365.25", "d")),
+    ("a_g", false, Atom::Derived("365.2425", "d")),
+    ("a", false, Atom::Derived("1", "a_j")),
+    ("mo_s", false, Atom::Derived("29.53059", "d")),
+    ("mo_j", false, Atom::Derived("1", "a_j/12")),
+    ("mo_g", false, Atom::Derived("1", "a_g/12")),
+    ("mo", false, Atom::Derived("1", "mo_j")),
+    // International customary units
+    ("[in_i]", false, Atom::Derived("2.54", "cm")),
+    ("[ft_i]", false, Atom::Derived("12", "[in_i]")),
+    ("[yd_i]", false, Atom::Derived("3", "[ft_i]")),
+    ("[mi_i]", false, Atom::Derived("5280", "[ft_i]")),
+    ("[gr]", false, Atom::Derived("64.79891", "mg")),
+    ("[lb_av]", false, Atom::Derived("7000", "[gr]")),
+    ("[oz_av]", false, Atom::Derived("1", "[lb_av]/16")),
+    ("[lbf_av]", false, Atom::Derived("1", "[lb_av].[g]")),
+    ("[psi]", false, Atom::Derived("1", "[lbf_av]/[in_i]2")),
+    ("[gal_us]", false, Atom::Derived("231", "[in_i]3")),
+    ("[qt_us]", false, Atom::Derived("1", "[gal_us]/4")),
+    ("[pt_us]", false, Atom::Derived("1", "[qt_us]/2")),
+    ("[foz_us]", false, Atom::Derived("1", "[pt_us]/16")),
+    ("[tbs_us]", false, Atom::Derived("1", "[foz_us]/2")),
+    ("[tsp_us]", false, Atom::Derived("1", "[tbs_us]/3")),
+    ("[drp]", false, Atom::Derived("1", "ml/20")),
+    ("[degF]", false, Atom::Special("459.67", "5/9", "K")),
+    // Arbitrary units
+    ("[iU]", true, Atom::Arbitrary),
+    ("[IU]", true, Atom::Derived("1", "[iU]")),
+    ("[arb'U]", false, Atom::Arbitrary),
+    ("[USP'U]", false, Atom::Arbitrary),
+];
+
+/// Metric prefixes and their factors, two-letter prefixes first
+const PREFIXES: &[(&str, &str)] = &[
+    ("da", "10"),
+    ("Y", "1000000000000000000000000"),
+    ("Z", "1000000000000000000000"),
+    ("E", "1000000000000000000"),
+    ("P", "1000000000000000"),
+    ("T", "1000000000000"),
+    ("G", "1000000000"),
+    ("M", "1000000"),
+    ("k", "1000"),
+    ("h", "100"),
+    ("d", "0.1"),
+    ("c", "0.01"),
+    ("m", "0.001"),
+    ("u", "0.000001"),
+    ("n", "0.000000001"),
+    ("p", "0.000000000001"),
+    ("f", "0.000000000000001"),
+    ("a", "0.000000000000000001"),
+    ("z", "0.000000000000000000001"),
+    ("y", "0.000000000000000000000001"),
+];
+
+/// Canonical form of a unit symbol, trying atoms before prefixed metric atoms
+fn resolve_symbol(symbol: &str) -> Option<Result<CanonicalUnit>> {
+    if let Some(unit) = resolve_atom(symbol, false) {
+        return Some(unit);
+    }
+    PREFIXES.iter().find_map(|(prefix, factor)| {
+        let atom = symbol.strip_prefix(prefix)?;
+        let unit = resolve_atom(atom, true)?;
+        Some(unit.and_then(|unit| CanonicalUnit::scalar(decimal(factor)).multiply(&unit)))
+    })
+}
+
+fn resolve_atom(code: &str, prefixed: bool) -> Option<Result<CanonicalUnit>> {
+    let (code, _, atom) = ATOMS
+        .iter()
+        .find(|(atom, metric, _)| *atom == code && (*metric || !prefixed))?;
+    Some(match atom {
+        Atom::Base | Atom::Arbitrary => Ok(CanonicalUnit::base(code)),
+        Atom::Derived(factor, unit) => {
+            parse_unit(unit).and_then(|unit| CanonicalUnit::scalar(decimal(factor)).multiply(&unit))
+        }
+        Atom::Special(offset, factor, unit) => parse_unit(factor)
+            .and_then(|factor| factor.multiply(&parse_unit(unit)?))
+            .map(|unit| CanonicalUnit {
+                offset: decimal(offset),
+                ..unit
+            }),
+    })
+}
+
+fn decimal(value: &str) -> Decimal {
+    value
+        .parse()
+        .expect("unit table factors are valid decimals")
+}
+
+fn checked(value: Option<Decimal>) -> Result<Decimal> {
+    value.ok_or_else(|| ModelError::validation_error("UCUM unit factor is out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_unit_parsing() {
+        assert_eq!(canonicalize("mg/dL").unwrap().dimension(), "g.m-3");
+        assert_eq!(canonicalize("kg.m/s2").unwrap(), canonicalize("N").unwrap());
+        assert_eq!(canonicalize("10*3/uL").unwrap().dimension(), "m-3");
+        assert_eq!(canonicalize("mm[Hg]").unwrap().dimension(), "g.m-1.s-2");
+        assert_eq!(canonicalize("{beats}/min").unwrap().dimension(), "s-1");
+        assert_eq!(canonicalize("").unwrap().dimension(), "1");
+        assert!(
+            canonicalize("cd")
+                .unwrap()
+                .is_commensurable(&canonicalize("lm/sr").unwrap())
+        );
+        assert!(canonicalize("[iU]/L").unwrap().dimension().contains("[iU]"));
+
+        for invalid in ["furlong", "mg/", "(mg", "[in_i", "km{x", "da[in_i]"] {
+            assert!(canonicalize(invalid).is_err(), "{invalid}");
+        }
+
+        // Parse errors are cached with the units, which share their dimensions
+        assert!(
+            CANONICAL_UNITS
+                .pin()
+                .get("furlong")
+                .is_some_and(|parsed| parsed.is_err())
+        );
+        assert_eq!(
+            canonicalize("furlong").unwrap_err().to_string(),
+            "Validation error: Unknown UCUM unit 'furlong' in 'furlong'"
+        );
+        assert!(Arc::ptr_eq(
+            &canonicalize("mg/dL").unwrap().dimensions,
+            &canonicalize("mg/dL").unwrap().dimensions
+        ));
+
+        // Exponents are applied at once and bounded
+        assert_eq!(canonicalize("cm3").unwrap(), canonicalize("mL").unwrap());
+        assert_eq!(canonicalize("m-2").unwrap().dimension(), "m-2");
+        assert_eq!(canonicalize("m0").unwrap().dimension(), "1");
+        for invalid in ["m2147483647", "m-2147483648", "10*99"] {
+            assert!(canonicalize(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_conversion() {
+        assert_eq!(convert(dec("1000"), "mg", "g").unwrap(), dec("1"));
+        assert_eq!(convert(dec("1"), "[lb_av]", "g").unwrap(), dec("453.59237"));
+        assert_eq!(convert(dec("60"), "/min", "Hz").unwrap(), dec("1"));
+        assert_eq!(convert(dec("37"), "Cel", "K").unwrap(), dec("310.15"));
+        assert_eq!(convert(dec("212"), "[degF]", "Cel").unwrap(), dec("100"));
+        assert_eq!(convert(dec("1"), "a", "mo").unwrap(), dec("12"));
+        assert_eq!(convert(dec("1"), "year", "months").unwrap(), dec("12"));
+        assert!(convert(dec("1"), "mg", "mL").is_err());
+        assert!(convert(dec("1"), "year", "a").is_err());
+    }
+
+    #[test]
+    fn test_quantity_comparison() {
+        assert_eq!(
+            compare_quantities(dec("1000"), "mg", dec("1"), "g"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_quantities(dec("2"), "g", dec("1500"), "mg"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare_quantities(dec("1"), "g", dec("1"), "m"), None);
+
+        // Unknown units only compare with themselves
+        assert_eq!(
+            compare_quantities(dec("2.0"), "[CFU]", dec("2"), "[CFU]"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_quantities(dec("1"), "tablet", dec("2"), "tablet"),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            compare_quantities(dec("1"), "tablet", dec("1"), "[CFU]"),
+            None
+        );
+        assert!(quantities_equivalent(
+            dec("5"),
+            "[in_us]",
+            dec("5"),
+            "[in_us]"
+        ));
+
+        // Calendar durations above seconds are only equivalent to UCUM durations
+        assert_eq!(compare_quantities(dec("1"), "year", dec("1"), "a"), None);
+        assert!(quantities_equivalent(dec("1"), "year", dec("1"), "a"));
+        assert_eq!(
+            compare_quantities(dec("1"), "second", dec("1000"), "ms"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            compare_quantities(dec("1"), "week", dec("7"), "days"),
+            Some(Ordering::Equal)
+        );
+
+        assert_eq!(
+            comparison_key(dec("1000"), "mg"),
+            comparison_key(dec("1.0"), "g")
+        );
+        assert_ne!(
+            comparison_key(dec("1"), "furlong"),
+            comparison_key(dec("1"), "g")
+        );
+    }
+}
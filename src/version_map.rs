@@ -0,0 +1,348 @@
+//! Cross-version mapping of types and element paths
+//!
+//! This module maps element paths between FHIR releases (R4, R4B, R5, R6), for
+//! example `MedicationRequest.medicationCodeableConcept` in R4 to
+//! `MedicationRequest.medication.concept` in R5. Mappings are applied one
+//! release at a time using bundled mapping data, so they work offline.
+
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::error::{ModelError, Result};
+use crate::provider::FhirVersion;
+
+/// Releases in publication order; mappings are defined between neighbours
+const RELEASE_ORDER: [FhirVersion; 4] = [
+    FhirVersion::R4,
+    FhirVersion::R4B,
+    FhirVersion::R5,
+    FhirVersion::R6,
+];
+
+static BUNDLED: LazyLock<VersionMap> = LazyLock::new(|| {
+    let data: JsonValue = serde_json::from_str(include_str!("../data/version_map.json"))
+        .expect("bundled version map is valid JSON");
+    VersionMap::from_json(&data).expect("bundled version map is well formed")
+});
+
+/// Result of mapping an element to another FHIR version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementMapping {
+    /// Type name in the target version (resources can be renamed)
+    pub type_name: String,
+    /// Full element path in the target version, or `None` if there is no equivalent
+    pub path: Option<String>,
+    /// Element type in the target version when it changed (e.g. `CodeableReference`)
+    pub element_type: Option<String>,
+    /// Whether data may be lost by the mapping
+    pub lossy: bool,
+    /// Notes attached to the applied mapping rules
+    pub notes: Vec<String>,
+}
+
+impl ElementMapping {
+    /// Whether the element has no equivalent in the target version
+    pub fn is_removed(&self) -> bool {
+        self.path.is_none()
+    }
+}
+
+/// Mapping of a type between two neighbouring releases
+#[derive(Debug, Clone)]
+struct TypeRule {
+    target: Option<String>,
+    lossy: bool,
+    comment: Option<String>,
+}
+
+/// Mapping of an element path (relative to its type) between two neighbouring releases
+#[derive(Debug, Clone)]
+struct ElementRule {
+    path: String,
+    target: Option<String>,
+    element_type: Option<String>,
+    lossy: bool,
+    comment: Option<String>,
+}
+
+/// Rules for a single step from one release to a neighbouring one
+#[derive(Debug, Clone, Default)]
+struct MappingStep {
+    types: HashMap<String, TypeRule>,
+    /// Type name -> element rules
+    elements: HashMap<String, Vec<ElementRule>>,
+}
+
+impl MappingStep {
+    fn apply(&self, mapping: &mut ElementMapping, relative: &mut Option<String>) {
+        if let Some(path) = relative.as_deref()
+            && let Some(rule) = self.element_rule(&mapping.type_name, path)
+        {
+            let remainder = &path[rule.path.len()..];
+            if remainder.is_empty() && rule.element_type.is_some() {
+                mapping.element_type.clone_from(&rule.element_type);
+            }
+            let mapped = rule
+                .target
+                .as_ref()
+                .map(|target| format!("{target}{remainder}"));
+            *relative = mapped;
+            mapping.lossy |= rule.lossy;
+            mapping.notes.extend(rule.comment.clone());
+        }
+
+        if let Some(rule) = self.types.get(&mapping.type_name) {
+            match &rule.target {
+                Some(target) => mapping.type_name.clone_from(target),
+                None => *relative = None,
+            }
+            mapping.lossy |= rule.lossy;
+            mapping.notes.extend(rule.comment.clone());
+        }
+    }
+
+    /// Most specific element rule covering a path (`component` covers `component.code`)
+    fn element_rule(&self, type_name: &str, path: &str) -> Option<&ElementRule> {
+        self.elements
+            .get(type_name)?
+            .iter()
+            .filter(|rule| {
+                path.strip_prefix(rule.path.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .max_by_key(|rule| rule.path.len())
+    }
+}
+
+/// Cross-version mapping table
+#[derive(Debug, Clone, Default)]
+pub struct VersionMap {
+    steps: HashMap<(FhirVersion, FhirVersion), MappingStep>,
+}
+
+impl VersionMap {
+    /// Mapping table bundled with this crate
+    pub fn bundled() -> &'static VersionMap {
+        &BUNDLED
+    }
+
+    /// Parse a mapping table
+    ///
+    /// The table contains `steps` between neighbouring releases, each with
+    /// `types` rules (`source` -> `target`) and `elements` rules (`type`, `path`
+    /// -> `target`, `targetType`). A `null` target means there is no equivalent.
+    pub fn from_json(value: &JsonValue) -> Result<Self> {
+        let mut map = Self::default();
+        for step in json_array(value, "steps") {
+            let version = |field: &str| {
+                step.get(field)
+                    .and_then(|v| v.as_str())
+                    .map(FhirVersion::from_version_str)
+                    .ok_or_else(|| {
+                        ModelError::schema_load_error(format!(
+                            "Version map step is missing '{field}'"
+                        ))
+                    })
+            };
+            let key = (version("from")?, version("to")?);
+
+            let mut rules = MappingStep::default();
+            for rule in json_array(step, "types") {
+                let source = required_str(rule, "source")?;
+                rules.types.insert(
+                    source,
+                    TypeRule {
+                        target: optional_str(rule, "target"),
+                        lossy: rule.get("lossy").and_then(|v| v.as_bool()).unwrap_or(false),
+                        comment: optional_str(rule, "comment"),
+                    },
+                );
+            }
+            for rule in json_array(step, "elements") {
+                rules
+                    .elements
+                    .entry(required_str(rule, "type")?)
+                    .or_default()
+                    .push(ElementRule {
+                        path: required_str(rule, "path")?,
+                        target: optional_str(rule, "target"),
+                        element_type: optional_str(rule, "targetType"),
+                        lossy: rule.get("lossy").and_then(|v| v.as_bool()).unwrap_or(false),
+                        comment: optional_str(rule, "comment"),
+                    });
+            }
+            map.steps.insert(key, rules);
+        }
+        Ok(map)
+    }
+
+    /// Map an element of a type to another FHIR version
+    ///
+    /// `path` may be relative to the type (`medication[x]`) or include it
+    /// (`MedicationRequest.medication[x]`); the mapped path always includes the
+    /// type. Elements without a mapping rule map to themselves. Mapping to or
+    /// from a custom version is not supported and yields the identity mapping.
+    pub fn map_element(
+        &self,
+        from: &FhirVersion,
+        to: &FhirVersion,
+        type_name: &str,
+        path: &str,
+    ) -> ElementMapping {
+        let relative = match path.strip_prefix(type_name) {
+            Some("") => "",
+            Some(rest) if rest.starts_with('.') => &rest[1..],
+            _ => path,
+        };
+
+        let mut mapping = ElementMapping {
+            type_name: type_name.to_string(),
+            path: None,
+            element_type: None,
+            lossy: false,
+            notes: Vec::new(),
+        };
+        let mut relative = Some(relative.to_string());
+
+        for step in release_steps(from, to) {
+            if relative.is_none() {
+                break;
+            }
+            if let Some(rules) = self.steps.get(&step) {
+                rules.apply(&mut mapping, &mut relative);
+            }
+        }
+
+        mapping.path = relative.map(|relative| {
+            if relative.is_empty() {
+                mapping.type_name.clone()
+            } else {
+                format!("{}.{relative}", mapping.type_name)
+            }
+        });
+        mapping
+    }
+
+    /// Map a type name to another FHIR version, or `None` if it has no equivalent
+    pub fn map_type(
+        &self,
+        from: &FhirVersion,
+        to: &FhirVersion,
+        type_name: &str,
+    ) -> Option<String> {
+        self.map_element(from, to, type_name, "").path
+    }
+}
+
+impl FhirVersion {
+    /// Map an element of a type in this version to another version using the bundled table
+    pub fn map_element(&self, target: &FhirVersion, type_name: &str, path: &str) -> ElementMapping {
+        VersionMap::bundled().map_element(self, target, type_name, path)
+    }
+}
+
+/// Neighbouring release pairs to walk from one version to another
+fn release_steps(from: &FhirVersion, to: &FhirVersion) -> Vec<(FhirVersion, FhirVersion)> {
+    let position = |version: &FhirVersion| RELEASE_ORDER.iter().position(|v| v == version);
+    let (Some(from), Some(to)) = (position(from), position(to)) else {
+        return Vec::new();
+    };
+
+    if from <= to {
+        (from..to)
+            .map(|i| (RELEASE_ORDER[i].clone(), RELEASE_ORDER[i + 1].clone()))
+            .collect()
+    } else {
+        (to + 1..=from)
+            .rev()
+            .map(|i| (RELEASE_ORDER[i].clone(), RELEASE_ORDER[i - 1].clone()))
+            .collect()
+    }
+}
+
+fn json_array<'a>(value: &'a JsonValue, field: &str) -> impl Iterator<Item = &'a JsonValue> {
+    value
+        .get(field)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+}
+
+fn required_str(value: &JsonValue, field: &str) -> Result<String> {
+    optional_str(value, field).ok_or_else(|| {
+        ModelError::schema_load_error(format!("Version map rule is missing '{field}'"))
+    })
+}
+
+fn optional_str(value: &JsonValue, field: &str) -> Option<String> {
+    value.get(field).and_then(|v| v.as_str()).map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_medication_becomes_codeable_reference() {
+        let mapping = FhirVersion::R4.map_element(
+            &FhirVersion::R5,
+            "MedicationRequest",
+            "MedicationRequest.medicationCodeableConcept.coding",
+        );
+        assert_eq!(
+            mapping.path.as_deref(),
+            Some("MedicationRequest.medication.concept.coding")
+        );
+        assert!(!mapping.lossy);
+
+        let choice =
+            FhirVersion::R4.map_element(&FhirVersion::R5, "MedicationRequest", "medication[x]");
+        assert_eq!(choice.path.as_deref(), Some("MedicationRequest.medication"));
+        assert_eq!(choice.element_type.as_deref(), Some("CodeableReference"));
+
+        let back = FhirVersion::R5.map_element(&FhirVersion::R4, "MedicationRequest", "medication");
+        assert_eq!(
+            back.path.as_deref(),
+            Some("MedicationRequest.medication[x]")
+        );
+        assert!(back.lossy);
+    }
+
+    #[test]
+    fn test_unmapped_and_removed_elements() {
+        let identity = FhirVersion::R4.map_element(&FhirVersion::R5, "Patient", "name.family");
+        assert_eq!(identity.path.as_deref(), Some("Patient.name.family"));
+        assert!(!identity.lossy && identity.element_type.is_none());
+
+        let removed =
+            FhirVersion::R5.map_element(&FhirVersion::R4, "Observation", "valueAttachment");
+        assert!(removed.is_removed());
+        assert!(removed.lossy);
+
+        let map = VersionMap::bundled();
+        assert_eq!(
+            map.map_type(&FhirVersion::R4, &FhirVersion::R5, "DeviceUseStatement")
+                .as_deref(),
+            Some("DeviceUsage")
+        );
+        assert_eq!(
+            map.map_type(
+                &FhirVersion::R4,
+                &FhirVersion::R4B,
+                "EffectEvidenceSynthesis"
+            ),
+            None
+        );
+        let renamed = map.map_element(
+            &FhirVersion::R4,
+            &FhirVersion::R5,
+            "DeviceUseStatement",
+            "device",
+        );
+        assert_eq!(
+            renamed.path.as_deref(),
+            Some("DeviceUsage.device.reference")
+        );
+    }
+}